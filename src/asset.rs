@@ -11,7 +11,7 @@ use stellar_strkey::{
     Strkey::{self, PublicKeyEd25519},
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Asset {
     pub code: String,
     pub issuer: Option<String>,
@@ -286,16 +286,9 @@ impl AssetBehavior for Asset {
     }
 }
 
-impl ToString for Asset {
-    fn to_string(&self) -> String {
-        if self.is_native() {
-            return "native".to_string();
-        }
-
-        match (self.get_code(), self.get_issuer()) {
-            (Some(code), Some(issuer)) => format!("{}:{}", code, issuer),
-            _ => "".to_string(),
-        }
+impl std::fmt::Display for Asset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_string_asset())
     }
 }
 
@@ -424,7 +417,7 @@ mod tests {
 
         match xdr {
             xdr::Asset::CreditAlphanum4(x) => {
-                assert_eq!(hex::encode(x.asset_code), hex::encode("USD\0".to_string()))
+                assert_eq!(hex::encode(x.asset_code), hex::encode("USD\0"))
             }
             _ => panic!("Error"),
         }
@@ -432,7 +425,7 @@ mod tests {
         let xdr = asset.to_change_trust_xdr_object();
         match xdr {
             xdr::ChangeTrustAsset::CreditAlphanum4(x) => {
-                assert_eq!(hex::encode(x.asset_code), hex::encode("USD\0".to_string()))
+                assert_eq!(hex::encode(x.asset_code), hex::encode("USD\0"))
             }
             _ => panic!("Error"),
         }
@@ -440,7 +433,7 @@ mod tests {
         let xdr = asset.to_trust_line_xdr_object();
         match xdr {
             xdr::TrustLineAsset::CreditAlphanum4(x) => {
-                assert_eq!(hex::encode(x.asset_code), hex::encode("USD\0".to_string()))
+                assert_eq!(hex::encode(x.asset_code), hex::encode("USD\0"))
             }
             _ => panic!("Error"),
         }
@@ -454,7 +447,7 @@ mod tests {
 
         match xdr {
             xdr::Asset::CreditAlphanum4(x) => {
-                assert_eq!(hex::encode(x.asset_code), hex::encode("BART".to_string()))
+                assert_eq!(hex::encode(x.asset_code), hex::encode("BART"))
             }
             _ => panic!("Error"),
         }
@@ -462,7 +455,7 @@ mod tests {
         let xdr = asset.to_change_trust_xdr_object();
         match xdr {
             xdr::ChangeTrustAsset::CreditAlphanum4(x) => {
-                assert_eq!(hex::encode(x.asset_code), hex::encode("BART".to_string()))
+                assert_eq!(hex::encode(x.asset_code), hex::encode("BART"))
             }
             _ => panic!("Error"),
         }
@@ -470,7 +463,7 @@ mod tests {
         let xdr = asset.to_trust_line_xdr_object();
         match xdr {
             xdr::TrustLineAsset::CreditAlphanum4(x) => {
-                assert_eq!(hex::encode(x.asset_code), hex::encode("BART".to_string()))
+                assert_eq!(hex::encode(x.asset_code), hex::encode("BART"))
             }
             _ => panic!("Error"),
         }
@@ -485,7 +478,7 @@ mod tests {
         match xdr {
             xdr::Asset::CreditAlphanum12(x) => assert_eq!(
                 hex::encode(x.asset_code),
-                hex::encode("12345\0\0\0\0\0\0\0".to_string())
+                hex::encode("12345\0\0\0\0\0\0\0")
             ),
             _ => panic!("Error"),
         }
@@ -494,7 +487,7 @@ mod tests {
         match xdr {
             xdr::ChangeTrustAsset::CreditAlphanum12(x) => assert_eq!(
                 hex::encode(x.asset_code),
-                hex::encode("12345\0\0\0\0\0\0\0".to_string())
+                hex::encode("12345\0\0\0\0\0\0\0")
             ),
             _ => panic!("Error"),
         }
@@ -503,7 +496,7 @@ mod tests {
         match xdr {
             xdr::TrustLineAsset::CreditAlphanum12(x) => assert_eq!(
                 hex::encode(x.asset_code),
-                hex::encode("12345\0\0\0\0\0\0\0".to_string())
+                hex::encode("12345\0\0\0\0\0\0\0")
             ),
             _ => panic!("Error"),
         }
@@ -516,28 +509,25 @@ mod tests {
         let xdr = asset.to_xdr_object();
 
         match xdr {
-            xdr::Asset::CreditAlphanum12(x) => assert_eq!(
-                hex::encode(x.asset_code),
-                hex::encode("123456789012".to_string())
-            ),
+            xdr::Asset::CreditAlphanum12(x) => {
+                assert_eq!(hex::encode(x.asset_code), hex::encode("123456789012"))
+            }
             _ => panic!("Error"),
         }
 
         let xdr = asset.to_change_trust_xdr_object();
         match xdr {
-            xdr::ChangeTrustAsset::CreditAlphanum12(x) => assert_eq!(
-                hex::encode(x.asset_code),
-                hex::encode("123456789012".to_string())
-            ),
+            xdr::ChangeTrustAsset::CreditAlphanum12(x) => {
+                assert_eq!(hex::encode(x.asset_code), hex::encode("123456789012"))
+            }
             _ => panic!("Error"),
         }
 
         let xdr = asset.to_trust_line_xdr_object();
         match xdr {
-            xdr::TrustLineAsset::CreditAlphanum12(x) => assert_eq!(
-                hex::encode(x.asset_code),
-                hex::encode("123456789012".to_string())
-            ),
+            xdr::TrustLineAsset::CreditAlphanum12(x) => {
+                assert_eq!(hex::encode(x.asset_code), hex::encode("123456789012"))
+            }
             _ => panic!("Error"),
         }
     }
@@ -0,0 +1,320 @@
+//! Channel accounts for submitting many transactions from one funding account in parallel.
+//!
+//! Each transaction uses a leased channel account as its source, so it consumes the channel's
+//! sequence number, while the operations keep the funding account as their source. Such
+//! transactions must be signed by both the channel and the funding account.
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::account::{Account, AccountBehavior};
+use crate::transaction::Transaction;
+use crate::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+use crate::xdr;
+use std::str::FromStr;
+
+struct Channels {
+    idle: Mutex<Vec<Account>>,
+    released: Condvar,
+}
+
+impl Channels {
+    // Pushing or popping an account cannot leave the list half-updated, so a panic in another
+    // thread holding the lock does not make it unusable
+    fn idle(&self) -> MutexGuard<'_, Vec<Account>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A thread-safe pool of channel accounts. Clones share the same channels.
+#[derive(Clone)]
+pub struct ChannelPool {
+    funding_account: xdr::MuxedAccount,
+    network_passphrase: String,
+    channels: Arc<Channels>,
+}
+
+/// A channel account taken from a `ChannelPool`, returned to it when dropped.
+pub struct ChannelLease {
+    pool: ChannelPool,
+    account: Option<Account>,
+}
+
+// Define a trait for ChannelPool behavior
+pub trait ChannelPoolBehavior {
+    fn new(funding_account: &str, network: &str, channels: Vec<Account>) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn lease(&self) -> ChannelLease;
+    fn try_lease(&self) -> Option<ChannelLease>;
+    fn idle(&self) -> usize;
+}
+
+// Define a trait for ChannelLease behavior
+pub trait ChannelLeaseBehavior {
+    fn account(&self) -> &Account;
+    fn build(
+        &mut self,
+        fee: u32,
+        timeout_seconds: i64,
+        operations: Vec<xdr::Operation>,
+    ) -> Result<Transaction, String>;
+    fn resync(&mut self, sequence: &str) -> Result<(), String>;
+}
+
+impl ChannelPoolBehavior for ChannelPool {
+    fn new(funding_account: &str, network: &str, channels: Vec<Account>) -> Result<Self, String> {
+        let funding_account = xdr::MuxedAccount::from_str(funding_account)
+            .map_err(|_| "invalid funding account".to_string())?;
+        if channels.is_empty() {
+            return Err("a channel pool needs at least one channel".to_string());
+        }
+
+        Ok(Self {
+            funding_account,
+            network_passphrase: network.to_string(),
+            channels: Arc::new(Channels {
+                idle: Mutex::new(channels),
+                released: Condvar::new(),
+            }),
+        })
+    }
+
+    /// Takes an idle channel, waiting for one to be released if all are leased.
+    fn lease(&self) -> ChannelLease {
+        let mut idle = self.channels.idle();
+        loop {
+            if let Some(account) = idle.pop() {
+                return ChannelLease {
+                    pool: self.clone(),
+                    account: Some(account),
+                };
+            }
+            idle = self
+                .channels
+                .released
+                .wait(idle)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Takes an idle channel without waiting.
+    fn try_lease(&self) -> Option<ChannelLease> {
+        let account = self.channels.idle().pop()?;
+        Some(ChannelLease {
+            pool: self.clone(),
+            account: Some(account),
+        })
+    }
+
+    fn idle(&self) -> usize {
+        self.channels.idle().len()
+    }
+}
+
+impl ChannelLeaseBehavior for ChannelLease {
+    fn account(&self) -> &Account {
+        self.account.as_ref().unwrap()
+    }
+
+    /// Builds a transaction sourced from the channel. Operations without a source account get
+    /// the funding account. The channel's sequence number is incremented.
+    fn build(
+        &mut self,
+        fee: u32,
+        timeout_seconds: i64,
+        operations: Vec<xdr::Operation>,
+    ) -> Result<Transaction, String> {
+        if operations.is_empty() {
+            return Err("a transaction needs at least one operation".to_string());
+        }
+        if operations.len() > 100 {
+            return Err("a transaction can have at most 100 operations".to_string());
+        }
+        fee.checked_mul(operations.len() as u32)
+            .ok_or("fee overflows u32")?;
+        let funding_account = self.pool.funding_account.clone();
+        let network = self.pool.network_passphrase.clone();
+        let account = self.account.as_mut().unwrap();
+
+        let mut builder = TransactionBuilder::new(account, &network, None);
+        builder.fee(fee).set_timeout(timeout_seconds)?;
+        for mut op in operations {
+            op.source_account
+                .get_or_insert_with(|| funding_account.clone());
+            builder.add_operation(op);
+        }
+        Ok(builder.build())
+    }
+
+    /// Resets the channel's sequence number, e.g. to the value on the network after a failed
+    /// submission left the local one ahead.
+    fn resync(&mut self, sequence: &str) -> Result<(), String> {
+        let account = self.account.as_mut().unwrap();
+        *account = Account::new(&account.account_id(), sequence)?;
+        Ok(())
+    }
+}
+
+impl Drop for ChannelLease {
+    fn drop(&mut self) {
+        if let Some(account) = self.account.take() {
+            self.pool.channels.idle().push(account);
+            self.pool.channels.released.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::{Asset, AssetBehavior};
+    use crate::keypair::{Keypair, KeypairBehavior};
+    use crate::network::{NetworkPassphrase, Networks};
+    use crate::operation::Operation;
+    use std::thread;
+
+    const FUNDING: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const DESTINATION: &str = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+
+    fn pool(channels: usize) -> ChannelPool {
+        let channels = (0..channels)
+            .map(|i| {
+                Account::new(
+                    &Keypair::random().unwrap().public_key(),
+                    &(i * 100).to_string(),
+                )
+                .unwrap()
+            })
+            .collect();
+        ChannelPool::new(FUNDING, Networks::testnet(), channels).unwrap()
+    }
+
+    fn payment() -> xdr::Operation {
+        Operation::new()
+            .payment(DESTINATION, &Asset::native(), 10)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_lease_builds_from_channel_with_funding_operations() {
+        let pool = pool(1);
+        let mut lease = pool.lease();
+        let channel = lease.account().account_id();
+        let sequence: i64 = lease.account().sequence_number().parse().unwrap();
+
+        let tx = lease.build(100, 30, vec![payment(), payment()]).unwrap();
+
+        assert_eq!(tx.source.as_deref(), Some(channel.as_str()));
+        assert_eq!(tx.sequence, Some((sequence + 1).to_string()));
+        let funding = xdr::MuxedAccount::from_str(FUNDING).unwrap();
+        for op in tx.operations.unwrap() {
+            assert_eq!(op.source_account, Some(funding.clone()));
+        }
+        assert_eq!(
+            lease.account().sequence_number(),
+            (sequence + 1).to_string()
+        );
+    }
+
+    #[test]
+    fn test_keeps_explicit_operation_sources() {
+        let pool = pool(1);
+        let op = Operation::with_source(DESTINATION)
+            .unwrap()
+            .bump_sequence(1)
+            .unwrap();
+
+        let tx = pool.lease().build(100, 0, vec![op.clone()]).unwrap();
+
+        assert_eq!(tx.operations.unwrap()[0], op);
+        assert!(pool.lease().build(100, 0, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unbuildable_transactions() {
+        let pool = pool(1);
+        let mut lease = pool.lease();
+        let sequence = lease.account().sequence_number();
+
+        assert_eq!(
+            lease.build(100, 0, vec![payment(); 101]).unwrap_err(),
+            "a transaction can have at most 100 operations"
+        );
+        assert_eq!(
+            lease
+                .build(u32::MAX, 0, vec![payment(), payment()])
+                .unwrap_err(),
+            "fee overflows u32"
+        );
+        assert_eq!(lease.account().sequence_number(), sequence);
+        assert!(lease.build(100, 0, vec![payment(); 100]).is_ok());
+    }
+
+    #[test]
+    fn test_channels_return_on_drop() {
+        let pool = pool(2);
+        let first = pool.lease();
+        let second = pool.try_lease().unwrap();
+        assert_ne!(first.account().account_id(), second.account().account_id());
+        assert!(pool.try_lease().is_none());
+        assert_eq!(pool.idle(), 0);
+
+        drop(first);
+        assert_eq!(pool.idle(), 1);
+        assert!(pool.try_lease().is_some());
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[test]
+    fn test_poisoned_lock_keeps_channels() {
+        let pool = pool(2);
+        let lease = pool.lease();
+        let poisoning = pool.clone();
+        thread::spawn(move || {
+            let _idle = poisoning.channels.idle.lock().unwrap();
+            panic!("poison the lock");
+        })
+        .join()
+        .unwrap_err();
+        assert!(pool.channels.idle.is_poisoned());
+
+        assert_eq!(pool.idle(), 1);
+        drop(lease);
+        assert_eq!(pool.idle(), 2);
+        let _first = pool.lease();
+        assert!(pool.try_lease().is_some());
+    }
+
+    #[test]
+    fn test_resync_persists_after_release() {
+        let pool = pool(1);
+        {
+            let mut lease = pool.lease();
+            lease.build(100, 0, vec![payment()]).unwrap();
+            lease.resync("41").unwrap();
+            assert!(lease.resync("not a number").is_err());
+        }
+
+        assert_eq!(pool.lease().account().sequence_number(), "41");
+    }
+
+    #[test]
+    fn test_concurrent_leases_use_distinct_sequences() {
+        let pool = pool(3);
+        let handles: Vec<_> = (0..12)
+            .map(|_| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    let mut lease = pool.lease();
+                    let tx = lease.build(100, 0, vec![payment()]).unwrap();
+                    (tx.source.unwrap(), tx.sequence.unwrap())
+                })
+            })
+            .collect();
+
+        let mut built: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        built.sort();
+        built.dedup();
+        assert_eq!(built.len(), 12);
+        assert_eq!(pool.idle(), 3);
+    }
+}
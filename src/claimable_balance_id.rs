@@ -0,0 +1,187 @@
+//! `ClaimableBalanceId` identifies a claimable balance entry on the ledger.
+//!
+//! The id of a balance created by a `create_claimable_balance` operation is derived from the
+//! transaction source account, its sequence number and the index of the operation, so it can be
+//! computed offline before the transaction is submitted.
+use std::str::FromStr;
+
+use crate::hashing::{HashingBehavior, Sha256Hasher};
+use crate::xdr;
+use crate::xdr::{ReadXdr, WriteXdr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimableBalanceId {
+    hash: [u8; 32],
+}
+
+// Define a trait for ClaimableBalanceId behavior
+pub trait ClaimableBalanceIdBehavior {
+    fn new(source_account: &str, sequence: &str, op_index: u32) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn from_string(balance_id: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn from_xdr_object(balance_id: &xdr::ClaimableBalanceId) -> Self
+    where
+        Self: Sized;
+    fn to_xdr_object(&self) -> xdr::ClaimableBalanceId;
+    fn to_hex(&self) -> String;
+    fn to_strkey(&self) -> String;
+    fn hash(&self) -> [u8; 32];
+}
+
+impl ClaimableBalanceIdBehavior for ClaimableBalanceId {
+    /// Computes the id of the balance created by the operation at `op_index` in a transaction
+    /// sent by `source_account` with the given `sequence` number.
+    ///
+    /// `source_account` may be a G... or M... address, muxed accounts are reduced to their
+    /// underlying ed25519 key as the network does.
+    fn new(source_account: &str, sequence: &str, op_index: u32) -> Result<Self, String> {
+        let source = xdr::MuxedAccount::from_str(source_account)
+            .map_err(|_| "source_account is invalid".to_string())?;
+        let ed25519 = match source {
+            xdr::MuxedAccount::Ed25519(key) => key,
+            xdr::MuxedAccount::MuxedEd25519(muxed) => muxed.ed25519,
+        };
+        let seq_num = sequence
+            .parse::<i64>()
+            .map_err(|_| "sequence is invalid".to_string())?;
+
+        let preimage = xdr::HashIdPreimage::OpId(xdr::HashIdPreimageOperationId {
+            source_account: xdr::AccountId(xdr::PublicKey::PublicKeyTypeEd25519(ed25519)),
+            seq_num: xdr::SequenceNumber(seq_num),
+            op_num: op_index,
+        });
+        let payload = preimage
+            .to_xdr(xdr::Limits::none())
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            hash: Sha256Hasher::hash(payload),
+        })
+    }
+
+    /// Parses a balance id given either as a B... strkey, as the hex encoded XDR
+    /// `ClaimableBalanceId` returned by Horizon (72 characters) or as the bare hex encoded
+    /// hash (64 characters).
+    fn from_string(balance_id: &str) -> Result<Self, String> {
+        let raw = match hex::decode(balance_id) {
+            Ok(raw) => raw,
+            Err(_) => {
+                let xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(hash)) =
+                    xdr::ClaimableBalanceId::from_str(balance_id)
+                        .map_err(|_| "balance_id is neither hex nor a strkey".to_string())?;
+                return Ok(Self { hash });
+            }
+        };
+
+        match raw.len() {
+            32 => Ok(Self {
+                hash: *raw.last_chunk::<32>().unwrap(),
+            }),
+            36 => {
+                let xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(hash)) =
+                    xdr::ClaimableBalanceId::from_xdr(raw, xdr::Limits::none())
+                        .map_err(|_| "balance_id is not a valid ClaimableBalanceId".to_string())?;
+                Ok(Self { hash })
+            }
+            _ => Err("balance_id has an invalid length".to_string()),
+        }
+    }
+
+    fn from_xdr_object(balance_id: &xdr::ClaimableBalanceId) -> Self {
+        let xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(hash)) = balance_id;
+        Self { hash: *hash }
+    }
+
+    fn to_xdr_object(&self) -> xdr::ClaimableBalanceId {
+        xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(self.hash))
+    }
+
+    /// Returns the hex encoded XDR `ClaimableBalanceId`, the format used by Horizon.
+    fn to_hex(&self) -> String {
+        hex::encode(self.to_xdr_object().to_xdr(xdr::Limits::none()).unwrap())
+    }
+
+    /// Returns the B... strkey representation of the balance id.
+    fn to_strkey(&self) -> String {
+        self.to_xdr_object().to_string()
+    }
+
+    fn hash(&self) -> [u8; 32] {
+        self.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const MUXED_SOURCE: &str =
+        "MA7QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJVAAAAAAAAAAAAAJLK";
+    const UNDERLYING_SOURCE: &str = "GA7QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJVSGZ";
+
+    #[test]
+    fn test_computes_balance_id() {
+        let id = ClaimableBalanceId::new(SOURCE, "1235", 0).unwrap();
+
+        assert_eq!(
+            id.to_hex(),
+            "000000006ec80556a0fdb66768568c3a603d64d14c5e6949ae1635cf76ab7035a2f2ed2b"
+        );
+    }
+
+    #[test]
+    fn test_balance_id_depends_on_op_index() {
+        let first = ClaimableBalanceId::new(SOURCE, "1235", 0).unwrap();
+        let second = ClaimableBalanceId::new(SOURCE, "1235", 1).unwrap();
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_muxed_source_uses_underlying_account() {
+        let muxed = ClaimableBalanceId::new(MUXED_SOURCE, "10", 2).unwrap();
+        let underlying = ClaimableBalanceId::new(UNDERLYING_SOURCE, "10", 2).unwrap();
+
+        assert_eq!(muxed, underlying);
+    }
+
+    #[test]
+    fn test_invalid_source_and_sequence() {
+        assert_eq!(
+            ClaimableBalanceId::new("GBBB", "1", 0).unwrap_err(),
+            "source_account is invalid"
+        );
+        assert_eq!(
+            ClaimableBalanceId::new(SOURCE, "abc", 0).unwrap_err(),
+            "sequence is invalid"
+        );
+    }
+
+    #[test]
+    fn test_string_round_trip() {
+        let id = ClaimableBalanceId::new(SOURCE, "1235", 0).unwrap();
+
+        let strkey = id.to_strkey();
+        assert!(strkey.starts_with('B'));
+        assert_eq!(ClaimableBalanceId::from_string(&strkey).unwrap(), id);
+        assert_eq!(ClaimableBalanceId::from_string(&id.to_hex()).unwrap(), id);
+        assert_eq!(
+            ClaimableBalanceId::from_string(&hex::encode(id.hash())).unwrap(),
+            id
+        );
+    }
+
+    #[test]
+    fn test_invalid_strings() {
+        assert!(ClaimableBalanceId::from_string("zz").is_err());
+        assert!(ClaimableBalanceId::from_string(&hex::encode([1; 31])).is_err());
+        assert!(
+            ClaimableBalanceId::from_string(&format!("00000001{}", hex::encode([1; 32]))).is_err()
+        );
+        assert!(ClaimableBalanceId::from_string(SOURCE).is_err());
+    }
+}
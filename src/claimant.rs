@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use stellar_strkey::ed25519::PublicKey;
 // use stellar_xdr::{xdr::VecM, xdr::ClaimPredicate};
 use crate::keypair::Keypair;
@@ -22,7 +24,12 @@ pub trait ClaimantBehavior {
     fn predicate_or(left: xdr::ClaimPredicate, right: xdr::ClaimPredicate) -> xdr::ClaimPredicate;
     fn predicate_not(predicate: xdr::ClaimPredicate) -> xdr::ClaimPredicate;
     fn predicate_before_absolute_time(abs_before: i64) -> xdr::ClaimPredicate;
-    fn predicate_before_relative_time(seconds_str: &str) -> xdr::ClaimPredicate;
+    fn predicate_before_relative_time(duration: Duration) -> xdr::ClaimPredicate;
+    fn evaluate(
+        predicate: &xdr::ClaimPredicate,
+        claimable_balance_created_at: i64,
+        now: i64,
+    ) -> bool;
     fn from_xdr(claimant_xdr: xdr::Claimant) -> Result<Self, &'static str>
     where
         Self: Sized;
@@ -79,11 +86,21 @@ impl ClaimantBehavior for Claimant {
         xdr::ClaimPredicate::BeforeAbsoluteTime(abs_before)
     }
 
-    fn predicate_before_relative_time(seconds_str: &str) -> xdr::ClaimPredicate {
-        let seconds = seconds_str
-            .parse::<i64>()
-            .expect("Failed to parse seconds string to i64");
-        xdr::ClaimPredicate::BeforeRelativeTime(seconds)
+    fn predicate_before_relative_time(duration: Duration) -> xdr::ClaimPredicate {
+        before_relative(duration)
+    }
+
+    /// Returns `true` if a balance guarded by `predicate` can be claimed at `now`.
+    ///
+    /// Relative time predicates are resolved against `claimable_balance_created_at`, the close
+    /// time of the ledger in which the balance was created. Both times are UNIX timestamps in
+    /// seconds.
+    fn evaluate(
+        predicate: &xdr::ClaimPredicate,
+        claimable_balance_created_at: i64,
+        now: i64,
+    ) -> bool {
+        predicate.evaluate(claimable_balance_created_at, now)
     }
 
     fn from_xdr(claimant_xdr: xdr::Claimant) -> Result<Claimant, &'static str> {
@@ -130,3 +147,295 @@ impl ClaimantBehavior for Claimant {
         self.predicate = _value;
     }
 }
+
+/// Predicate that is always satisfied.
+pub fn unconditional() -> xdr::ClaimPredicate {
+    xdr::ClaimPredicate::Unconditional
+}
+
+/// Predicate satisfied strictly before the UNIX timestamp `abs_before` (in seconds).
+pub fn before(abs_before: i64) -> xdr::ClaimPredicate {
+    xdr::ClaimPredicate::BeforeAbsoluteTime(abs_before)
+}
+
+/// Predicate satisfied while less than `duration` has elapsed since the balance was created.
+///
+/// Sub-second precision is dropped and durations beyond `i64::MAX` seconds saturate.
+pub fn before_relative(duration: Duration) -> xdr::ClaimPredicate {
+    let seconds = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+    xdr::ClaimPredicate::BeforeRelativeTime(seconds)
+}
+
+/// Predicate satisfied when `predicate` is not.
+pub fn not(predicate: xdr::ClaimPredicate) -> xdr::ClaimPredicate {
+    xdr::ClaimPredicate::Not(Some(Box::new(predicate)))
+}
+
+/// Composable helpers on top of `xdr::ClaimPredicate`, e.g.
+/// `before(unlock_end).and(not(before(unlock_start)))`
+pub trait ClaimPredicateBehavior {
+    fn and(self, other: xdr::ClaimPredicate) -> xdr::ClaimPredicate;
+    fn or(self, other: xdr::ClaimPredicate) -> xdr::ClaimPredicate;
+    fn negate(self) -> xdr::ClaimPredicate;
+    fn evaluate(&self, claimable_balance_created_at: i64, now: i64) -> bool;
+    fn resolve(&self, claimable_balance_created_at: i64) -> xdr::ClaimPredicate;
+    fn describe(&self) -> String;
+}
+
+// Resolves each predicate of an `And`/`Or`, keeping malformed lists with fewer than two
+// predicates as they are instead of indexing into them
+fn resolve_all(
+    preds: &xdr::VecM<xdr::ClaimPredicate, 2>,
+    claimable_balance_created_at: i64,
+) -> xdr::VecM<xdr::ClaimPredicate, 2> {
+    preds
+        .iter()
+        .map(|p| p.resolve(claimable_balance_created_at))
+        .collect::<Vec<_>>()
+        .try_into()
+        .expect("resolving keeps the number of predicates")
+}
+
+impl ClaimPredicateBehavior for xdr::ClaimPredicate {
+    fn and(self, other: xdr::ClaimPredicate) -> xdr::ClaimPredicate {
+        Claimant::predicate_and(self, other)
+    }
+
+    fn or(self, other: xdr::ClaimPredicate) -> xdr::ClaimPredicate {
+        Claimant::predicate_or(self, other)
+    }
+
+    fn negate(self) -> xdr::ClaimPredicate {
+        not(self)
+    }
+
+    /// Same as [ClaimantBehavior::evaluate]. A `Not` without an inner predicate is never
+    /// satisfied, the network rejects such predicates.
+    fn evaluate(&self, claimable_balance_created_at: i64, now: i64) -> bool {
+        match self {
+            xdr::ClaimPredicate::Unconditional => true,
+            xdr::ClaimPredicate::And(preds) => preds
+                .iter()
+                .all(|p| p.evaluate(claimable_balance_created_at, now)),
+            xdr::ClaimPredicate::Or(preds) => preds
+                .iter()
+                .any(|p| p.evaluate(claimable_balance_created_at, now)),
+            xdr::ClaimPredicate::Not(Some(pred)) => {
+                !pred.evaluate(claimable_balance_created_at, now)
+            }
+            xdr::ClaimPredicate::Not(None) => false,
+            xdr::ClaimPredicate::BeforeAbsoluteTime(abs_before) => now < *abs_before,
+            xdr::ClaimPredicate::BeforeRelativeTime(rel_before) => {
+                now < claimable_balance_created_at.saturating_add(*rel_before)
+            }
+        }
+    }
+
+    /// Converts relative time predicates into absolute ones, as the network does when the
+    /// balance is created at `claimable_balance_created_at`.
+    fn resolve(&self, claimable_balance_created_at: i64) -> xdr::ClaimPredicate {
+        match self {
+            xdr::ClaimPredicate::And(preds) => {
+                xdr::ClaimPredicate::And(resolve_all(preds, claimable_balance_created_at))
+            }
+            xdr::ClaimPredicate::Or(preds) => {
+                xdr::ClaimPredicate::Or(resolve_all(preds, claimable_balance_created_at))
+            }
+            xdr::ClaimPredicate::Not(Some(pred)) => not(pred.resolve(claimable_balance_created_at)),
+            xdr::ClaimPredicate::BeforeRelativeTime(rel_before) => {
+                before(claimable_balance_created_at.saturating_add(*rel_before))
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// Renders the predicate as a human readable sentence, absolute times are printed as UTC
+    /// RFC 3339 timestamps.
+    fn describe(&self) -> String {
+        match self {
+            xdr::ClaimPredicate::Unconditional => "always".to_string(),
+            xdr::ClaimPredicate::And(preds) => format!(
+                "({})",
+                preds
+                    .iter()
+                    .map(|p| p.describe())
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ),
+            xdr::ClaimPredicate::Or(preds) => format!(
+                "({})",
+                preds
+                    .iter()
+                    .map(|p| p.describe())
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ),
+            xdr::ClaimPredicate::Not(Some(pred)) => match pred.as_ref() {
+                xdr::ClaimPredicate::BeforeAbsoluteTime(t) => {
+                    format!("at or after {}", format_timestamp(*t))
+                }
+                xdr::ClaimPredicate::BeforeRelativeTime(s) => {
+                    format!("{} or more after creation", format_duration(*s))
+                }
+                xdr::ClaimPredicate::Unconditional => "never".to_string(),
+                other => format!("not {}", other.describe()),
+            },
+            xdr::ClaimPredicate::Not(None) => "never".to_string(),
+            xdr::ClaimPredicate::BeforeAbsoluteTime(t) => {
+                format!("before {}", format_timestamp(*t))
+            }
+            xdr::ClaimPredicate::BeforeRelativeTime(s) => {
+                format!("within {} of creation", format_duration(*s))
+            }
+        }
+    }
+}
+
+fn format_duration(seconds: i64) -> String {
+    let units = [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)];
+    let mut rest = seconds.max(0);
+    let mut parts = Vec::new();
+    for (suffix, size) in units {
+        if rest >= size {
+            parts.push(format!("{}{}", rest / size, suffix));
+            rest %= size;
+        }
+    }
+    if parts.is_empty() {
+        return "0s".to_string();
+    }
+    parts.join(" ")
+}
+
+// Formats a UNIX timestamp as `YYYY-MM-DDTHH:MM:SSZ` using the proleptic Gregorian calendar
+fn format_timestamp(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let secs = timestamp.rem_euclid(86_400);
+
+    // Civil date from days since 1970-01-01 (H. Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JAN_1_2024: i64 = 1_704_067_200;
+    const FEB_1_2024: i64 = 1_706_745_600;
+
+    #[test]
+    fn test_evaluate_absolute_time() {
+        let pred = Claimant::predicate_before_absolute_time(JAN_1_2024);
+
+        assert!(Claimant::evaluate(&pred, 0, JAN_1_2024 - 1));
+        assert!(!Claimant::evaluate(&pred, 0, JAN_1_2024));
+    }
+
+    #[test]
+    fn test_evaluate_relative_time_against_creation() {
+        let pred = Claimant::predicate_before_relative_time(Duration::from_secs(3_600));
+
+        assert_eq!(pred, xdr::ClaimPredicate::BeforeRelativeTime(3_600));
+        assert!(Claimant::evaluate(&pred, JAN_1_2024, JAN_1_2024 + 3_599));
+        assert!(!Claimant::evaluate(&pred, JAN_1_2024, JAN_1_2024 + 3_600));
+        assert!(Claimant::evaluate(&pred, FEB_1_2024, FEB_1_2024 + 10));
+    }
+
+    #[test]
+    fn test_evaluate_vesting_window() {
+        // claimable between Jan 1st and Feb 1st
+        let pred = before(FEB_1_2024).and(not(before(JAN_1_2024)));
+
+        assert!(!pred.evaluate(0, JAN_1_2024 - 1));
+        assert!(pred.evaluate(0, JAN_1_2024));
+        assert!(pred.evaluate(0, FEB_1_2024 - 1));
+        assert!(!pred.evaluate(0, FEB_1_2024));
+    }
+
+    #[test]
+    fn test_evaluate_combinators() {
+        let past = before(JAN_1_2024);
+        let future = before(FEB_1_2024);
+        let now = JAN_1_2024 + 1;
+
+        assert!(past.clone().or(future.clone()).evaluate(0, now));
+        assert!(!past.clone().and(future.clone()).evaluate(0, now));
+        assert!(past.negate().evaluate(0, now));
+        assert!(unconditional().evaluate(0, now));
+        assert!(!xdr::ClaimPredicate::Not(None).evaluate(0, now));
+    }
+
+    #[test]
+    fn test_builder_matches_claimant_behavior() {
+        let built = before(FEB_1_2024).or(not(before_relative(Duration::from_secs(60))));
+        let expected = Claimant::predicate_or(
+            Claimant::predicate_before_absolute_time(FEB_1_2024),
+            Claimant::predicate_not(xdr::ClaimPredicate::BeforeRelativeTime(60)),
+        );
+
+        assert_eq!(built, expected);
+    }
+
+    #[test]
+    fn test_resolve_relative_time() {
+        let pred = before_relative(Duration::from_secs(86_400)).and(unconditional());
+
+        assert_eq!(
+            pred.resolve(JAN_1_2024),
+            before(JAN_1_2024 + 86_400).and(unconditional())
+        );
+    }
+
+    #[test]
+    fn test_resolve_malformed_predicates() {
+        let relative = before_relative(Duration::from_secs(60));
+        let one = xdr::ClaimPredicate::Or(vec![relative.clone()].try_into().unwrap());
+        assert_eq!(
+            one.resolve(JAN_1_2024),
+            xdr::ClaimPredicate::Or(vec![before(JAN_1_2024 + 60)].try_into().unwrap())
+        );
+
+        let empty = xdr::ClaimPredicate::And(vec![].try_into().unwrap());
+        assert_eq!(empty.resolve(JAN_1_2024), empty);
+        assert!(empty.evaluate(JAN_1_2024, JAN_1_2024));
+    }
+
+    #[test]
+    fn test_describe() {
+        let pred = before(FEB_1_2024).and(not(before(JAN_1_2024)));
+        assert_eq!(
+            pred.describe(),
+            "(before 2024-02-01T00:00:00Z and at or after 2024-01-01T00:00:00Z)"
+        );
+
+        let pred = before_relative(Duration::from_secs(90_061)).or(unconditional());
+        assert_eq!(
+            pred.describe(),
+            "(within 1d 1h 1m 1s of creation or always)"
+        );
+
+        assert_eq!(
+            not(before_relative(Duration::from_secs(120))).describe(),
+            "2m or more after creation"
+        );
+        assert_eq!(before(-1).describe(), "before 1969-12-31T23:59:59Z");
+    }
+}
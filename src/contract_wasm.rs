@@ -0,0 +1,308 @@
+//! Offline inspection of contract WASM before uploading it.
+//!
+//! Reads the code hash and the `contractenvmetav0` and `contractmetav0` custom sections
+//! that the Soroban SDK embeds in every contract.
+use std::io::Cursor;
+
+use crate::hashing::{HashingBehavior, Sha256Hasher};
+use crate::xdr::{self, Limited, Limits, ReadXdr};
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+const WASM_VERSION: &[u8] = &[1, 0, 0, 0];
+const ENV_META_SECTION: &str = "contractenvmetav0";
+const META_SECTION: &str = "contractmetav0";
+/// The `contractmetav0` key holding the version of the Rust SDK that built the contract.
+pub const SDK_VERSION_META_KEY: &str = "rssdkver";
+
+/// A contract WASM with its hash and metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractWasm {
+    /// The SHA-256 hash identifying the code, as in `ContractExecutable::Wasm`.
+    pub hash: [u8; 32],
+    pub size: usize,
+    pub env_meta: Vec<xdr::ScEnvMetaEntry>,
+    pub meta: Vec<xdr::ScMetaEntry>,
+}
+
+// Define a trait for ContractWasm behavior
+pub trait ContractWasmBehavior {
+    fn parse(wasm: &[u8]) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn interface_version(&self) -> Option<&xdr::ScEnvMetaEntryInterfaceVersion>;
+    fn protocol_version(&self) -> Option<u32>;
+    fn meta_value(&self, key: &str) -> Option<String>;
+    fn sdk_version(&self) -> Option<String>;
+    fn executable(&self) -> xdr::ContractExecutable;
+    fn check_size(&self, max_size_bytes: u32) -> Result<(), String>;
+    fn check_size_from_config(&self, settings: &[xdr::ConfigSettingEntry]) -> Result<(), String>;
+}
+
+fn read_u32_leb128(bytes: &[u8], pos: &mut usize) -> Result<u32, String> {
+    let mut result = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or("unexpected end of wasm")?;
+        *pos += 1;
+        result |= u32::from(byte & 0x7f)
+            .checked_shl(shift)
+            .filter(|_| shift < 28 || byte & 0x70 == 0)
+            .ok_or("invalid LEB128 integer in wasm")?;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err("invalid LEB128 integer in wasm".to_string())
+}
+
+fn read_bytes<'a>(bytes: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let end = pos
+        .checked_add(len)
+        .filter(|end| *end <= bytes.len())
+        .ok_or("unexpected end of wasm")?;
+    let slice = &bytes[*pos..end];
+    *pos = end;
+    Ok(slice)
+}
+
+/// Returns the payloads of the custom sections named `name`, in order.
+fn custom_sections<'a>(wasm: &'a [u8], name: &str) -> Result<Vec<&'a [u8]>, String> {
+    if wasm.get(..4) != Some(WASM_MAGIC) {
+        return Err("not a wasm module".to_string());
+    }
+    if wasm.get(4..8) != Some(WASM_VERSION) {
+        return Err("unsupported wasm version".to_string());
+    }
+
+    let mut sections = Vec::new();
+    let mut pos = 8;
+    while pos < wasm.len() {
+        let id = wasm[pos];
+        pos += 1;
+        let len = read_u32_leb128(wasm, &mut pos)? as usize;
+        let content = read_bytes(wasm, &mut pos, len)?;
+        if id == 0 {
+            let mut content_pos = 0;
+            let name_len = read_u32_leb128(content, &mut content_pos)? as usize;
+            let section_name = read_bytes(content, &mut content_pos, name_len)?;
+            if section_name == name.as_bytes() {
+                sections.push(&content[content_pos..]);
+            }
+        }
+    }
+    Ok(sections)
+}
+
+fn read_entries<T: ReadXdr>(wasm: &[u8], name: &str) -> Result<Vec<T>, String> {
+    let mut entries = Vec::new();
+    for payload in custom_sections(wasm, name)? {
+        let mut reader = Limited::new(Cursor::new(payload), Limits::none());
+        for entry in T::read_xdr_iter(&mut reader) {
+            entries.push(entry.map_err(|e| format!("invalid {} section: {}", name, e))?);
+        }
+    }
+    Ok(entries)
+}
+
+impl ContractWasmBehavior for ContractWasm {
+    fn parse(wasm: &[u8]) -> Result<Self, String> {
+        Ok(Self {
+            hash: Sha256Hasher::hash(wasm),
+            size: wasm.len(),
+            env_meta: read_entries(wasm, ENV_META_SECTION)?,
+            meta: read_entries(wasm, META_SECTION)?,
+        })
+    }
+
+    fn interface_version(&self) -> Option<&xdr::ScEnvMetaEntryInterfaceVersion> {
+        self.env_meta
+            .iter()
+            .map(|entry| match entry {
+                xdr::ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion(version) => version,
+            })
+            .next()
+    }
+
+    /// The protocol version the contract was built for.
+    fn protocol_version(&self) -> Option<u32> {
+        self.interface_version().map(|version| version.protocol)
+    }
+
+    fn meta_value(&self, key: &str) -> Option<String> {
+        self.meta.iter().find_map(|entry| match entry {
+            xdr::ScMetaEntry::ScMetaV0(meta) if meta.key.as_slice() == key.as_bytes() => {
+                Some(meta.val.to_utf8_string_lossy())
+            }
+            _ => None,
+        })
+    }
+
+    fn sdk_version(&self) -> Option<String> {
+        self.meta_value(SDK_VERSION_META_KEY)
+    }
+
+    fn executable(&self) -> xdr::ContractExecutable {
+        xdr::ContractExecutable::Wasm(xdr::Hash(self.hash))
+    }
+
+    fn check_size(&self, max_size_bytes: u32) -> Result<(), String> {
+        if self.size > max_size_bytes as usize {
+            return Err(format!(
+                "wasm is {} bytes, more than the limit of {} bytes",
+                self.size, max_size_bytes
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the size against the network's `ContractMaxSizeBytes` setting.
+    fn check_size_from_config(&self, settings: &[xdr::ConfigSettingEntry]) -> Result<(), String> {
+        let max_size_bytes = settings
+            .iter()
+            .find_map(|setting| match setting {
+                xdr::ConfigSettingEntry::ContractMaxSizeBytes(max) => Some(*max),
+                _ => None,
+            })
+            .ok_or("missing config setting: ContractMaxSizeBytes")?;
+        self.check_size(max_size_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation::Operation;
+    use crate::xdr::WriteXdr;
+
+    fn section(id: u8, content: &[u8]) -> Vec<u8> {
+        assert!(content.len() < 128);
+        let mut bytes = vec![id, content.len() as u8];
+        bytes.extend_from_slice(content);
+        bytes
+    }
+
+    fn custom_section<T: WriteXdr>(name: &str, entries: &[T]) -> Vec<u8> {
+        let mut content = vec![name.len() as u8];
+        content.extend_from_slice(name.as_bytes());
+        for entry in entries {
+            content.extend(entry.to_xdr(Limits::none()).unwrap());
+        }
+        section(0, &content)
+    }
+
+    fn meta(key: &str, val: &str) -> xdr::ScMetaEntry {
+        xdr::ScMetaEntry::ScMetaV0(xdr::ScMetaV0 {
+            key: key.try_into().unwrap(),
+            val: val.try_into().unwrap(),
+        })
+    }
+
+    fn wasm() -> Vec<u8> {
+        let mut wasm = [WASM_MAGIC, WASM_VERSION].concat();
+        // An empty type section.
+        wasm.extend(section(1, &[0]));
+        wasm.extend(custom_section(
+            ENV_META_SECTION,
+            &[xdr::ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion(
+                xdr::ScEnvMetaEntryInterfaceVersion {
+                    protocol: 23,
+                    pre_release: 0,
+                },
+            )],
+        ));
+        wasm.extend(custom_section(
+            META_SECTION,
+            &[meta("rsver", "1.89.0"), meta("rssdkver", "23.0.1#abc")],
+        ));
+        wasm.extend(custom_section(META_SECTION, &[meta("name", "token")]));
+        wasm
+    }
+
+    #[test]
+    fn test_parses_metadata() {
+        let wasm = wasm();
+        let contract = ContractWasm::parse(&wasm).unwrap();
+
+        assert_eq!(contract.hash, Sha256Hasher::hash(&wasm));
+        assert_eq!(contract.size, wasm.len());
+        assert_eq!(contract.protocol_version(), Some(23));
+        assert_eq!(contract.interface_version().unwrap().pre_release, 0);
+        assert_eq!(contract.sdk_version().as_deref(), Some("23.0.1#abc"));
+        assert_eq!(contract.meta_value("name").as_deref(), Some("token"));
+        assert_eq!(contract.meta.len(), 3);
+        assert_eq!(contract.meta_value("missing"), None);
+    }
+
+    #[test]
+    fn test_wasm_without_metadata() {
+        let wasm = [WASM_MAGIC, WASM_VERSION].concat();
+        let contract = ContractWasm::parse(&wasm).unwrap();
+        assert_eq!(contract.protocol_version(), None);
+        assert_eq!(contract.sdk_version(), None);
+    }
+
+    #[test]
+    fn test_rejects_malformed_wasm() {
+        assert_eq!(
+            ContractWasm::parse(b"not wasm").unwrap_err(),
+            "not a wasm module"
+        );
+        assert_eq!(
+            ContractWasm::parse(&[WASM_MAGIC, &[2, 0, 0, 0]].concat()).unwrap_err(),
+            "unsupported wasm version"
+        );
+
+        let mut truncated = wasm();
+        truncated.truncate(truncated.len() - 1);
+        assert_eq!(
+            ContractWasm::parse(&truncated).unwrap_err(),
+            "unexpected end of wasm"
+        );
+
+        let mut wasm = [WASM_MAGIC, WASM_VERSION].concat();
+        wasm.extend(section(
+            0,
+            &[
+                [META_SECTION.len() as u8].as_slice(),
+                META_SECTION.as_bytes(),
+                &[0, 0],
+            ]
+            .concat(),
+        ));
+        assert!(ContractWasm::parse(&wasm)
+            .unwrap_err()
+            .starts_with("invalid contractmetav0 section"));
+    }
+
+    #[test]
+    fn test_checks_size_against_limits() {
+        let contract = ContractWasm::parse(&wasm()).unwrap();
+        assert_eq!(contract.check_size(contract.size as u32), Ok(()));
+        assert!(contract.check_size(10).is_err());
+        assert!(contract
+            .check_size_from_config(&[xdr::ConfigSettingEntry::ContractMaxSizeBytes(65_536)])
+            .is_ok());
+        assert_eq!(
+            contract.check_size_from_config(&[]).unwrap_err(),
+            "missing config setting: ContractMaxSizeBytes"
+        );
+    }
+
+    #[test]
+    fn test_hash_feeds_create_contract() {
+        let wasm = wasm();
+        let contract = ContractWasm::parse(&wasm).unwrap();
+        let deployer = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+
+        let op = Operation::new()
+            .create_contract(deployer, contract.hash, Some([0; 32]), None, vec![])
+            .unwrap();
+
+        let xdr::OperationBody::InvokeHostFunction(op) = op.body else {
+            panic!("expected an invoke host function operation");
+        };
+        let xdr::HostFunction::CreateContractV2(args) = op.host_function else {
+            panic!("expected a create contract host function");
+        };
+        assert_eq!(args.executable, contract.executable());
+    }
+}
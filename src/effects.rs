@@ -0,0 +1,573 @@
+//! Effects of a transaction derived from the ledger entry changes in its meta.
+//!
+//! Each list of `LedgerEntryChanges` is diffed on its own: a `State` change records the value of
+//! an entry before the `Updated` or `Removed` change that follows it.
+use std::collections::HashMap;
+
+use crate::asset::{Asset, AssetBehavior};
+use crate::claimable_balance_id::{ClaimableBalanceId, ClaimableBalanceIdBehavior};
+use crate::xdr;
+
+/// An asset an account can hold a balance of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalanceAsset {
+    Asset(Asset),
+    /// Shares of the liquidity pool with this hex id.
+    PoolShare(String),
+    /// A balance held in a Stellar Asset Contract, identified by its C... address.
+    Contract(String),
+}
+
+/// A change in the balance an account or contract holds of an asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    /// The G... or C... address of the holder.
+    pub account: String,
+    pub asset: BalanceAsset,
+    pub before: i128,
+    pub after: i128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerEffect {
+    BalanceChanged(BalanceChange),
+    TrustlineCreated {
+        account: String,
+        asset: BalanceAsset,
+    },
+    TrustlineRemoved {
+        account: String,
+        asset: BalanceAsset,
+    },
+    OfferCreated {
+        seller: String,
+        offer_id: i64,
+    },
+    OfferRemoved {
+        seller: String,
+        offer_id: i64,
+    },
+    ClaimableBalanceCreated {
+        /// The hex id of the balance.
+        balance_id: String,
+        asset: Asset,
+        amount: i64,
+    },
+    ClaimableBalanceRemoved {
+        balance_id: String,
+    },
+}
+
+// Define a trait for BalanceChange behavior
+pub trait BalanceChangeBehavior {
+    fn amount(&self) -> i128;
+}
+
+// Define a trait for LedgerEffect behavior
+pub trait LedgerEffectBehavior {
+    fn from_changes(changes: &[xdr::LedgerEntryChange]) -> Result<Vec<Self>, String>
+    where
+        Self: Sized;
+    fn from_meta(meta: &xdr::TransactionMeta) -> Result<Vec<Self>, String>
+    where
+        Self: Sized;
+}
+
+/// Returns the balance changes in `meta`, in the order they were applied.
+pub fn balance_changes(meta: &xdr::TransactionMeta) -> Result<Vec<BalanceChange>, String> {
+    Ok(LedgerEffect::from_meta(meta)?
+        .into_iter()
+        .filter_map(|effect| match effect {
+            LedgerEffect::BalanceChanged(change) => Some(change),
+            _ => None,
+        })
+        .collect())
+}
+
+/// The lists of changes in `meta`, in the order they were applied.
+fn change_lists(meta: &xdr::TransactionMeta) -> Vec<&[xdr::LedgerEntryChange]> {
+    match meta {
+        xdr::TransactionMeta::V0(operations) => {
+            operations.iter().map(|op| op.changes.as_slice()).collect()
+        }
+        xdr::TransactionMeta::V1(meta) => std::iter::once(meta.tx_changes.as_slice())
+            .chain(meta.operations.iter().map(|op| op.changes.as_slice()))
+            .collect(),
+        xdr::TransactionMeta::V2(meta) => std::iter::once(meta.tx_changes_before.as_slice())
+            .chain(meta.operations.iter().map(|op| op.changes.as_slice()))
+            .chain(std::iter::once(meta.tx_changes_after.as_slice()))
+            .collect(),
+        xdr::TransactionMeta::V3(meta) => std::iter::once(meta.tx_changes_before.as_slice())
+            .chain(meta.operations.iter().map(|op| op.changes.as_slice()))
+            .chain(std::iter::once(meta.tx_changes_after.as_slice()))
+            .collect(),
+        xdr::TransactionMeta::V4(meta) => std::iter::once(meta.tx_changes_before.as_slice())
+            .chain(meta.operations.iter().map(|op| op.changes.as_slice()))
+            .chain(std::iter::once(meta.tx_changes_after.as_slice()))
+            .collect(),
+    }
+}
+
+fn trust_line_asset(asset: &xdr::TrustLineAsset) -> Result<BalanceAsset, String> {
+    let asset = match asset {
+        xdr::TrustLineAsset::Native => xdr::Asset::Native,
+        xdr::TrustLineAsset::CreditAlphanum4(asset) => xdr::Asset::CreditAlphanum4(asset.clone()),
+        xdr::TrustLineAsset::CreditAlphanum12(asset) => xdr::Asset::CreditAlphanum12(asset.clone()),
+        xdr::TrustLineAsset::PoolShare(xdr::PoolId(xdr::Hash(id))) => {
+            return Ok(BalanceAsset::PoolShare(hex::encode(id)))
+        }
+    };
+    Asset::from_operation(asset).map(BalanceAsset::Asset)
+}
+
+fn balance_id(id: &xdr::ClaimableBalanceId) -> String {
+    ClaimableBalanceId::from_xdr_object(id).to_hex()
+}
+
+/// Reads the holder of a Stellar Asset Contract balance from a contract data key.
+fn sac_balance_holder(key: &xdr::ScVal) -> Option<String> {
+    let xdr::ScVal::Vec(Some(parts)) = key else {
+        return None;
+    };
+    match parts.as_slice() {
+        [xdr::ScVal::Symbol(name), xdr::ScVal::Address(holder)]
+            if name.as_slice() == b"Balance" =>
+        {
+            Some(holder.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Reads the `amount` of a Stellar Asset Contract balance value.
+fn sac_balance_amount(val: &xdr::ScVal) -> Option<i128> {
+    let xdr::ScVal::Map(Some(map)) = val else {
+        return None;
+    };
+    map.iter().find_map(|entry| match (&entry.key, &entry.val) {
+        (xdr::ScVal::Symbol(name), xdr::ScVal::I128(amount)) if name.as_slice() == b"amount" => {
+            Some((i128::from(amount.hi) << 64) | i128::from(amount.lo))
+        }
+        _ => None,
+    })
+}
+
+fn balance_changed(
+    effects: &mut Vec<LedgerEffect>,
+    account: String,
+    asset: BalanceAsset,
+    before: i128,
+    after: i128,
+) {
+    if before != after {
+        effects.push(LedgerEffect::BalanceChanged(BalanceChange {
+            account,
+            asset,
+            before,
+            after,
+        }));
+    }
+}
+
+/// Appends the effects of an entry going from `before` to `after`, where `None` means the
+/// entry did not exist.
+fn diff(
+    effects: &mut Vec<LedgerEffect>,
+    key: &xdr::LedgerKey,
+    before: Option<&xdr::LedgerEntry>,
+    after: Option<&xdr::LedgerEntry>,
+) -> Result<(), String> {
+    let (before, after) = (before.map(|e| &e.data), after.map(|e| &e.data));
+
+    match key {
+        xdr::LedgerKey::Account(key) => {
+            let balance = |data: Option<&xdr::LedgerEntryData>| match data {
+                Some(xdr::LedgerEntryData::Account(account)) => i128::from(account.balance),
+                _ => 0,
+            };
+            balance_changed(
+                effects,
+                key.account_id.to_string(),
+                BalanceAsset::Asset(Asset::native()),
+                balance(before),
+                balance(after),
+            );
+        }
+        xdr::LedgerKey::Trustline(key) => {
+            let account = key.account_id.to_string();
+            let asset = trust_line_asset(&key.asset)?;
+            let balance = |data: Option<&xdr::LedgerEntryData>| match data {
+                Some(xdr::LedgerEntryData::Trustline(trust_line)) => i128::from(trust_line.balance),
+                _ => 0,
+            };
+            if before.is_none() && after.is_some() {
+                effects.push(LedgerEffect::TrustlineCreated {
+                    account: account.clone(),
+                    asset: asset.clone(),
+                });
+            }
+            balance_changed(
+                effects,
+                account.clone(),
+                asset.clone(),
+                balance(before),
+                balance(after),
+            );
+            if before.is_some() && after.is_none() {
+                effects.push(LedgerEffect::TrustlineRemoved { account, asset });
+            }
+        }
+        xdr::LedgerKey::Offer(key) => {
+            let seller = key.seller_id.to_string();
+            match (before, after) {
+                (None, Some(_)) => effects.push(LedgerEffect::OfferCreated {
+                    seller,
+                    offer_id: key.offer_id,
+                }),
+                (Some(_), None) => effects.push(LedgerEffect::OfferRemoved {
+                    seller,
+                    offer_id: key.offer_id,
+                }),
+                _ => {}
+            }
+        }
+        xdr::LedgerKey::ClaimableBalance(key) => match (before, after) {
+            (None, Some(xdr::LedgerEntryData::ClaimableBalance(entry))) => {
+                effects.push(LedgerEffect::ClaimableBalanceCreated {
+                    balance_id: balance_id(&key.balance_id),
+                    asset: Asset::from_operation(entry.asset.clone())?,
+                    amount: entry.amount,
+                })
+            }
+            (Some(_), None) => effects.push(LedgerEffect::ClaimableBalanceRemoved {
+                balance_id: balance_id(&key.balance_id),
+            }),
+            _ => {}
+        },
+        xdr::LedgerKey::ContractData(key) => {
+            let (Some(account), xdr::ScAddress::Contract(_)) =
+                (sac_balance_holder(&key.key), &key.contract)
+            else {
+                return Ok(());
+            };
+            let balance = |data: Option<&xdr::LedgerEntryData>| match data {
+                Some(xdr::LedgerEntryData::ContractData(data)) => {
+                    sac_balance_amount(&data.val).unwrap_or(0)
+                }
+                _ => 0,
+            };
+            balance_changed(
+                effects,
+                account,
+                BalanceAsset::Contract(key.contract.to_string()),
+                balance(before),
+                balance(after),
+            );
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+impl BalanceChangeBehavior for BalanceChange {
+    fn amount(&self) -> i128 {
+        self.after - self.before
+    }
+}
+
+impl LedgerEffectBehavior for LedgerEffect {
+    fn from_changes(changes: &[xdr::LedgerEntryChange]) -> Result<Vec<Self>, String> {
+        let mut state: HashMap<xdr::LedgerKey, &xdr::LedgerEntry> = HashMap::new();
+        let mut effects = Vec::new();
+        for change in changes {
+            match change {
+                // Restored entries existed before, they were only archived.
+                xdr::LedgerEntryChange::State(entry) | xdr::LedgerEntryChange::Restored(entry) => {
+                    state.insert(entry.to_key(), entry);
+                }
+                xdr::LedgerEntryChange::Created(entry) => {
+                    diff(&mut effects, &entry.to_key(), None, Some(entry))?;
+                }
+                xdr::LedgerEntryChange::Updated(entry) => {
+                    let key = entry.to_key();
+                    let before = state.insert(key.clone(), entry);
+                    diff(&mut effects, &key, before, Some(entry))?;
+                }
+                xdr::LedgerEntryChange::Removed(key) => {
+                    let before = state.remove(key);
+                    diff(&mut effects, key, before, None)?;
+                }
+            }
+        }
+        Ok(effects)
+    }
+
+    fn from_meta(meta: &xdr::TransactionMeta) -> Result<Vec<Self>, String> {
+        let mut effects = Vec::new();
+        for changes in change_lists(meta) {
+            effects.extend(Self::from_changes(changes)?);
+        }
+        Ok(effects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const ALICE: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const BOB: &str = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+    const CONTRACT: &str = "CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXE";
+
+    fn account_id(address: &str) -> xdr::AccountId {
+        xdr::AccountId::from_str(address).unwrap()
+    }
+
+    fn entry(data: xdr::LedgerEntryData) -> xdr::LedgerEntry {
+        xdr::LedgerEntry {
+            last_modified_ledger_seq: 1,
+            data,
+            ext: xdr::LedgerEntryExt::V0,
+        }
+    }
+
+    fn account(address: &str, balance: i64) -> xdr::LedgerEntry {
+        entry(xdr::LedgerEntryData::Account(xdr::AccountEntry {
+            account_id: account_id(address),
+            balance,
+            seq_num: xdr::SequenceNumber(1),
+            num_sub_entries: 0,
+            inflation_dest: None,
+            flags: 0,
+            home_domain: Default::default(),
+            thresholds: xdr::Thresholds([1, 0, 0, 0]),
+            signers: Default::default(),
+            ext: xdr::AccountEntryExt::V0,
+        }))
+    }
+
+    fn usd() -> Asset {
+        Asset::new("USD", Some(BOB)).unwrap()
+    }
+
+    fn trust_line(address: &str, asset: xdr::TrustLineAsset, balance: i64) -> xdr::LedgerEntry {
+        entry(xdr::LedgerEntryData::Trustline(xdr::TrustLineEntry {
+            account_id: account_id(address),
+            asset,
+            balance,
+            limit: i64::MAX,
+            flags: 1,
+            ext: xdr::TrustLineEntryExt::V0,
+        }))
+    }
+
+    fn sac_balance(holder: &str, amount: i128) -> xdr::LedgerEntry {
+        let symbol = |s: &str| xdr::ScVal::Symbol(s.try_into().unwrap());
+        entry(xdr::LedgerEntryData::ContractData(xdr::ContractDataEntry {
+            ext: xdr::ExtensionPoint::V0,
+            contract: xdr::ScAddress::from_str(CONTRACT).unwrap(),
+            key: xdr::ScVal::Vec(Some(
+                vec![
+                    symbol("Balance"),
+                    xdr::ScVal::Address(xdr::ScAddress::from_str(holder).unwrap()),
+                ]
+                .try_into()
+                .unwrap(),
+            )),
+            durability: xdr::ContractDataDurability::Persistent,
+            val: xdr::ScVal::Map(Some(
+                vec![
+                    xdr::ScMapEntry {
+                        key: symbol("amount"),
+                        val: xdr::ScVal::I128(xdr::Int128Parts {
+                            hi: (amount >> 64) as i64,
+                            lo: amount as u64,
+                        }),
+                    },
+                    xdr::ScMapEntry {
+                        key: symbol("authorized"),
+                        val: xdr::ScVal::Bool(true),
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            )),
+        }))
+    }
+
+    #[test]
+    fn test_native_and_credit_balance_changes() {
+        let changes = [
+            xdr::LedgerEntryChange::State(account(ALICE, 1_000)),
+            xdr::LedgerEntryChange::Updated(account(ALICE, 900)),
+            xdr::LedgerEntryChange::State(trust_line(ALICE, usd().into(), 50)),
+            xdr::LedgerEntryChange::Updated(trust_line(ALICE, usd().into(), 80)),
+            // An account touched without a balance change has no effect.
+            xdr::LedgerEntryChange::State(account(BOB, 10)),
+            xdr::LedgerEntryChange::Updated(account(BOB, 10)),
+        ];
+        let effects = LedgerEffect::from_changes(&changes).unwrap();
+        assert_eq!(
+            effects,
+            [
+                LedgerEffect::BalanceChanged(BalanceChange {
+                    account: ALICE.to_string(),
+                    asset: BalanceAsset::Asset(Asset::native()),
+                    before: 1_000,
+                    after: 900,
+                }),
+                LedgerEffect::BalanceChanged(BalanceChange {
+                    account: ALICE.to_string(),
+                    asset: BalanceAsset::Asset(usd()),
+                    before: 50,
+                    after: 80,
+                }),
+            ]
+        );
+        let LedgerEffect::BalanceChanged(change) = &effects[0] else {
+            panic!("expected a balance change");
+        };
+        assert_eq!(change.amount(), -100);
+    }
+
+    #[test]
+    fn test_created_and_removed_entries() {
+        let pool = xdr::TrustLineAsset::PoolShare(xdr::PoolId(xdr::Hash([7; 32])));
+        let pool_key = trust_line(BOB, pool.clone(), 0).to_key();
+        let offer = entry(xdr::LedgerEntryData::Offer(xdr::OfferEntry {
+            seller_id: account_id(ALICE),
+            offer_id: 12,
+            selling: xdr::Asset::Native,
+            buying: usd().to_xdr_object(),
+            amount: 10,
+            price: xdr::Price { n: 1, d: 1 },
+            flags: 0,
+            ext: xdr::OfferEntryExt::V0,
+        }));
+        let balance_id = xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash([3; 32]));
+        let claimable_balance = entry(xdr::LedgerEntryData::ClaimableBalance(
+            xdr::ClaimableBalanceEntry {
+                balance_id: balance_id.clone(),
+                claimants: Default::default(),
+                asset: usd().to_xdr_object(),
+                amount: 25,
+                ext: xdr::ClaimableBalanceEntryExt::V0,
+            },
+        ));
+
+        let changes = [
+            xdr::LedgerEntryChange::Created(trust_line(ALICE, usd().into(), 0)),
+            xdr::LedgerEntryChange::State(trust_line(BOB, pool, 5)),
+            xdr::LedgerEntryChange::Removed(pool_key),
+            xdr::LedgerEntryChange::Created(offer.clone()),
+            xdr::LedgerEntryChange::State(offer.clone()),
+            xdr::LedgerEntryChange::Removed(offer.to_key()),
+            xdr::LedgerEntryChange::Created(claimable_balance.clone()),
+            xdr::LedgerEntryChange::State(claimable_balance.clone()),
+            xdr::LedgerEntryChange::Removed(claimable_balance.to_key()),
+        ];
+        let pool_share = BalanceAsset::PoolShare(hex::encode([7; 32]));
+        let balance_id = ClaimableBalanceId::from_xdr_object(&balance_id).to_hex();
+        assert_eq!(
+            LedgerEffect::from_changes(&changes).unwrap(),
+            [
+                LedgerEffect::TrustlineCreated {
+                    account: ALICE.to_string(),
+                    asset: BalanceAsset::Asset(usd()),
+                },
+                LedgerEffect::BalanceChanged(BalanceChange {
+                    account: BOB.to_string(),
+                    asset: pool_share.clone(),
+                    before: 5,
+                    after: 0,
+                }),
+                LedgerEffect::TrustlineRemoved {
+                    account: BOB.to_string(),
+                    asset: pool_share,
+                },
+                LedgerEffect::OfferCreated {
+                    seller: ALICE.to_string(),
+                    offer_id: 12,
+                },
+                LedgerEffect::OfferRemoved {
+                    seller: ALICE.to_string(),
+                    offer_id: 12,
+                },
+                LedgerEffect::ClaimableBalanceCreated {
+                    balance_id: balance_id.clone(),
+                    asset: usd(),
+                    amount: 25,
+                },
+                LedgerEffect::ClaimableBalanceRemoved { balance_id },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sac_balance_changes() {
+        let big = i128::from(u64::MAX) + 5;
+        let changes = [
+            xdr::LedgerEntryChange::State(sac_balance(ALICE, big)),
+            xdr::LedgerEntryChange::Updated(sac_balance(ALICE, 5)),
+            xdr::LedgerEntryChange::Created(sac_balance(CONTRACT, big - 5)),
+        ];
+        let contract = BalanceAsset::Contract(CONTRACT.to_string());
+        assert_eq!(
+            LedgerEffect::from_changes(&changes).unwrap(),
+            [
+                LedgerEffect::BalanceChanged(BalanceChange {
+                    account: ALICE.to_string(),
+                    asset: contract.clone(),
+                    before: big,
+                    after: 5,
+                }),
+                LedgerEffect::BalanceChanged(BalanceChange {
+                    account: CONTRACT.to_string(),
+                    asset: contract,
+                    before: 0,
+                    after: big - 5,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_balance_changes_from_meta() {
+        let meta = xdr::TransactionMeta::V3(xdr::TransactionMetaV3 {
+            ext: xdr::ExtensionPoint::V0,
+            tx_changes_before: vec![
+                xdr::LedgerEntryChange::State(account(ALICE, 1_000)),
+                xdr::LedgerEntryChange::Updated(account(ALICE, 999)),
+            ]
+            .try_into()
+            .unwrap(),
+            operations: vec![xdr::OperationMeta {
+                changes: vec![
+                    xdr::LedgerEntryChange::Created(account(BOB, 500)),
+                    xdr::LedgerEntryChange::State(account(ALICE, 999)),
+                    xdr::LedgerEntryChange::Updated(account(ALICE, 499)),
+                ]
+                .try_into()
+                .unwrap(),
+            }]
+            .try_into()
+            .unwrap(),
+            tx_changes_after: Default::default(),
+            soroban_meta: None,
+        });
+        let changes: Vec<(String, i128)> = balance_changes(&meta)
+            .unwrap()
+            .iter()
+            .map(|change| (change.account.clone(), change.amount()))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (ALICE.to_string(), -1),
+                (BOB.to_string(), 500),
+                (ALICE.to_string(), -500),
+            ]
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! SEP-2 federation addresses and lookups.
+//!
+//! See <https://github.com/stellar/stellar-protocol/blob/master/ecosystem/sep-0002.md>.
+use std::fmt;
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::keypair::{Keypair, KeypairBehavior};
+use crate::xdr;
+
+/// A `name*domain.com` federation address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FederationAddress {
+    pub name: String,
+    pub domain: String,
+}
+
+/// A federation server query, serialized as `type` and `q` request parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FederationRequest {
+    Name(FederationAddress),
+    Id(String),
+    Txid(String),
+    Forward(Vec<(String, String)>),
+}
+
+/// The JSON body of a successful federation lookup.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FederationResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stellar_address: Option<String>,
+    pub account_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+// Define a trait for FederationAddress behavior
+pub trait FederationAddressBehavior {
+    fn new(name: &str, domain: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn parse(address: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+}
+
+// Define a trait for FederationRequest behavior
+pub trait FederationRequestBehavior {
+    fn query(&self) -> Vec<(String, String)>;
+}
+
+// Define a trait for FederationResponse behavior
+pub trait FederationResponseBehavior {
+    fn from_json(json: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn to_destination(&self) -> Result<(String, xdr::Memo), String>;
+}
+
+/// Resolves federation requests, typically against the `FEDERATION_SERVER` of a domain.
+pub trait FederationLookup {
+    fn lookup(&self, request: &FederationRequest) -> Result<FederationResponse, String>;
+}
+
+fn is_valid_domain(domain: &str) -> bool {
+    domain.len() <= 253
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+impl FederationAddressBehavior for FederationAddress {
+    fn new(name: &str, domain: &str) -> Result<Self, String> {
+        if name.is_empty() || name.contains(['*', '>']) || name.chars().any(char::is_whitespace) {
+            return Err(format!("invalid federation name: {}", name));
+        }
+        if !is_valid_domain(domain) {
+            return Err(format!("invalid federation domain: {}", domain));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            domain: domain.to_lowercase(),
+        })
+    }
+
+    /// Parses a `name*domain.com` address. The name may itself contain an `@`, as in
+    /// `jed@stellar.org*stellar.org`.
+    fn parse(address: &str) -> Result<Self, String> {
+        let (name, domain) = address
+            .rsplit_once('*')
+            .ok_or_else(|| format!("invalid federation address: {}", address))?;
+        Self::new(name, domain)
+    }
+}
+
+impl FromStr for FederationAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for FederationAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}*{}", self.name, self.domain)
+    }
+}
+
+impl FederationRequestBehavior for FederationRequest {
+    /// Returns the query parameters of the request.
+    fn query(&self) -> Vec<(String, String)> {
+        let (kind, q) = match self {
+            Self::Name(address) => ("name", address.to_string()),
+            Self::Id(account_id) => ("id", account_id.clone()),
+            Self::Txid(txid) => ("txid", txid.clone()),
+            Self::Forward(params) => {
+                let mut query = vec![("type".to_string(), "forward".to_string())];
+                query.extend(params.iter().cloned());
+                return query;
+            }
+        };
+        vec![("q".to_string(), q), ("type".to_string(), kind.to_string())]
+    }
+}
+
+impl FederationResponseBehavior for FederationResponse {
+    fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// Returns the account to pay and the memo to attach, `Memo::None` when the server
+    /// gave none. Hash memos are base64 encoded as the SEP requires.
+    fn to_destination(&self) -> Result<(String, xdr::Memo), String> {
+        Keypair::from_public_key(&self.account_id)
+            .map_err(|_| format!("invalid account_id: {}", self.account_id))?;
+
+        let memo = match (self.memo_type.as_deref(), self.memo.as_deref()) {
+            (None, None) => xdr::Memo::None,
+            (Some("id"), Some(memo)) => xdr::Memo::Id(
+                memo.parse()
+                    .map_err(|_| format!("invalid id memo: {}", memo))?,
+            ),
+            (Some("text"), Some(memo)) => xdr::Memo::Text(
+                memo.try_into()
+                    .map_err(|_| "text memo is longer than 28 bytes".to_string())?,
+            ),
+            (Some("hash"), Some(memo)) => {
+                let hash = STANDARD
+                    .decode(memo)
+                    .ok()
+                    .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+                    .ok_or_else(|| format!("invalid hash memo: {}", memo))?;
+                xdr::Memo::Hash(xdr::Hash(hash))
+            }
+            (Some(memo_type), Some(_)) => {
+                return Err(format!("unsupported memo_type: {}", memo_type))
+            }
+            _ => return Err("memo and memo_type must be set together".to_string()),
+        };
+
+        Ok((self.account_id.clone(), memo))
+    }
+}
+
+/// A federation lookup answering from a fixed set of records.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryFederation {
+    pub records: Vec<FederationResponse>,
+}
+
+impl FederationLookup for InMemoryFederation {
+    fn lookup(&self, request: &FederationRequest) -> Result<FederationResponse, String> {
+        let record = match request {
+            FederationRequest::Name(address) => {
+                let address = address.to_string();
+                self.records
+                    .iter()
+                    .find(|record| record.stellar_address.as_deref() == Some(address.as_str()))
+            }
+            FederationRequest::Id(account_id) => self
+                .records
+                .iter()
+                .find(|record| &record.account_id == account_id),
+            FederationRequest::Txid(_) | FederationRequest::Forward(_) => {
+                return Err("not implemented".to_string())
+            }
+        };
+        record.cloned().ok_or_else(|| "not found".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+
+    #[test]
+    fn test_parse_address() {
+        let address = FederationAddress::parse("jed@stellar.org*Stellar.org").unwrap();
+        assert_eq!(address.name, "jed@stellar.org");
+        assert_eq!(address.domain, "stellar.org");
+        assert_eq!(address.to_string(), "jed@stellar.org*stellar.org");
+        assert_eq!(
+            "bob*example.com".parse::<FederationAddress>().unwrap(),
+            FederationAddress::new("bob", "example.com").unwrap()
+        );
+
+        for invalid in [
+            "bob",
+            "*example.com",
+            "bob*",
+            "bob smith*example.com",
+            "a*b*example.com",
+            "bob*exa_mple.com",
+            "bob*example..com",
+            "bob*-example.com",
+        ] {
+            assert!(FederationAddress::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_request_query() {
+        let request = FederationRequest::Name(FederationAddress::parse("bob*example.com").unwrap());
+        assert_eq!(
+            request.query(),
+            vec![
+                ("q".to_string(), "bob*example.com".to_string()),
+                ("type".to_string(), "name".to_string())
+            ]
+        );
+
+        let request = FederationRequest::Forward(vec![
+            ("forward_type".to_string(), "bank_account".to_string()),
+            ("swift".to_string(), "BOPBPHMM".to_string()),
+        ]);
+        assert_eq!(
+            request.query()[0],
+            ("type".to_string(), "forward".to_string())
+        );
+        assert_eq!(request.query().len(), 3);
+    }
+
+    #[test]
+    fn test_response_to_destination() {
+        let response = FederationResponse::from_json(&format!(
+            r#"{{"stellar_address":"bob*example.com","account_id":"{}","memo_type":"id","memo":"123"}}"#,
+            ACCOUNT
+        ))
+        .unwrap();
+        assert_eq!(
+            response.to_destination().unwrap(),
+            (ACCOUNT.to_string(), xdr::Memo::Id(123))
+        );
+
+        let response = FederationResponse {
+            account_id: ACCOUNT.to_string(),
+            memo_type: Some("text".to_string()),
+            memo: Some("hello".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            response.to_destination().unwrap().1,
+            xdr::Memo::Text("hello".try_into().unwrap())
+        );
+
+        let response = FederationResponse {
+            account_id: ACCOUNT.to_string(),
+            memo_type: Some("hash".to_string()),
+            memo: Some(STANDARD.encode([7; 32])),
+            ..Default::default()
+        };
+        assert_eq!(
+            response.to_destination().unwrap().1,
+            xdr::Memo::Hash(xdr::Hash([7; 32]))
+        );
+
+        let response = FederationResponse {
+            account_id: ACCOUNT.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(response.to_destination().unwrap().1, xdr::Memo::None);
+    }
+
+    #[test]
+    fn test_response_rejects_invalid_values() {
+        let response = |account_id: &str, memo_type: Option<&str>, memo: Option<&str>| {
+            FederationResponse {
+                stellar_address: None,
+                account_id: account_id.to_string(),
+                memo_type: memo_type.map(str::to_string),
+                memo: memo.map(str::to_string),
+            }
+            .to_destination()
+            .unwrap_err()
+        };
+
+        assert_eq!(response("GABC", None, None), "invalid account_id: GABC");
+        assert_eq!(
+            response(ACCOUNT, Some("id"), Some("-1")),
+            "invalid id memo: -1"
+        );
+        assert_eq!(
+            response(ACCOUNT, Some("text"), Some(&"a".repeat(29))),
+            "text memo is longer than 28 bytes"
+        );
+        assert_eq!(
+            response(ACCOUNT, Some("hash"), Some("AAAA")),
+            "invalid hash memo: AAAA"
+        );
+        assert_eq!(
+            response(ACCOUNT, Some("return"), Some("AAAA")),
+            "unsupported memo_type: return"
+        );
+        assert_eq!(
+            response(ACCOUNT, Some("id"), None),
+            "memo and memo_type must be set together"
+        );
+    }
+
+    #[test]
+    fn test_in_memory_lookup() {
+        let federation = InMemoryFederation {
+            records: vec![FederationResponse {
+                stellar_address: Some("bob*example.com".to_string()),
+                account_id: ACCOUNT.to_string(),
+                memo_type: Some("id".to_string()),
+                memo: Some("1".to_string()),
+            }],
+        };
+        let address = FederationAddress::parse("bob*example.com").unwrap();
+
+        let by_name = federation
+            .lookup(&FederationRequest::Name(address))
+            .unwrap();
+        assert_eq!(by_name.account_id, ACCOUNT);
+        let by_id = federation
+            .lookup(&FederationRequest::Id(ACCOUNT.to_string()))
+            .unwrap();
+        assert_eq!(by_id, by_name);
+        assert_eq!(
+            federation
+                .lookup(&FederationRequest::Name(
+                    FederationAddress::parse("alice*example.com").unwrap()
+                ))
+                .unwrap_err(),
+            "not found"
+        );
+        assert!(federation
+            .lookup(&FederationRequest::Txid("00".to_string()))
+            .is_err());
+    }
+}
@@ -0,0 +1,371 @@
+//! Password protected storage for `Keypair` secrets.
+//!
+//! The password is stretched with scrypt (N = 16384, r = 8, p = 1) over a random 32 byte salt,
+//! and the secret seed is sealed with XSalsa20-Poly1305. The encrypted blob is the version
+//! byte, the 24 byte nonce and the ciphertext, base64 encoded. The format is specific to this
+//! crate; it is not the wallet SDK's `EncryptedKey` layout.
+use std::fs;
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand_core::{OsRng, TryRngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::keypair::{Keypair, KeypairBehavior};
+
+pub const KEYSTORE_VERSION: u8 = 1;
+pub const ENCRYPTER_NAME: &str = "scrypt-xsalsa20poly1305";
+const SALT_BYTES: usize = 32;
+const NONCE_BYTES: usize = 24;
+const MAC_BYTES: usize = 16;
+
+/// An encrypted secret, stored as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Keystore {
+    pub version: u8,
+    pub public_key: String,
+    pub encrypter_name: String,
+    pub salt: String,
+    pub encrypted_blob: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The password does not open the encrypted blob.
+    WrongPassword,
+    /// The keystore was modified after it was written.
+    Tampered(String),
+    UnsupportedVersion(u8),
+    UnsupportedEncrypter(String),
+    InvalidFormat(String),
+    Io(String),
+    Crypto(String),
+}
+
+// Define a trait for Keystore behavior
+pub trait KeystoreBehavior {
+    fn encrypt(keypair: &Keypair, password: &str) -> Result<Self, Error>
+    where
+        Self: Sized;
+    fn decrypt(&self, password: &str) -> Result<Keypair, Error>;
+    fn change_password(&mut self, old_password: &str, new_password: &str) -> Result<(), Error>;
+    fn to_json(&self) -> String;
+    fn from_json(json: &str) -> Result<Self, Error>
+    where
+        Self: Sized;
+    fn save(&self, path: impl AsRef<Path>) -> Result<(), Error>;
+    fn load(path: impl AsRef<Path>) -> Result<Self, Error>
+    where
+        Self: Sized;
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], Error> {
+    let mut bytes = [0u8; N];
+    OsRng
+        .try_fill_bytes(&mut bytes)
+        .map_err(|e| Error::Crypto(e.to_string()))?;
+    Ok(bytes)
+}
+
+impl KeystoreBehavior for Keystore {
+    /// Encrypts the secret of `keypair` with a fresh salt and nonce.
+    fn encrypt(keypair: &Keypair, password: &str) -> Result<Self, Error> {
+        let secret = keypair
+            .secret_key()
+            .map_err(|_| Error::Crypto("keypair has no secret key".into()))?;
+        let salt = random_bytes::<SALT_BYTES>()?;
+        let nonce = random_bytes::<NONCE_BYTES>()?;
+        let key = crypto::derive_key(password.as_bytes(), &salt)?;
+
+        let mut blob = vec![KEYSTORE_VERSION];
+        blob.extend_from_slice(&nonce);
+        blob.extend(crypto::seal(secret.as_bytes(), &nonce, &key)?);
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            public_key: keypair.public_key(),
+            encrypter_name: ENCRYPTER_NAME.to_string(),
+            salt: STANDARD.encode(salt),
+            encrypted_blob: STANDARD.encode(blob),
+        })
+    }
+
+    /// Decrypts the secret. The keypair must match the public key recorded next to it.
+    fn decrypt(&self, password: &str) -> Result<Keypair, Error> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(Error::UnsupportedVersion(self.version));
+        }
+        if self.encrypter_name != ENCRYPTER_NAME {
+            return Err(Error::UnsupportedEncrypter(self.encrypter_name.clone()));
+        }
+        let salt = STANDARD
+            .decode(&self.salt)
+            .map_err(|_| Error::InvalidFormat("salt is not valid base64".into()))?;
+        let blob = STANDARD
+            .decode(&self.encrypted_blob)
+            .map_err(|_| Error::InvalidFormat("encryptedBlob is not valid base64".into()))?;
+        if blob.len() < 1 + NONCE_BYTES + MAC_BYTES {
+            return Err(Error::InvalidFormat("encryptedBlob is too short".into()));
+        }
+        if blob[0] != self.version {
+            return Err(Error::Tampered(
+                "encryptedBlob version does not match".into(),
+            ));
+        }
+
+        let nonce: [u8; NONCE_BYTES] = blob[1..1 + NONCE_BYTES].try_into().unwrap();
+        let key = crypto::derive_key(password.as_bytes(), &salt)?;
+        let secret = crypto::open(&blob[1 + NONCE_BYTES..], &nonce, &key)?;
+
+        let keypair = std::str::from_utf8(&secret)
+            .ok()
+            .and_then(|secret| Keypair::from_secret(secret).ok())
+            .ok_or_else(|| Error::Tampered("encrypted secret is not a valid seed".into()))?;
+        if keypair.public_key() != self.public_key {
+            return Err(Error::Tampered(
+                "publicKey does not match the secret".into(),
+            ));
+        }
+        Ok(keypair)
+    }
+
+    /// Re-encrypts the secret under `new_password`, with a new salt and nonce.
+    fn change_password(&mut self, old_password: &str, new_password: &str) -> Result<(), Error> {
+        let keypair = self.decrypt(old_password)?;
+        *self = Self::encrypt(&keypair, new_password)?;
+        Ok(())
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|e| Error::InvalidFormat(e.to_string()))
+    }
+
+    fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        fs::write(path, self.to_json()).map_err(|e| Error::Io(e.to_string()))
+    }
+
+    fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let json = fs::read_to_string(path).map_err(|e| Error::Io(e.to_string()))?;
+        Self::from_json(&json)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod crypto {
+    use super::{Error, MAC_BYTES, NONCE_BYTES};
+
+    const SCRYPT_N: u64 = 16384;
+    const SCRYPT_R: u32 = 8;
+    const SCRYPT_P: u32 = 1;
+
+    pub fn derive_key(password: &[u8], salt: &[u8]) -> Result<[u8; 32], Error> {
+        let mut key = [0u8; 32];
+        let result = unsafe {
+            libsodium_sys::sodium_init();
+            libsodium_sys::crypto_pwhash_scryptsalsa208sha256_ll(
+                password.as_ptr(),
+                password.len(),
+                salt.as_ptr(),
+                salt.len(),
+                SCRYPT_N,
+                SCRYPT_R,
+                SCRYPT_P,
+                key.as_mut_ptr(),
+                key.len(),
+            )
+        };
+        if result != 0 {
+            return Err(Error::Crypto("scrypt key derivation failed".into()));
+        }
+        Ok(key)
+    }
+
+    pub fn seal(
+        message: &[u8],
+        nonce: &[u8; NONCE_BYTES],
+        key: &[u8; 32],
+    ) -> Result<Vec<u8>, Error> {
+        let mut ciphertext = vec![0u8; message.len() + MAC_BYTES];
+        let result = unsafe {
+            libsodium_sys::crypto_secretbox_easy(
+                ciphertext.as_mut_ptr(),
+                message.as_ptr(),
+                message.len() as libc::c_ulonglong,
+                nonce.as_ptr(),
+                key.as_ptr(),
+            )
+        };
+        if result != 0 {
+            return Err(Error::Crypto("encryption failed".into()));
+        }
+        Ok(ciphertext)
+    }
+
+    pub fn open(
+        ciphertext: &[u8],
+        nonce: &[u8; NONCE_BYTES],
+        key: &[u8; 32],
+    ) -> Result<Vec<u8>, Error> {
+        let mut message = vec![0u8; ciphertext.len() - MAC_BYTES];
+        let result = unsafe {
+            libsodium_sys::crypto_secretbox_open_easy(
+                message.as_mut_ptr(),
+                ciphertext.as_ptr(),
+                ciphertext.len() as libc::c_ulonglong,
+                nonce.as_ptr(),
+                key.as_ptr(),
+            )
+        };
+        // Poly1305 cannot tell a wrong key from a modified ciphertext
+        if result != 0 {
+            return Err(Error::WrongPassword);
+        }
+        Ok(message)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod crypto {
+    use super::{Error, NONCE_BYTES};
+
+    const UNSUPPORTED: &str = "keystores are not supported on wasm32";
+
+    pub fn derive_key(_password: &[u8], _salt: &[u8]) -> Result<[u8; 32], Error> {
+        Err(Error::Crypto(UNSUPPORTED.into()))
+    }
+
+    pub fn seal(
+        _message: &[u8],
+        _nonce: &[u8; NONCE_BYTES],
+        _key: &[u8; 32],
+    ) -> Result<Vec<u8>, Error> {
+        Err(Error::Crypto(UNSUPPORTED.into()))
+    }
+
+    pub fn open(
+        _ciphertext: &[u8],
+        _nonce: &[u8; NONCE_BYTES],
+        _key: &[u8; 32],
+    ) -> Result<Vec<u8>, Error> {
+        Err(Error::Crypto(UNSUPPORTED.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "SD7X7LEHBNMUIKQGKPARG5TDJNBHKC346OUARHGZL5ITC6IJPXHILY36";
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let keypair = Keypair::from_secret(SECRET).unwrap();
+        let keystore = Keystore::encrypt(&keypair, "correct horse").unwrap();
+
+        assert_eq!(keystore.version, KEYSTORE_VERSION);
+        assert_eq!(keystore.encrypter_name, ENCRYPTER_NAME);
+        assert_eq!(keystore.public_key, keypair.public_key());
+        assert!(!keystore.to_json().contains(SECRET));
+        assert_eq!(
+            keystore
+                .decrypt("correct horse")
+                .unwrap()
+                .secret_key()
+                .unwrap(),
+            SECRET
+        );
+
+        let other = Keystore::encrypt(&keypair, "correct horse").unwrap();
+        assert_ne!(other.salt, keystore.salt);
+        assert_ne!(other.encrypted_blob, keystore.encrypted_blob);
+    }
+
+    #[test]
+    fn test_wrong_password() {
+        let keystore = Keystore::encrypt(&Keypair::random().unwrap(), "correct horse").unwrap();
+
+        assert_eq!(
+            keystore.decrypt("battery staple").unwrap_err(),
+            Error::WrongPassword
+        );
+    }
+
+    #[test]
+    fn test_tampering() {
+        let keypair = Keypair::from_secret(SECRET).unwrap();
+        let keystore = Keystore::encrypt(&keypair, "pw").unwrap();
+
+        let mut swapped = keystore.clone();
+        swapped.public_key = Keypair::random().unwrap().public_key();
+        assert!(matches!(swapped.decrypt("pw"), Err(Error::Tampered(_))));
+
+        let mut blob = STANDARD.decode(&keystore.encrypted_blob).unwrap();
+        let last = blob.len() - 1;
+        blob[last] ^= 1;
+        let mut flipped = keystore.clone();
+        flipped.encrypted_blob = STANDARD.encode(&blob);
+        assert_eq!(flipped.decrypt("pw").unwrap_err(), Error::WrongPassword);
+
+        blob[0] = 2;
+        flipped.encrypted_blob = STANDARD.encode(&blob);
+        assert!(matches!(flipped.decrypt("pw"), Err(Error::Tampered(_))));
+
+        let mut truncated = keystore.clone();
+        truncated.encrypted_blob = STANDARD.encode([1, 2, 3]);
+        assert!(matches!(
+            truncated.decrypt("pw"),
+            Err(Error::InvalidFormat(_))
+        ));
+
+        let mut future = keystore;
+        future.version = 2;
+        assert_eq!(
+            future.decrypt("pw").unwrap_err(),
+            Error::UnsupportedVersion(2)
+        );
+    }
+
+    #[test]
+    fn test_change_password() {
+        let keypair = Keypair::from_secret(SECRET).unwrap();
+        let mut keystore = Keystore::encrypt(&keypair, "old").unwrap();
+
+        assert_eq!(
+            keystore.change_password("wrong", "new").unwrap_err(),
+            Error::WrongPassword
+        );
+        keystore.change_password("old", "new").unwrap();
+        assert_eq!(keystore.decrypt("old").unwrap_err(), Error::WrongPassword);
+        assert_eq!(
+            keystore.decrypt("new").unwrap().secret_key().unwrap(),
+            SECRET
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let keypair = Keypair::from_secret(SECRET).unwrap();
+        let keystore = Keystore::encrypt(&keypair, "pw").unwrap();
+        let path = std::env::temp_dir().join(format!("keystore-{}.json", keypair.public_key()));
+
+        keystore.save(&path).unwrap();
+        let loaded = Keystore::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, keystore);
+        assert_eq!(
+            loaded.decrypt("pw").unwrap().public_key(),
+            keypair.public_key()
+        );
+        assert!(matches!(Keystore::load(&path), Err(Error::Io(_))));
+        assert!(matches!(
+            Keystore::from_json("{}"),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
+}
@@ -10,27 +10,46 @@ pub mod address;
 /// Asset class represents an asset, either the native asset (`XLM`)
 /// or an asset code / issuer account ID pair
 pub mod asset;
+pub mod channel_pool;
+pub mod claimable_balance_id;
 pub mod claimant;
 /// `Contract` represents a single contract in the Stellar network
 pub mod contract;
+pub mod contract_wasm;
+pub mod effects;
+pub mod federation;
 pub mod get_liquidity_pool;
 pub mod hashing;
 pub mod keypair;
+pub mod keystore;
 pub mod liquidity_pool_asset;
 pub mod liquidity_pool_id;
+pub mod liquidity_pool_math;
 pub mod memo;
 pub mod muxed_account;
 pub mod network;
 pub mod operation;
+pub mod path_finding;
+pub mod sep10;
+pub mod sep29;
+pub mod sep7;
+pub mod signature_check;
 pub mod signer_key;
 pub mod signing;
 pub mod soroban;
 pub mod soroban_data_builder;
+pub mod soroban_fee;
+pub mod soroban_rent;
+pub mod stellar_toml;
 pub mod transaction;
+pub mod transaction_batcher;
 /// Builder pattern to construct new transactions
 /// that interact with Stellar environment
 pub mod transaction_builder;
+pub mod transaction_result;
+pub mod txrep;
 pub mod utils;
+pub mod xdr_stream;
 
 /// Re-exporting XDR from stellar-xdr
 pub mod xdr {
@@ -0,0 +1,579 @@
+//! Constant product liquidity pool math.
+//!
+//! Computes deposits, withdrawals and swaps against a snapshot of a pool the same way
+//! stellar-core does, including its rounding, so quotes match what the network will execute.
+use ethnum::U256;
+
+use crate::xdr;
+
+/// Fee in basis points of every constant product pool since protocol 18.
+pub const LIQUIDITY_POOL_FEE_V18: i32 = 30;
+const MAX_BPS: u128 = 10_000;
+
+/// A price as the `(numerator, denominator)` pair taken by `liquidity_pool_deposit`.
+pub type Price = (i32, i32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidityPoolState {
+    pub reserve_a: i64,
+    pub reserve_b: i64,
+    pub total_shares: i64,
+    fee_bps: u32,
+}
+
+/// The side of the pool an asset is sent to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolAsset {
+    A,
+    B,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositQuote {
+    pub amount_a: i64,
+    pub amount_b: i64,
+    pub shares: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawQuote {
+    pub amount_a: i64,
+    pub amount_b: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub to_pool: i64,
+    pub from_pool: i64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    InvalidAmount(i64),
+    InvalidSlippage(u32),
+    InvalidFee(i32),
+    EmptyPool,
+    InsufficientReserves,
+    BadPrice,
+    Overflow,
+}
+
+// Define a trait for LiquidityPoolState behavior
+pub trait LiquidityPoolStateBehavior {
+    fn new(reserve_a: i64, reserve_b: i64, total_shares: i64) -> Result<Self, Error>
+    where
+        Self: Sized;
+    fn with_fee(
+        reserve_a: i64,
+        reserve_b: i64,
+        total_shares: i64,
+        fee_bps: i32,
+    ) -> Result<Self, Error>
+    where
+        Self: Sized;
+    fn from_xdr_object(entry: &xdr::LiquidityPoolEntry) -> Result<Self, Error>
+    where
+        Self: Sized;
+    fn fee_bps(&self) -> u32;
+    fn deposit(&self, max_amount_a: i64, max_amount_b: i64) -> Result<DepositQuote, Error>;
+    fn withdraw(&self, shares: i64) -> Result<WithdrawQuote, Error>;
+    fn swap_strict_send(&self, send: PoolAsset, send_amount: i64) -> Result<SwapQuote, Error>;
+    fn swap_strict_receive(&self, send: PoolAsset, dest_amount: i64) -> Result<SwapQuote, Error>;
+    fn deposit_price_bounds(&self, slippage_bps: u32) -> Result<(Price, Price), Error>;
+    fn withdraw_min_amounts(&self, shares: i64, slippage_bps: u32) -> Result<(i64, i64), Error>;
+}
+
+impl LiquidityPoolStateBehavior for LiquidityPoolState {
+    /// Creates the state of a constant product pool with the protocol fee.
+    fn new(reserve_a: i64, reserve_b: i64, total_shares: i64) -> Result<Self, Error> {
+        Self::with_fee(reserve_a, reserve_b, total_shares, LIQUIDITY_POOL_FEE_V18)
+    }
+
+    /// Creates the state of a pool charging `fee_bps`, which must be below 10000.
+    fn with_fee(
+        reserve_a: i64,
+        reserve_b: i64,
+        total_shares: i64,
+        fee_bps: i32,
+    ) -> Result<Self, Error> {
+        for amount in [reserve_a, reserve_b, total_shares] {
+            if amount < 0 {
+                return Err(Error::InvalidAmount(amount));
+            }
+        }
+        let fee = u32::try_from(fee_bps)
+            .ok()
+            .filter(|fee| (*fee as u128) < MAX_BPS)
+            .ok_or(Error::InvalidFee(fee_bps))?;
+
+        Ok(Self {
+            reserve_a,
+            reserve_b,
+            total_shares,
+            fee_bps: fee,
+        })
+    }
+
+    fn from_xdr_object(entry: &xdr::LiquidityPoolEntry) -> Result<Self, Error> {
+        let xdr::LiquidityPoolEntryBody::LiquidityPoolConstantProduct(pool) = &entry.body;
+        let xdr::LiquidityPoolConstantProductParameters { fee, .. } = pool.params;
+
+        Self::with_fee(pool.reserve_a, pool.reserve_b, pool.total_pool_shares, fee)
+    }
+
+    fn fee_bps(&self) -> u32 {
+        self.fee_bps
+    }
+
+    /// Amounts actually deposited and shares received for a `liquidity_pool_deposit` of at
+    /// most `max_amount_a` and `max_amount_b`.
+    ///
+    /// The first deposit in an empty pool takes both maximums and mints `floor(sqrt(a * b))`
+    /// shares. Later deposits mint the largest number of shares both maximums allow and round
+    /// the deposited amounts up.
+    fn deposit(&self, max_amount_a: i64, max_amount_b: i64) -> Result<DepositQuote, Error> {
+        for amount in [max_amount_a, max_amount_b] {
+            if amount <= 0 {
+                return Err(Error::InvalidAmount(amount));
+            }
+        }
+
+        let quote = if self.total_shares == 0 {
+            DepositQuote {
+                amount_a: max_amount_a,
+                amount_b: max_amount_b,
+                shares: (max_amount_a as u128 * max_amount_b as u128).isqrt() as i64,
+            }
+        } else {
+            let shares_a = big_divide(
+                self.total_shares,
+                max_amount_a,
+                self.reserve_a,
+                Rounding::Down,
+            )
+            .unwrap_or(i64::MAX);
+            let shares_b = big_divide(
+                self.total_shares,
+                max_amount_b,
+                self.reserve_b,
+                Rounding::Down,
+            )
+            .unwrap_or(i64::MAX);
+            let shares = shares_a.min(shares_b);
+
+            DepositQuote {
+                amount_a: big_divide(shares, self.reserve_a, self.total_shares, Rounding::Up)
+                    .ok_or(Error::Overflow)?,
+                amount_b: big_divide(shares, self.reserve_b, self.total_shares, Rounding::Up)
+                    .ok_or(Error::Overflow)?,
+                shares,
+            }
+        };
+
+        if quote.amount_a > i64::MAX - self.reserve_a
+            || quote.amount_b > i64::MAX - self.reserve_b
+            || quote.shares > i64::MAX - self.total_shares
+        {
+            return Err(Error::Overflow);
+        }
+
+        Ok(quote)
+    }
+
+    /// Amounts received for burning `shares` in a `liquidity_pool_withdraw`, rounded down.
+    fn withdraw(&self, shares: i64) -> Result<WithdrawQuote, Error> {
+        if shares <= 0 {
+            return Err(Error::InvalidAmount(shares));
+        }
+        if self.total_shares == 0 {
+            return Err(Error::EmptyPool);
+        }
+        if shares > self.total_shares {
+            return Err(Error::InsufficientReserves);
+        }
+
+        Ok(WithdrawQuote {
+            amount_a: big_divide(shares, self.reserve_a, self.total_shares, Rounding::Down)
+                .ok_or(Error::Overflow)?,
+            amount_b: big_divide(shares, self.reserve_b, self.total_shares, Rounding::Down)
+                .ok_or(Error::Overflow)?,
+        })
+    }
+
+    /// Amount received from the pool when sending exactly `send_amount` of the `send` asset,
+    /// as in a `path_payment_strict_send` hop. Rounded down.
+    fn swap_strict_send(&self, send: PoolAsset, send_amount: i64) -> Result<SwapQuote, Error> {
+        if send_amount <= 0 {
+            return Err(Error::InvalidAmount(send_amount));
+        }
+        let (reserve_to, reserve_from) = self.reserves(send);
+        if reserve_to == 0 || reserve_from == 0 {
+            return Err(Error::EmptyPool);
+        }
+        if send_amount > i64::MAX - reserve_to {
+            return Err(Error::Overflow);
+        }
+
+        let fee_factor = MAX_BPS - self.fee_bps as u128;
+        let numerator =
+            U256::from(fee_factor * reserve_from as u128) * U256::from(send_amount as u128);
+        let denominator =
+            U256::from(MAX_BPS * reserve_to as u128) + U256::from(fee_factor * send_amount as u128);
+        let from_pool = to_i64(numerator / denominator)?;
+        if from_pool == 0 {
+            return Err(Error::InsufficientReserves);
+        }
+
+        Ok(SwapQuote {
+            to_pool: send_amount,
+            from_pool,
+        })
+    }
+
+    /// Amount of the `send` asset the pool requires to pay out exactly `dest_amount` of the
+    /// other asset, as in a `path_payment_strict_receive` hop. Rounded up.
+    fn swap_strict_receive(&self, send: PoolAsset, dest_amount: i64) -> Result<SwapQuote, Error> {
+        if dest_amount <= 0 {
+            return Err(Error::InvalidAmount(dest_amount));
+        }
+        let (reserve_to, reserve_from) = self.reserves(send);
+        if reserve_to == 0 || reserve_from == 0 {
+            return Err(Error::EmptyPool);
+        }
+        if dest_amount >= reserve_from {
+            return Err(Error::InsufficientReserves);
+        }
+
+        let fee_factor = MAX_BPS - self.fee_bps as u128;
+        let numerator = U256::from(MAX_BPS * reserve_to as u128) * U256::from(dest_amount as u128);
+        let denominator = U256::from(fee_factor * (reserve_from - dest_amount) as u128);
+        let to_pool = to_i64((numerator + denominator - 1) / denominator)?;
+        if to_pool > i64::MAX - reserve_to {
+            return Err(Error::Overflow);
+        }
+
+        Ok(SwapQuote {
+            to_pool,
+            from_pool: dest_amount,
+        })
+    }
+
+    /// `min_price` and `max_price` for a `liquidity_pool_deposit` that accepts the current
+    /// A/B price moving by at most `slippage_bps` basis points either way.
+    ///
+    /// Prices that do not fit `i32` are approximated outwards, so the bounds never get
+    /// tighter than requested.
+    fn deposit_price_bounds(&self, slippage_bps: u32) -> Result<(Price, Price), Error> {
+        if slippage_bps as u128 >= MAX_BPS {
+            return Err(Error::InvalidSlippage(slippage_bps));
+        }
+        if self.reserve_a == 0 || self.reserve_b == 0 {
+            return Err(Error::EmptyPool);
+        }
+
+        let slippage = slippage_bps as u128;
+        let min_price = to_price(
+            self.reserve_a as u128 * (MAX_BPS - slippage),
+            self.reserve_b as u128 * MAX_BPS,
+            Rounding::Down,
+        )?;
+        let max_price = to_price(
+            self.reserve_a as u128 * (MAX_BPS + slippage),
+            self.reserve_b as u128 * MAX_BPS,
+            Rounding::Up,
+        )?;
+
+        Ok((min_price, max_price))
+    }
+
+    /// `min_amount_a` and `min_amount_b` for a `liquidity_pool_withdraw` of `shares` that
+    /// accepts receiving up to `slippage_bps` basis points less than the current quote.
+    fn withdraw_min_amounts(&self, shares: i64, slippage_bps: u32) -> Result<(i64, i64), Error> {
+        if slippage_bps as u128 >= MAX_BPS {
+            return Err(Error::InvalidSlippage(slippage_bps));
+        }
+        let quote = self.withdraw(shares)?;
+        let keep = (MAX_BPS - slippage_bps as u128) as i64;
+
+        Ok((
+            big_divide(quote.amount_a, keep, MAX_BPS as i64, Rounding::Down)
+                .ok_or(Error::Overflow)?,
+            big_divide(quote.amount_b, keep, MAX_BPS as i64, Rounding::Down)
+                .ok_or(Error::Overflow)?,
+        ))
+    }
+}
+
+impl LiquidityPoolState {
+    // Returns the reserves as (sent to the pool, received from the pool)
+    fn reserves(&self, send: PoolAsset) -> (i64, i64) {
+        match send {
+            PoolAsset::A => (self.reserve_a, self.reserve_b),
+            PoolAsset::B => (self.reserve_b, self.reserve_a),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Rounding {
+    Down,
+    Up,
+}
+
+// `a * b / c` computed on 128 bits, `None` when `c` is zero or the result overflows i64
+fn big_divide(a: i64, b: i64, c: i64, rounding: Rounding) -> Option<i64> {
+    if a < 0 || b < 0 || c <= 0 {
+        return None;
+    }
+    let numerator = a as u128 * b as u128;
+    let result = match rounding {
+        Rounding::Down => numerator / c as u128,
+        Rounding::Up => numerator.div_ceil(c as u128),
+    };
+    i64::try_from(result).ok()
+}
+
+fn to_i64(value: U256) -> Result<i64, Error> {
+    u128::try_from(value)
+        .ok()
+        .and_then(|v| i64::try_from(v).ok())
+        .ok_or(Error::Overflow)
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// Reduces `n / d` to an `i32` fraction, rounding the approximation in the given direction
+fn to_price(n: u128, d: u128, rounding: Rounding) -> Result<Price, Error> {
+    let divisor = gcd(n, d);
+    let (mut n, mut d) = (n / divisor, d / divisor);
+    let max = i32::MAX as u128;
+
+    if n > max || d > max {
+        let scale = n.max(d).div_ceil(max);
+        (n, d) = match rounding {
+            Rounding::Down => (n / scale, d.div_ceil(scale)),
+            Rounding::Up => (n.div_ceil(scale), d / scale),
+        };
+        if n > max || d > max {
+            return Err(Error::BadPrice);
+        }
+    }
+    if n == 0 || d == 0 {
+        return Err(Error::BadPrice);
+    }
+
+    Ok((n as i32, d as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(reserve_a: i64, reserve_b: i64, total_shares: i64) -> LiquidityPoolState {
+        LiquidityPoolState::new(reserve_a, reserve_b, total_shares).unwrap()
+    }
+
+    #[test]
+    fn test_deposit_into_empty_pool() {
+        let quote = pool(0, 0, 0).deposit(100, 400).unwrap();
+
+        assert_eq!(
+            quote,
+            DepositQuote {
+                amount_a: 100,
+                amount_b: 400,
+                shares: 200,
+            }
+        );
+
+        // floor(sqrt(2 * 3)) = 2
+        assert_eq!(pool(0, 0, 0).deposit(2, 3).unwrap().shares, 2);
+    }
+
+    #[test]
+    fn test_deposit_into_pool_rounds_amounts_up() {
+        let quote = pool(1_000, 3_000, 1_000).deposit(10, 100).unwrap();
+
+        // limited by A: 10 * 1000 / 1000 shares, B rounded up from 30
+        assert_eq!(
+            quote,
+            DepositQuote {
+                amount_a: 10,
+                amount_b: 30,
+                shares: 10,
+            }
+        );
+
+        let quote = pool(3, 7, 5).deposit(2, 100).unwrap();
+        // shares = floor(5 * 2 / 3) = 3, a = ceil(3 * 3 / 5) = 2, b = ceil(3 * 7 / 5) = 5
+        assert_eq!(
+            quote,
+            DepositQuote {
+                amount_a: 2,
+                amount_b: 5,
+                shares: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_deposit_invalid_amount() {
+        assert_eq!(
+            pool(1, 1, 1).deposit(0, 10).unwrap_err(),
+            Error::InvalidAmount(0)
+        );
+    }
+
+    #[test]
+    fn test_withdraw_rounds_down() {
+        let quote = pool(3, 7, 5).withdraw(2).unwrap();
+
+        assert_eq!(
+            quote,
+            WithdrawQuote {
+                amount_a: 1,
+                amount_b: 2,
+            }
+        );
+        assert_eq!(
+            pool(3, 7, 5).withdraw(6).unwrap_err(),
+            Error::InsufficientReserves
+        );
+        assert_eq!(pool(0, 0, 0).withdraw(1).unwrap_err(), Error::EmptyPool);
+    }
+
+    #[test]
+    fn test_swap_strict_send() {
+        let state = pool(1_000_000, 2_000_000, 1_000_000);
+        let quote = state.swap_strict_send(PoolAsset::A, 10_000).unwrap();
+
+        // 9970 * 2_000_000 * 10_000 / (10_000 * 1_000_000 + 9970 * 10_000)
+        assert_eq!(quote.to_pool, 10_000);
+        assert_eq!(quote.from_pool, 19_743);
+
+        let quote = state.swap_strict_send(PoolAsset::B, 10_000).unwrap();
+        assert_eq!(quote.from_pool, 4_960);
+    }
+
+    #[test]
+    fn test_swap_strict_receive() {
+        let state = pool(1_000_000, 2_000_000, 1_000_000);
+        let quote = state.swap_strict_receive(PoolAsset::A, 19_743).unwrap();
+
+        // ceil(10_000 * 1_000_000 * 19_743 / (9970 * (2_000_000 - 19_743)))
+        assert_eq!(quote.from_pool, 19_743);
+        assert_eq!(quote.to_pool, 10_000);
+
+        assert_eq!(
+            state
+                .swap_strict_receive(PoolAsset::A, 2_000_000)
+                .unwrap_err(),
+            Error::InsufficientReserves
+        );
+    }
+
+    #[test]
+    fn test_swap_large_reserves_do_not_overflow() {
+        let state = pool(i64::MAX / 2, i64::MAX / 2, i64::MAX / 2);
+        let quote = state.swap_strict_send(PoolAsset::A, i64::MAX / 4).unwrap();
+
+        assert!(quote.from_pool > 0 && quote.from_pool < i64::MAX / 4);
+        assert_eq!(
+            state.swap_strict_send(PoolAsset::A, i64::MAX).unwrap_err(),
+            Error::Overflow
+        );
+    }
+
+    #[test]
+    fn test_deposit_price_bounds() {
+        let state = pool(1_000, 2_000, 1_000);
+        let (min_price, max_price) = state.deposit_price_bounds(100).unwrap();
+
+        assert_eq!(min_price, (99, 200));
+        assert_eq!(max_price, (101, 200));
+
+        let quote = state.deposit(10, 20).unwrap();
+        let op = crate::operation::Operation::new().liquidity_pool_deposit(
+            &hex::encode([1; 32]),
+            quote.amount_a,
+            quote.amount_b,
+            min_price,
+            max_price,
+        );
+        assert!(op.is_ok());
+    }
+
+    #[test]
+    fn test_deposit_price_bounds_large_reserves() {
+        // the real price is far above i32::MAX
+        let state = pool(i64::MAX - 1, 3, 1);
+        assert_eq!(state.deposit_price_bounds(0).unwrap_err(), Error::BadPrice);
+
+        // 1_000_000_007 / 2_147_483_659 is approximated outwards
+        let state = pool(1_000_000_007, 2_147_483_659, 1);
+        let ((min_n, min_d), (max_n, max_d)) = state.deposit_price_bounds(0).unwrap();
+        assert!((min_n as i128) * 2_147_483_659 <= 1_000_000_007 * (min_d as i128));
+        assert!((max_n as i128) * 2_147_483_659 >= 1_000_000_007 * (max_d as i128));
+    }
+
+    #[test]
+    fn test_withdraw_min_amounts() {
+        let state = pool(1_000_000, 2_000_000, 1_000_000);
+
+        assert_eq!(state.withdraw_min_amounts(1_000, 50).unwrap(), (995, 1_990));
+        assert_eq!(
+            state.withdraw_min_amounts(1_000, 10_001).unwrap_err(),
+            Error::InvalidSlippage(10_001)
+        );
+        assert_eq!(
+            state.withdraw_min_amounts(1_000, 10_000).unwrap_err(),
+            Error::InvalidSlippage(10_000)
+        );
+        assert_eq!(
+            state.deposit_price_bounds(10_000).unwrap_err(),
+            Error::InvalidSlippage(10_000)
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_fees() {
+        assert_eq!(
+            LiquidityPoolState::with_fee(10, 10, 10, -1).unwrap_err(),
+            Error::InvalidFee(-1)
+        );
+        assert_eq!(
+            LiquidityPoolState::with_fee(10, 10, 10, 10_000).unwrap_err(),
+            Error::InvalidFee(10_000)
+        );
+
+        let entry = xdr::LiquidityPoolEntry {
+            liquidity_pool_id: xdr::PoolId(xdr::Hash([0; 32])),
+            body: xdr::LiquidityPoolEntryBody::LiquidityPoolConstantProduct(
+                xdr::LiquidityPoolEntryConstantProduct {
+                    params: xdr::LiquidityPoolConstantProductParameters {
+                        asset_a: xdr::Asset::Native,
+                        asset_b: xdr::Asset::Native,
+                        fee: 20_000,
+                    },
+                    reserve_a: 1_000,
+                    reserve_b: 1_000,
+                    total_pool_shares: 1_000,
+                    pool_shares_trust_line_count: 1,
+                },
+            ),
+        };
+        assert_eq!(
+            LiquidityPoolState::from_xdr_object(&entry).unwrap_err(),
+            Error::InvalidFee(20_000)
+        );
+
+        let state = LiquidityPoolState::with_fee(1_000, 1_000, 1_000, 100).unwrap();
+        assert_eq!(state.fee_bps(), 100);
+        assert!(state.swap_strict_send(PoolAsset::A, 10).is_ok());
+    }
+}
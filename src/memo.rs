@@ -1,7 +1,6 @@
-use std::str::FromStr;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::xdr;
-use num_traits::ToPrimitive;
 
 const MEMO_NONE: &str = "none";
 const MEMO_ID: &str = "id";
@@ -17,10 +16,12 @@ pub enum MemoValue {
     ReturnValue(Vec<u8>),
 }
 
-#[derive(Debug)]
+/// A memo of any type. Values are kept as bytes: text memos are arbitrary bytes on the network
+/// and hash and return memos are 32 raw bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Memo {
     memo_type: String,
-    value: Option<String>,
+    value: Option<Vec<u8>>,
 }
 
 // Define a trait for Memo behavior
@@ -28,19 +29,19 @@ pub trait MemoBehavior {
     fn new(memo_type: &str, value: Option<&str>) -> Result<Self, Box<dyn std::error::Error>>
     where
         Self: Sized;
-    fn id(input: &str) -> Self
+    fn id(input: &str) -> Result<Self, String>
     where
         Self: Sized;
-    fn text(input: &str) -> Self
+    fn text(input: &str) -> Result<Self, String>
     where
         Self: Sized;
-    fn text_buffer(input: Vec<u8>) -> Self
+    fn text_buffer(input: Vec<u8>) -> Result<Self, String>
     where
         Self: Sized;
-    fn hash_buffer(input: Vec<u8>) -> Self
+    fn hash_buffer(input: Vec<u8>) -> Result<Self, String>
     where
         Self: Sized;
-    fn return_hash(input: Vec<u8>) -> Self
+    fn return_hash(input: Vec<u8>) -> Result<Self, String>
     where
         Self: Sized;
     fn none() -> Self
@@ -52,141 +53,80 @@ pub trait MemoBehavior {
         Self: Sized;
     fn to_xdr_object(&self) -> Option<xdr::Memo>;
     fn _validate_id_value(value: &str) -> Result<(), String>;
-    fn _validate_text_value(value: &str);
-    fn _validate_hash_value(value: &[u8]);
+    fn _validate_text_value(value: &[u8]) -> Result<(), String>;
+    fn _validate_hash_value(value: &[u8]) -> Result<(), String>;
 }
 
 impl MemoBehavior for Memo {
+    /// Creates a memo, returning an error when the value does not fit the memo type. Hash and
+    /// return values are either 32 raw bytes or 64 hex characters.
     fn new(memo_type: &str, value: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut value_buf = None;
-        match memo_type {
-            MEMO_NONE => {}
-            MEMO_ID => {
-                Self::_validate_id_value(value.expect("Expected a value for MEMO_ID"));
-                if let Some(v) = value {
-                    unsafe {
-                        value_buf = Some(String::from_utf8_unchecked(v.into()));
-                    }
-                }
-            }
-            MEMO_TEXT => {
-                Self::_validate_text_value(value.expect("Expected a value for MEMO_TEXT"));
-                if let Some(v) = value {
-                    unsafe {
-                        value_buf = Some(String::from_utf8_unchecked(v.into()));
-                    }
-                }
-            }
-            MEMO_HASH | MEMO_RETURN => {
-                Self::_validate_hash_value(unsafe {
-                    String::from_utf8_unchecked(value.unwrap().as_bytes().to_vec()).as_bytes()
-                });
-                if let Some(v) = value {
-                    value_buf = Some(v.into());
-                }
-            }
-            _ => return Err("Invalid memo type".into()),
+        if memo_type == MEMO_NONE {
+            return Ok(Self::none());
         }
-
-        Ok(Memo {
-            memo_type: memo_type.to_string(),
-            value: value_buf,
-        })
+        if ![MEMO_ID, MEMO_TEXT, MEMO_HASH, MEMO_RETURN].contains(&memo_type) {
+            return Err("Invalid memo type".into());
+        }
+        let value = value.ok_or_else(|| format!("Expected a value for a {} memo", memo_type))?;
+        Ok(match memo_type {
+            MEMO_ID => Self::id(value),
+            MEMO_TEXT => Self::text(value),
+            MEMO_HASH => Self::hash_buffer(value.as_bytes().to_vec()),
+            _ => Self::return_hash(value.as_bytes().to_vec()),
+        }?)
     }
 
     fn _validate_id_value(value: &str) -> Result<(), String> {
-        let error = format!("Expects an int64 as a string. Got {}", value);
-
-        let number = match value.parse::<i64>() {
-            Ok(num) => num,
-            Err(_) => return Err(error.clone()),
-        };
-
-        Ok(())
-    }
-
-    fn _validate_text_value(value: &str) {
-        assert!(value.len() <= 28, "String is longer than 28 bytes");
-        let _ = xdr::Memo::Text(value.try_into().unwrap());
+        value
+            .parse::<u64>()
+            .map(|_| ())
+            .map_err(|_| format!("Expects an uint64 as a string. Got {}", value))
     }
 
-    fn id(input: &str) -> Self {
-        unsafe {
-            Memo {
-                memo_type: MEMO_ID.to_string(),
-                value: Some(String::from_utf8_unchecked(input.into())),
-            }
+    fn _validate_text_value(value: &[u8]) -> Result<(), String> {
+        if value.len() > 28 {
+            return Err("String is longer than 28 bytes".to_string());
         }
+        Ok(())
     }
 
-    fn text(input: &str) -> Self {
-        assert!(input.len() <= 28, "String is longer than 28 bytes");
-
-        unsafe {
-            Memo {
-                memo_type: MEMO_TEXT.to_string(),
-                value: Some(String::from_utf8_unchecked(input.into())),
-            }
-        }
+    fn id(input: &str) -> Result<Self, String> {
+        Self::_validate_id_value(input)?;
+        Ok(Memo {
+            memo_type: MEMO_ID.to_string(),
+            value: Some(input.as_bytes().to_vec()),
+        })
     }
 
-    fn text_buffer(input: Vec<u8>) -> Self {
-        unsafe {
-            Memo {
-                memo_type: MEMO_TEXT.to_string(),
-                value: Some(String::from_utf8_unchecked(input)),
-            }
-        }
+    fn text(input: &str) -> Result<Self, String> {
+        Self::text_buffer(input.as_bytes().to_vec())
     }
 
-    fn hash_buffer(input: Vec<u8>) -> Self {
-        Self::_validate_hash_value(unsafe {
-            String::from_utf8_unchecked(input.clone()).as_bytes()
-        });
-
-        unsafe {
-            Memo {
-                memo_type: MEMO_HASH.to_string(),
-                value: Some(String::from_utf8_unchecked(input)),
-            }
-        }
+    /// Creates a text memo from raw bytes, which need not be UTF-8.
+    fn text_buffer(input: Vec<u8>) -> Result<Self, String> {
+        Self::_validate_text_value(&input)?;
+        Ok(Memo {
+            memo_type: MEMO_TEXT.to_string(),
+            value: Some(input),
+        })
     }
 
-    fn return_hash(input: Vec<u8>) -> Self {
-        Self::_validate_hash_value(unsafe {
-            String::from_utf8_unchecked(input.clone()).as_bytes()
-        });
-
-        unsafe {
-            Memo {
-                memo_type: MEMO_RETURN.to_string(),
-                value: Some(String::from_utf8_unchecked(input)),
-            }
-        }
+    fn hash_buffer(input: Vec<u8>) -> Result<Self, String> {
+        Ok(Memo {
+            memo_type: MEMO_HASH.to_string(),
+            value: Some(hash_value(&input)?.0.to_vec()),
+        })
     }
 
-    fn _validate_hash_value(value: &[u8]) {
-        if value.len() == 64 {
-            // Check if it's hex encoded string
-            let hex_str = match std::str::from_utf8(value) {
-                Ok(s) => s,
-                Err(_) => panic!("Expects a 32 byte hash value or hex encoded string"),
-            };
+    fn return_hash(input: Vec<u8>) -> Result<Self, String> {
+        Ok(Memo {
+            memo_type: MEMO_RETURN.to_string(),
+            value: Some(hash_value(&input)?.0.to_vec()),
+        })
+    }
 
-            if hex::decode(hex_str).is_err() {
-                panic!("Expects a 32 byte hash value or hex encoded string");
-            }
-            let decoded = match hex::decode(hex_str) {
-                Ok(d) => d,
-                Err(_) => panic!("Failed to decode hex string: {}", hex_str),
-            };
-            if decoded.len() != 32 {
-                panic!("Expects a 32 byte hash value or hex encoded string");
-            }
-        } else if value.len() != 32 {
-            let s = std::str::from_utf8(value).unwrap_or("<non-UTF8 data>");
-            panic!("Expects a 32 byte hash value or hex encoded string");
-        }
+    fn _validate_hash_value(value: &[u8]) -> Result<(), String> {
+        hash_value(value).map(|_| ())
     }
 
     fn none() -> Self {
@@ -197,82 +137,124 @@ impl MemoBehavior for Memo {
     }
 
     fn value(&self) -> Result<MemoValue, &'static str> {
+        let value = self.value.clone().unwrap_or_default();
         match self.memo_type.as_str() {
             MEMO_NONE => Ok(MemoValue::NoneValue),
-            MEMO_ID => Ok(MemoValue::IdValue(self.value.clone().unwrap())),
-            MEMO_TEXT => Ok(MemoValue::TextValue(
-                self.value.clone().unwrap().as_bytes().to_vec(),
-            )),
-            MEMO_HASH | MEMO_RETURN => Ok(MemoValue::HashValue(
-                self.value.clone().unwrap().as_bytes().to_vec(),
+            MEMO_ID => Ok(MemoValue::IdValue(
+                String::from_utf8(value).map_err(|_| "Invalid memo id")?,
             )),
+            MEMO_TEXT => Ok(MemoValue::TextValue(value)),
+            MEMO_HASH | MEMO_RETURN => Ok(MemoValue::HashValue(value)),
             _ => Err("Invalid memo type"),
         }
     }
 
+    /// Converts an XDR memo. Every memo converts, including text memos that are not UTF-8.
     fn from_xdr_object(object: xdr::Memo) -> Result<Self, &'static str> {
-        unsafe {
-            match object {
-                xdr::Memo::None => Ok(Memo {
-                    memo_type: MEMO_NONE.to_owned(),
-                    value: None,
-                }),
-                xdr::Memo::Text(x) => Ok(Memo {
-                    memo_type: MEMO_TEXT.to_owned(),
-                    value: Some(String::from_utf8_unchecked(x.to_vec())),
-                }),
-                xdr::Memo::Id(x) => Ok(Memo {
-                    memo_type: MEMO_ID.to_owned(),
-                    value: Some(x.to_string()),
-                }),
-                xdr::Memo::Hash(x) => Ok(Memo {
-                    memo_type: MEMO_HASH.to_owned(),
-                    value: Some(String::from_utf8_unchecked(x.0.to_vec())),
-                }),
-                xdr::Memo::Return(x) => Ok(Memo {
-                    memo_type: MEMO_RETURN.to_owned(),
-                    value: Some(String::from_utf8_unchecked(x.0.to_vec())),
-                }),
-            }
-        }
+        let (memo_type, value) = match object {
+            xdr::Memo::None => return Ok(Self::none()),
+            xdr::Memo::Text(x) => (MEMO_TEXT, x.to_vec()),
+            xdr::Memo::Id(x) => (MEMO_ID, x.to_string().into_bytes()),
+            xdr::Memo::Hash(x) => (MEMO_HASH, x.0.to_vec()),
+            xdr::Memo::Return(x) => (MEMO_RETURN, x.0.to_vec()),
+        };
+        Ok(Memo {
+            memo_type: memo_type.to_owned(),
+            value: Some(value),
+        })
     }
 
     fn to_xdr_object(&self) -> Option<xdr::Memo> {
-        match self.memo_type.as_str() {
-            MEMO_NONE => Some(xdr::Memo::None),
-            MEMO_ID => Some(xdr::Memo::Id(
-                u64::from_str(self.value.clone().unwrap().as_str()).unwrap(),
-            )),
-            MEMO_TEXT => Some(xdr::Memo::Text(
-                self.value.clone().unwrap().as_str().try_into().unwrap(),
-            )),
-            MEMO_HASH => Some(xdr::Memo::Hash(
-                xdr::Hash::from_str(&hex::encode(self.value.clone().unwrap().as_str())).unwrap(),
-            )),
-            // MemoType::MemoReturn => Some(XDRMemo::memo_return(&self._value)),
-            MEMO_RETURN => Some(xdr::Memo::Return(
-                xdr::Hash::from_str(&hex::encode(self.value.clone().unwrap().as_str())).unwrap(),
-            )),
-            _ => None,
-        }
+        xdr::Memo::try_from(self).ok()
     }
 }
 
-fn assert_panic<F: FnOnce(), S: AsRef<str>>(f: F, expected_msg: S) {
-    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
-    match result {
-        Ok(_) => panic!("Function did not panic as expected"),
-        Err(err) => {
-            if let Some(s) = err.downcast_ref::<&str>() {
-                assert!(
-                    s.contains(expected_msg.as_ref()),
-                    "Unexpected panic message. Got: {}",
-                    s
-                );
-            } else {
-                panic!("Unexpected panic type");
+// 32 raw bytes or 64 hex characters
+fn hash_value(value: &[u8]) -> Result<xdr::Hash, String> {
+    let error = || "Expects a 32 byte hash value or hex encoded string".to_string();
+    let bytes = if value.len() == 64 {
+        hex::decode(value).map_err(|_| error())?
+    } else {
+        value.to_vec()
+    };
+    bytes.try_into().map(xdr::Hash).map_err(|_| error())
+}
+
+impl TryFrom<&Memo> for xdr::Memo {
+    type Error = String;
+
+    fn try_from(memo: &Memo) -> Result<Self, Self::Error> {
+        let value = || {
+            memo.value
+                .as_deref()
+                .ok_or_else(|| format!("Expected a value for a {} memo", memo.memo_type))
+        };
+        match memo.memo_type.as_str() {
+            MEMO_NONE => Ok(xdr::Memo::None),
+            MEMO_ID => {
+                let value = String::from_utf8_lossy(value()?);
+                value
+                    .parse()
+                    .map(xdr::Memo::Id)
+                    .map_err(|_| format!("Expects an uint64 as a string. Got {}", value))
             }
+            MEMO_TEXT => value()?
+                .to_vec()
+                .try_into()
+                .map(xdr::Memo::Text)
+                .map_err(|_| "String is longer than 28 bytes".to_string()),
+            MEMO_HASH => hash_value(value()?).map(xdr::Memo::Hash),
+            MEMO_RETURN => hash_value(value()?).map(xdr::Memo::Return),
+            _ => Err("Invalid memo type".to_string()),
+        }
+    }
+}
+
+impl TryFrom<Memo> for xdr::Memo {
+    type Error = String;
+
+    fn try_from(memo: Memo) -> Result<Self, Self::Error> {
+        xdr::Memo::try_from(&memo)
+    }
+}
+
+impl TryFrom<xdr::Memo> for Memo {
+    type Error = String;
+
+    fn try_from(memo: xdr::Memo) -> Result<Self, Self::Error> {
+        Memo::from_xdr_object(memo).map_err(str::to_string)
+    }
+}
+
+/// Memos serialize as `{"type": "hash", "value": "..."}`, with hash and return values in hex.
+#[derive(Serialize, Deserialize)]
+struct MemoJson {
+    #[serde(rename = "type")]
+    memo_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+impl Serialize for Memo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match xdr::Memo::try_from(self).map_err(ser::Error::custom)? {
+            xdr::Memo::None => None,
+            xdr::Memo::Id(id) => Some(id.to_string()),
+            xdr::Memo::Text(text) => Some(text.to_utf8_string().map_err(ser::Error::custom)?),
+            xdr::Memo::Hash(hash) | xdr::Memo::Return(hash) => Some(hex::encode(hash.0)),
+        };
+        MemoJson {
+            memo_type: self.memo_type.clone(),
+            value,
         }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Memo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = MemoJson::deserialize(deserializer)?;
+        Memo::new(&json.memo_type, json.value.as_deref()).map_err(de::Error::custom)
     }
 }
 
@@ -285,7 +267,7 @@ mod tests {
 
     use crate::memo::{MEMO_HASH, MEMO_NONE, MEMO_RETURN};
 
-    use super::{assert_panic, Memo, MEMO_ID, MEMO_TEXT};
+    use super::{Memo, MEMO_ID, MEMO_TEXT};
 
     #[test]
     fn constructor_throws_error_when_type_is_invalid() {
@@ -314,7 +296,6 @@ mod tests {
             _ => panic!("Invalid Type"),
         };
         let b = String::from("三代之時");
-        print!("xx {}", val);
 
         assert_eq!(val, b, "Memo text value does not match expected value");
     }
@@ -328,29 +309,19 @@ mod tests {
             0x00, 0x00, 0x00, 0x01, // length
             0xd1, 0x00, 0x00, 0x00,
         ];
-        // let mut memo_text: Vec<u8> = vec![];
         let memo_text = Memo::text_buffer(vec2.clone())
+            .unwrap()
             .to_xdr_object()
             .unwrap()
             .to_xdr(xdr::Limits::none())
             .unwrap();
 
-        unsafe {
-            let memo_text_2 =
-                Memo::new(MEMO_TEXT, Some(&String::from_utf8_unchecked(vec2.clone())))
-                    .unwrap()
-                    .to_xdr_object()
-                    .unwrap()
-                    .to_xdr(xdr::Limits::none())
-                    .unwrap();
-            assert_eq!(memo_text_2, expected);
-        }
         assert_eq!(memo_text, expected);
     }
 
     #[test]
     fn converts_to_from_xdr_object() {
-        let memo = Memo::text("test").to_xdr_object().unwrap();
+        let memo = Memo::text("test").unwrap().to_xdr_object().unwrap();
 
         let val = match memo.clone() {
             xdr::Memo::Text(x) => x.to_string(),
@@ -361,59 +332,66 @@ mod tests {
 
         let base_memo = Memo::from_xdr_object(memo.clone()).unwrap();
         assert_eq!(base_memo.memo_type, MEMO_TEXT);
-        assert_eq!(base_memo.value.unwrap(), "test");
+        assert_eq!(base_memo.value.unwrap(), b"test");
     }
 
     #[test]
     fn converts_to_from_xdr_object_buffer() {
         let buf = vec![0xd1];
-        // unsafe {
-        let memo = Memo::text_buffer(buf.clone()).to_xdr_object().unwrap();
-        // }
+        let memo = Memo::text_buffer(buf.clone())
+            .unwrap()
+            .to_xdr_object()
+            .unwrap();
         let val = match memo.clone() {
             xdr::Memo::Text(x) => x,
             _ => panic!("Invalid Type"),
         };
 
-        unsafe {
-            assert_eq!(val.to_vec(), buf);
-        }
+        assert_eq!(val.to_vec(), buf);
 
-        let base_memo = Memo::from_xdr_object(memo).unwrap();
+        let base_memo = Memo::from_xdr_object(memo.clone()).unwrap();
         assert_eq!(base_memo.memo_type, MEMO_TEXT);
 
         let val = match base_memo.value().unwrap() {
             crate::memo::MemoValue::TextValue(x) => x,
             _ => panic!("Bad"),
         };
-        unsafe {
-            assert_eq!(val.to_vec(), buf);
-        }
+        assert_eq!(val.to_vec(), buf);
+        assert_eq!(base_memo.to_xdr_object().unwrap(), memo);
     }
 
     #[test]
     fn errors_when_string_longer_than_28_bytes() {
         let long_string = "12345678901234567890123456789";
-        let scenario_1 = || {
-            Memo::text(long_string);
-        };
-        assert_panic(scenario_1, "String is longer than 28 bytes");
-
-        let scenario_2 = || {
-            let long_utf8_string = "三代之時三代之時三代之時";
-            Memo::text(long_utf8_string);
-        };
-        assert_panic(scenario_2, "String is longer than 28 bytes");
+        assert_eq!(
+            Memo::text(long_string).unwrap_err(),
+            "String is longer than 28 bytes"
+        );
+
+        let long_utf8_string = "三代之時三代之時三代之時";
+        assert_eq!(
+            Memo::text(long_utf8_string).unwrap_err(),
+            "String is longer than 28 bytes"
+        );
+        assert_eq!(
+            Memo::text_buffer(vec![0xff; 29]).unwrap_err(),
+            "String is longer than 28 bytes"
+        );
     }
 
+    #[test]
     fn memo_id_handles_correct_argument() {
-        Memo::new(MEMO_ID, Some("1000"));
-        Memo::new(MEMO_ID, Some("0"));
+        assert!(Memo::new(MEMO_ID, Some("1000")).is_ok());
+        assert!(Memo::new(MEMO_ID, Some("0")).is_ok());
+        assert_eq!(
+            Memo::id("abc").unwrap_err(),
+            "Expects an uint64 as a string. Got abc"
+        );
     }
 
     #[test]
     fn converts_to_from_xdr_object_if() {
-        let memo = Memo::id("1000").to_xdr_object().unwrap();
+        let memo = Memo::id("1000").unwrap().to_xdr_object().unwrap();
 
         let val = match memo {
             xdr::Memo::Id(x) => x,
@@ -429,50 +407,44 @@ mod tests {
             _ => panic!("Invalid"),
         }
 
-        assert_eq!(base_memo.value.unwrap(), "1000");
+        assert_eq!(base_memo.value.unwrap(), b"1000");
     }
 
     #[test]
     fn hash_converts_to_from_xdr_object() {
-        // Assuming you have a Rust-equivalent to allocate a buffer of length 32 with all bytes being 10.
         let buffer = vec![10u8; 32];
 
-        let memo = Memo::hash_buffer(buffer.clone()).to_xdr_object().unwrap();
+        let memo = Memo::hash_buffer(buffer.clone())
+            .unwrap()
+            .to_xdr_object()
+            .unwrap();
 
         let val = match memo.clone() {
             xdr::Memo::Hash(x) => x,
             _ => panic!("Invalid"),
         };
         assert_eq!(val.0.len(), 32);
-        unsafe {
-            assert_eq!(
-                val.to_string(),
-                String::from_utf8_unchecked(hex::encode(buffer.clone()).into())
-            );
-        }
+        assert_eq!(val.to_string(), hex::encode(&buffer));
         let base_memo = Memo::from_xdr_object(memo).unwrap();
 
         match base_memo.memo_type.as_str() {
             MEMO_HASH => (),
             _ => panic!("Invalid"),
         }
-        assert_eq!(base_memo.value.clone().unwrap().len(), 32);
-
-        let base_memo_hex = hex::encode(base_memo.value.unwrap());
-        let buffer_hex = hex::encode(buffer.clone());
-
-        assert_eq!(base_memo_hex, buffer_hex);
+        assert_eq!(base_memo.value.clone().unwrap(), buffer);
+        match base_memo.value().unwrap() {
+            crate::memo::MemoValue::HashValue(x) => assert_eq!(x, buffer),
+            _ => panic!("Invalid"),
+        }
     }
 
     #[test]
     fn return_converts_to_from_xdr_object() {
         let buffer = vec![10u8; 32];
 
-        // Convert Vec<u8> to hex string
-        let buffer_hex: String = hex::encode(&buffer);
-
-        // Testing string hash
-        let memo = Memo::return_hash(unsafe { buffer.clone() })
+        // Testing hex encoded hash
+        let memo = Memo::return_hash(hex::encode(&buffer).into_bytes())
+            .unwrap()
             .to_xdr_object()
             .unwrap();
 
@@ -482,12 +454,7 @@ mod tests {
         };
 
         assert_eq!(val.0.len(), 32);
-        unsafe {
-            assert_eq!(
-                val.to_string(),
-                String::from_utf8_unchecked(hex::encode(buffer.clone()).into())
-            );
-        }
+        assert_eq!(val.to_string(), hex::encode(&buffer));
 
         let base_memo = Memo::from_xdr_object(memo).unwrap();
 
@@ -496,10 +463,7 @@ mod tests {
             _ => panic!("Invalid"),
         };
 
-        assert_eq!(base_memo.value.clone().unwrap().len(), 32);
-        let base_memo_hex = hex::encode(base_memo.value.unwrap());
-        let buffer_hex = hex::encode(buffer.clone());
-        assert_eq!(base_memo_hex, buffer_hex);
+        assert_eq!(base_memo.value.unwrap(), buffer);
     }
 
     #[test]
@@ -507,10 +471,10 @@ mod tests {
         let methods = [Memo::hash_buffer, Memo::return_hash];
 
         for method in &methods {
-            let _ = method(vec![0u8; 32]);
+            assert!(method(vec![0u8; 32]).is_ok());
 
             let hex_str = "0000000000000000000000000000000000000000000000000000000000000000";
-            let _ = method(hex::decode(hex_str).expect("Failed to decode hex"));
+            assert!(method(hex_str.as_bytes().to_vec()).is_ok());
         }
 
         let binding_1 =
@@ -526,20 +490,86 @@ mod tests {
             &[0, 10, 20],
             binding_3,      // 33 zeros
             &binding_1[..], // 31 zeros in hex
-            &binding_2[..], // 32 zeros in hex
-                            // ... add any other byte slices as needed
+            &binding_2[..], // 33 zeros in hex
+            &[0xff; 64],    // 64 bytes that are not hex
         ];
 
         for method in &methods {
             for input in &invalid_inputs {
-                let scenario_1 = || {
-                    method(input.to_vec());
-                };
-                assert_panic(
-                    scenario_1,
-                    "Expects a 32 byte hash value or hex encoded string",
+                assert_eq!(
+                    method(input.to_vec()).unwrap_err(),
+                    "Expects a 32 byte hash value or hex encoded string"
                 );
             }
         }
     }
+
+    #[test]
+    fn new_returns_errors_for_invalid_values() {
+        assert!(Memo::new(MEMO_ID, None).is_err());
+        assert!(Memo::new(MEMO_ID, Some("-1")).is_err());
+        assert!(Memo::new(MEMO_TEXT, Some("12345678901234567890123456789")).is_err());
+        assert!(Memo::new(MEMO_HASH, Some("test")).is_err());
+        assert!(Memo::new(MEMO_RETURN, Some(&"zz".repeat(32))).is_err());
+
+        let memo = Memo::new(MEMO_RETURN, Some(&"0a".repeat(32))).unwrap();
+        assert_eq!(
+            xdr::Memo::try_from(&memo).unwrap(),
+            xdr::Memo::Return(xdr::Hash([10; 32]))
+        );
+    }
+
+    #[test]
+    fn try_from_converts_both_ways() {
+        for memo in [
+            xdr::Memo::None,
+            xdr::Memo::Id(u64::MAX),
+            xdr::Memo::Text("refund".try_into().unwrap()),
+            xdr::Memo::Text(vec![0xff, 0xd1, 0x00].try_into().unwrap()),
+            xdr::Memo::Hash(xdr::Hash([1; 32])),
+            xdr::Memo::Return(xdr::Hash([2; 32])),
+            xdr::Memo::Hash(xdr::Hash([0xff; 32])),
+            xdr::Memo::Return(xdr::Hash([0xc3; 32])),
+        ] {
+            let converted = Memo::try_from(memo.clone()).unwrap();
+            assert_eq!(xdr::Memo::try_from(converted).unwrap(), memo);
+        }
+    }
+
+    #[test]
+    fn serializes_to_and_from_json() {
+        let memo = Memo::return_hash(vec![10u8; 32]).unwrap();
+        let json = serde_json::to_string(&memo).unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"type":"return","value":"{}"}}"#, "0a".repeat(32))
+        );
+        let parsed: Memo = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, memo);
+
+        assert_eq!(
+            serde_json::to_string(&Memo::id("42").unwrap()).unwrap(),
+            r#"{"type":"id","value":"42"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Memo::none()).unwrap(),
+            r#"{"type":"none"}"#
+        );
+        let memo = Memo::try_from(xdr::Memo::Hash(xdr::Hash([0xff; 32]))).unwrap();
+        let json = serde_json::to_string(&memo).unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"type":"hash","value":"{}"}}"#, "ff".repeat(32))
+        );
+        let parsed: Memo = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, memo);
+
+        let parsed: Memo = serde_json::from_str(r#"{"type":"text","value":"hi"}"#).unwrap();
+        assert_eq!(parsed, Memo::text("hi").unwrap());
+
+        assert!(serde_json::from_str::<Memo>(r#"{"type":"id","value":"x"}"#).is_err());
+        assert!(serde_json::from_str::<Memo>(r#"{"type":"hash"}"#).is_err());
+        // JSON values are strings, so non UTF-8 text has no JSON form
+        assert!(serde_json::to_string(&Memo::text_buffer(vec![0xd1]).unwrap()).is_err());
+    }
 }
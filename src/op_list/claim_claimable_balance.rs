@@ -1,4 +1,5 @@
 use crate::{
+    claimable_balance_id::{ClaimableBalanceId, ClaimableBalanceIdBehavior},
     operation::{self, Operation},
     xdr,
 };
@@ -7,16 +8,18 @@ impl Operation {
     /// Claims a ClaimableBalanceEntry that corresponds to the `balance_id` and adds the amount
     /// of an asset on the entry to the source account
     ///
+    /// The `balance_id` can be given as hex (as returned by Horizon) or as a B... strkey, see
+    /// [ClaimableBalanceId](crate::claimable_balance_id::ClaimableBalanceId).
+    ///
     /// Threshold: Medium
     pub fn claim_claimable_balance(
         &self,
         balance_id: &str,
     ) -> Result<xdr::Operation, operation::Error> {
         //
-        let mut h = [0; 32];
-        hex::decode_to_slice(balance_id, &mut h)
-            .map_err(|_| operation::Error::InvalidField("balance_id".into()))?;
-        let xdr_balance_id = xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(h));
+        let xdr_balance_id = ClaimableBalanceId::from_string(balance_id)
+            .map_err(|_| operation::Error::InvalidField("balance_id".into()))?
+            .to_xdr_object();
         let body = xdr::OperationBody::ClaimClaimableBalance(xdr::ClaimClaimableBalanceOp {
             balance_id: xdr_balance_id,
         });
@@ -30,6 +33,7 @@ impl Operation {
 #[cfg(test)]
 mod tests {
     use crate::{
+        claimable_balance_id::{ClaimableBalanceId, ClaimableBalanceIdBehavior},
         operation::{self, Operation},
         xdr,
     };
@@ -52,6 +56,24 @@ mod tests {
             panic!("Fail")
         }
     }
+    #[test]
+    fn test_claim_cb_strkey_and_prefixed_hex() {
+        let balance_id = ClaimableBalanceId::from_string(&hex::encode([2; 32])).unwrap();
+
+        for id in [balance_id.to_strkey(), balance_id.to_hex()] {
+            let op = Operation::new().claim_claimable_balance(&id).unwrap();
+
+            if let xdr::OperationBody::ClaimClaimableBalance(xdr::ClaimClaimableBalanceOp {
+                balance_id: xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(h)),
+            }) = op.body
+            {
+                assert_eq!(h, [2; 32]);
+            } else {
+                panic!("Fail")
+            }
+        }
+    }
+
     #[test]
     fn test_claim_cb_id_too_big() {
         let balance_id = hex::encode([3; 33]);
@@ -1,18 +1,22 @@
 use crate::{
+    claimable_balance_id::{ClaimableBalanceId, ClaimableBalanceIdBehavior},
     operation::{self, Operation},
     xdr,
 };
 
 impl Operation {
+    /// Claws back an unclaimed ClaimableBalanceEntry that corresponds to the `balance_id`,
+    /// given as hex or as a B... strkey
+    ///
+    /// Threshold: Medium
     pub fn clawback_claimable_balance(
         &self,
         balance_id: &str,
     ) -> Result<xdr::Operation, operation::Error> {
         //
-        let mut h = [0; 32];
-        hex::decode_to_slice(balance_id, &mut h)
-            .map_err(|_| operation::Error::InvalidField("balance_id".into()))?;
-        let xdr_balance_id = xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(h));
+        let xdr_balance_id = ClaimableBalanceId::from_string(balance_id)
+            .map_err(|_| operation::Error::InvalidField("balance_id".into()))?
+            .to_xdr_object();
         let body = xdr::OperationBody::ClawbackClaimableBalance(xdr::ClawbackClaimableBalanceOp {
             balance_id: xdr_balance_id,
         });
@@ -73,6 +73,9 @@ impl Operation {
 
     /// Create a new contract for the `wasm_hash`.
     ///
+    /// The `wasm_hash` of uploaded code is the `hash` of
+    /// [ContractWasm](crate::contract_wasm::ContractWasm).
+    ///
     /// The `salt` and `deployer` are used to computed the contract_id pre-image of the newly
     /// created contract.
     ///
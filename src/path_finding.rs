@@ -0,0 +1,551 @@
+//! Offline path finding for `path_payment_strict_send` and `path_payment_strict_receive`.
+//!
+//! Paths are searched over a snapshot of the order books and liquidity pools the caller has
+//! cached. Like the network, every hop converts through whichever of the order book or the pool
+//! gives the better rate.
+use std::collections::HashMap;
+
+use crate::asset::{Asset, AssetBehavior};
+use crate::liquidity_pool_math::{LiquidityPoolState, LiquidityPoolStateBehavior, PoolAsset};
+
+/// Maximum number of assets between the source and destination asset of a path payment.
+pub const MAX_PATH_LENGTH: usize = 5;
+
+/// An offer selling `amount` of `selling` at `price` units of `buying` per unit of `selling`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Offer {
+    pub selling: Asset,
+    pub buying: Asset,
+    pub amount: i64,
+    pub price: (i32, i32),
+}
+
+// Order books are indexed as `books[selling][buying]` and pools as `pools[from][to]`, so a hop
+// only looks at the offers and pool of its own asset pair
+#[derive(Debug, Clone, Default)]
+pub struct MarketSnapshot {
+    books: HashMap<Asset, HashMap<Asset, Vec<Offer>>>,
+    pools: HashMap<Asset, HashMap<Asset, (LiquidityPoolState, PoolAsset)>>,
+    neighbors: HashMap<Asset, Vec<Asset>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentPath {
+    pub source_asset: Asset,
+    pub source_amount: i64,
+    pub destination_asset: Asset,
+    pub destination_amount: i64,
+    /// Assets between the source and destination asset, as expected by the `path` argument.
+    pub path: Vec<Asset>,
+}
+
+// Define a trait for MarketSnapshot behavior
+pub trait MarketSnapshotBehavior {
+    fn new() -> Self
+    where
+        Self: Sized;
+    fn add_offer(&mut self, offer: Offer) -> Result<&mut Self, String>;
+    fn add_pool(
+        &mut self,
+        asset_a: &Asset,
+        asset_b: &Asset,
+        state: LiquidityPoolState,
+    ) -> Result<&mut Self, String>;
+    fn find_strict_send_paths(
+        &self,
+        source_asset: &Asset,
+        source_amount: i64,
+        destination_assets: &[Asset],
+        max_results: usize,
+    ) -> Vec<PaymentPath>;
+    fn find_strict_receive_paths(
+        &self,
+        source_assets: &[Asset],
+        destination_asset: &Asset,
+        destination_amount: i64,
+        max_results: usize,
+    ) -> Vec<PaymentPath>;
+}
+
+impl MarketSnapshotBehavior for MarketSnapshot {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_offer(&mut self, offer: Offer) -> Result<&mut Self, String> {
+        if offer.amount <= 0 {
+            return Err("offer amount must be positive".to_string());
+        }
+        if offer.price.0 <= 0 || offer.price.1 <= 0 {
+            return Err("offer price must be positive".to_string());
+        }
+        if offer.selling == offer.buying {
+            return Err("offer must trade two different assets".to_string());
+        }
+
+        self.link(&offer.selling, &offer.buying);
+        let book = self
+            .books
+            .entry(offer.selling.clone())
+            .or_default()
+            .entry(offer.buying.clone())
+            .or_default();
+        // Keep the book sorted by price, offers at the same price in insertion order
+        let price = |o: &Offer| (o.price.0 as i64, o.price.1 as i64);
+        let (n, d) = price(&offer);
+        let index = book.partition_point(|o| {
+            let (on, od) = price(o);
+            on * d <= n * od
+        });
+        book.insert(index, offer);
+        Ok(self)
+    }
+
+    fn add_pool(
+        &mut self,
+        asset_a: &Asset,
+        asset_b: &Asset,
+        state: LiquidityPoolState,
+    ) -> Result<&mut Self, String> {
+        if asset_a == asset_b {
+            return Err("pool must hold two different assets".to_string());
+        }
+
+        self.link(asset_a, asset_b);
+        self.pools
+            .entry(asset_a.clone())
+            .or_default()
+            .insert(asset_b.clone(), (state.clone(), PoolAsset::A));
+        self.pools
+            .entry(asset_b.clone())
+            .or_default()
+            .insert(asset_a.clone(), (state, PoolAsset::B));
+        Ok(self)
+    }
+
+    /// Finds the best paths sending exactly `source_amount` of `source_asset` to any of
+    /// `destination_assets`, best destination amount first and at most `max_results` of them.
+    fn find_strict_send_paths(
+        &self,
+        source_asset: &Asset,
+        source_amount: i64,
+        destination_assets: &[Asset],
+        max_results: usize,
+    ) -> Vec<PaymentPath> {
+        if source_amount <= 0 {
+            return Vec::new();
+        }
+
+        let mut paths: Vec<PaymentPath> = self
+            .search(
+                source_asset,
+                source_amount,
+                destination_assets,
+                |current, next, amount| self.convert_send(current, next, amount),
+                |a, b| a > b,
+            )
+            .into_iter()
+            .map(|(assets, amount)| PaymentPath {
+                source_asset: source_asset.clone(),
+                source_amount,
+                destination_asset: assets[assets.len() - 1].clone(),
+                destination_amount: amount,
+                path: assets[1..assets.len() - 1].to_vec(),
+            })
+            .collect();
+        paths.sort_by(|a, b| {
+            b.destination_amount
+                .cmp(&a.destination_amount)
+                .then(a.path.len().cmp(&b.path.len()))
+        });
+        paths.truncate(max_results);
+        paths
+    }
+
+    /// Finds the best paths delivering exactly `destination_amount` of `destination_asset` from
+    /// any of `source_assets`, cheapest source amount first and at most `max_results` of them.
+    fn find_strict_receive_paths(
+        &self,
+        source_assets: &[Asset],
+        destination_asset: &Asset,
+        destination_amount: i64,
+        max_results: usize,
+    ) -> Vec<PaymentPath> {
+        if destination_amount <= 0 {
+            return Vec::new();
+        }
+
+        let mut paths: Vec<PaymentPath> = self
+            .search(
+                destination_asset,
+                destination_amount,
+                source_assets,
+                |current, previous, amount| self.convert_receive(previous, current, amount),
+                |a, b| a < b,
+            )
+            .into_iter()
+            .map(|(assets, amount)| PaymentPath {
+                source_asset: assets[assets.len() - 1].clone(),
+                source_amount: amount,
+                destination_asset: destination_asset.clone(),
+                destination_amount,
+                path: assets[1..assets.len() - 1].iter().rev().cloned().collect(),
+            })
+            .collect();
+        paths.sort_by(|a, b| {
+            a.source_amount
+                .cmp(&b.source_amount)
+                .then(a.path.len().cmp(&b.path.len()))
+        });
+        paths.truncate(max_results);
+        paths
+    }
+}
+
+impl MarketSnapshot {
+    fn link(&mut self, a: &Asset, b: &Asset) {
+        for (from, to) in [(a, b), (b, a)] {
+            let neighbors = self.neighbors.entry(from.clone()).or_default();
+            if !neighbors.contains(to) {
+                neighbors.push(to.clone());
+            }
+        }
+    }
+
+    // Searches hop by hop from `origin` towards any of `targets`, converting amounts with
+    // `convert`. Only the best amount reaching an asset at a given depth is extended, and only
+    // if it beats every shorter path to that asset, so each hop costs one pass over the
+    // edges. Returns the assets crossed from `origin` to a target with the converted amount.
+    fn search<C, B>(
+        &self,
+        origin: &Asset,
+        amount: i64,
+        targets: &[Asset],
+        convert: C,
+        better: B,
+    ) -> Vec<(Vec<Asset>, i64)>
+    where
+        C: Fn(&Asset, &Asset, i64) -> Option<i64>,
+        B: Fn(i64, i64) -> bool,
+    {
+        let mut found = Vec::new();
+        let mut best: HashMap<&Asset, i64> = HashMap::new();
+        let mut frontier = vec![(vec![origin.clone()], amount)];
+
+        // A path crosses at most MAX_PATH_LENGTH assets, that is MAX_PATH_LENGTH + 1 hops
+        for _ in 0..=MAX_PATH_LENGTH {
+            let mut layer: Vec<(Vec<Asset>, i64)> = Vec::new();
+            let mut index: HashMap<&Asset, usize> = HashMap::new();
+            for (assets, amount) in &frontier {
+                let current = &assets[assets.len() - 1];
+                for next in self.neighbors.get(current).into_iter().flatten() {
+                    if assets.contains(next) {
+                        continue;
+                    }
+                    let Some(converted) = convert(current, next, *amount) else {
+                        continue;
+                    };
+                    if best.get(next).is_some_and(|b| !better(converted, *b)) {
+                        continue;
+                    }
+                    let mut extended = assets.clone();
+                    extended.push(next.clone());
+                    match index.get(next) {
+                        Some(&i) if better(converted, layer[i].1) => {
+                            layer[i] = (extended, converted)
+                        }
+                        Some(_) => {}
+                        None => {
+                            index.insert(next, layer.len());
+                            layer.push((extended, converted));
+                        }
+                    }
+                }
+            }
+
+            frontier = Vec::new();
+            for (assets, amount) in layer {
+                let reached = &assets[assets.len() - 1];
+                let key = self.neighbors.get_key_value(reached).map(|(k, _)| k);
+                if let Some(key) = key {
+                    best.insert(key, amount);
+                }
+                if targets.contains(reached) {
+                    found.push((assets, amount));
+                } else {
+                    frontier.push((assets, amount));
+                }
+            }
+            if frontier.is_empty() {
+                break;
+            }
+        }
+        found
+    }
+
+    // Offers selling `to` for `from`, cheapest first
+    fn book(&self, from: &Asset, to: &Asset) -> &[Offer] {
+        self.books
+            .get(to)
+            .and_then(|books| books.get(from))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn pool(&self, from: &Asset, to: &Asset) -> Option<&(LiquidityPoolState, PoolAsset)> {
+        self.pools.get(from).and_then(|pools| pools.get(to))
+    }
+
+    // Amount of `to` received for exactly `amount` of `from`
+    fn convert_send(&self, from: &Asset, to: &Asset, amount: i64) -> Option<i64> {
+        let mut remaining = amount as i128;
+        let mut received: i128 = 0;
+        for offer in self.book(from, to) {
+            let (n, d) = (offer.price.0 as i128, offer.price.1 as i128);
+            let cost = (offer.amount as i128 * n + d - 1) / d;
+            if remaining >= cost {
+                received += offer.amount as i128;
+                remaining -= cost;
+            } else {
+                received += remaining * d / n;
+                remaining = 0;
+            }
+            if remaining == 0 {
+                break;
+            }
+        }
+        let book = (remaining == 0 && received > 0)
+            .then(|| i64::try_from(received).ok())
+            .flatten();
+
+        let pool = self
+            .pool(from, to)
+            .and_then(|(state, side)| state.swap_strict_send(*side, amount).ok())
+            .map(|quote| quote.from_pool);
+
+        book.max(pool)
+    }
+
+    // Amount of `from` needed to receive exactly `amount` of `to`
+    fn convert_receive(&self, from: &Asset, to: &Asset, amount: i64) -> Option<i64> {
+        let mut needed = amount as i128;
+        let mut cost: i128 = 0;
+        for offer in self.book(from, to) {
+            let (n, d) = (offer.price.0 as i128, offer.price.1 as i128);
+            let taken = needed.min(offer.amount as i128);
+            cost += (taken * n + d - 1) / d;
+            needed -= taken;
+            if needed == 0 {
+                break;
+            }
+        }
+        let book = (needed == 0).then(|| i64::try_from(cost).ok()).flatten();
+
+        let pool = self
+            .pool(from, to)
+            .and_then(|(state, side)| state.swap_strict_receive(*side, amount).ok())
+            .map(|quote| quote.to_pool);
+
+        match (book, pool) {
+            (Some(book), Some(pool)) => Some(book.min(pool)),
+            (book, pool) => book.or(pool),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISSUER: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+
+    fn asset(code: &str) -> Asset {
+        Asset::new(code, Some(ISSUER)).unwrap()
+    }
+
+    fn offer(selling: &Asset, buying: &Asset, amount: i64, price: (i32, i32)) -> Offer {
+        Offer {
+            selling: selling.clone(),
+            buying: buying.clone(),
+            amount,
+            price,
+        }
+    }
+
+    fn pool(reserve_a: i64, reserve_b: i64) -> LiquidityPoolState {
+        LiquidityPoolState::new(reserve_a, reserve_b, reserve_a.min(reserve_b)).unwrap()
+    }
+
+    #[test]
+    fn test_strict_send_through_order_book() {
+        let (xlm, usd) = (Asset::native(), asset("USD"));
+        let mut market = MarketSnapshot::new();
+        market
+            .add_offer(offer(&usd, &xlm, 100, (2, 1)))
+            .unwrap()
+            .add_offer(offer(&usd, &xlm, 100, (4, 1)))
+            .unwrap();
+
+        // 200 XLM buys the whole first offer, the last 100 XLM buy 25 USD from the second
+        let paths = market.find_strict_send_paths(&xlm, 300, std::slice::from_ref(&usd), 10);
+
+        assert_eq!(
+            paths,
+            vec![PaymentPath {
+                source_asset: xlm.clone(),
+                source_amount: 300,
+                destination_asset: usd.clone(),
+                destination_amount: 125,
+                path: vec![],
+            }]
+        );
+
+        // not enough offers to take the whole amount
+        assert!(market
+            .find_strict_send_paths(&xlm, 1_000, std::slice::from_ref(&usd), 10)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_strict_receive_through_order_book() {
+        let (xlm, usd) = (Asset::native(), asset("USD"));
+        let mut market = MarketSnapshot::new();
+        market
+            .add_offer(offer(&usd, &xlm, 100, (4, 1)))
+            .unwrap()
+            .add_offer(offer(&usd, &xlm, 100, (2, 1)))
+            .unwrap();
+
+        let paths = market.find_strict_receive_paths(std::slice::from_ref(&xlm), &usd, 125, 10);
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].source_amount, 300);
+        assert_eq!(paths[0].destination_amount, 125);
+    }
+
+    #[test]
+    fn test_hop_uses_better_of_book_and_pool() {
+        let (xlm, usd) = (Asset::native(), asset("USD"));
+        let mut market = MarketSnapshot::new();
+        market
+            .add_offer(offer(&usd, &xlm, 1_000_000, (2, 1)))
+            .unwrap()
+            .add_pool(&xlm, &usd, pool(1_000_000, 1_000_000))
+            .unwrap();
+
+        // the pool gives almost 1:1 for small amounts
+        let paths = market.find_strict_send_paths(&xlm, 1_000, std::slice::from_ref(&usd), 10);
+        assert_eq!(paths[0].destination_amount, 996);
+
+        // large amounts move the pool price past the book
+        let paths = market.find_strict_send_paths(&xlm, 1_000_000, std::slice::from_ref(&usd), 10);
+        assert_eq!(paths[0].destination_amount, 500_000);
+    }
+
+    #[test]
+    fn test_multi_hop_paths_are_ranked() {
+        let (xlm, usd, eur) = (Asset::native(), asset("USD"), asset("EUR"));
+        let mut market = MarketSnapshot::new();
+        market
+            .add_offer(offer(&usd, &xlm, 1_000, (4, 1)))
+            .unwrap()
+            .add_offer(offer(&eur, &xlm, 1_000, (1, 1)))
+            .unwrap()
+            .add_offer(offer(&usd, &eur, 1_000, (2, 1)))
+            .unwrap();
+
+        let paths = market.find_strict_send_paths(&xlm, 100, std::slice::from_ref(&usd), 10);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].path, vec![eur.clone()]);
+        assert_eq!(paths[0].destination_amount, 50);
+        assert_eq!(paths[1].path, vec![]);
+        assert_eq!(paths[1].destination_amount, 25);
+
+        let paths = market.find_strict_receive_paths(std::slice::from_ref(&xlm), &usd, 50, 10);
+        assert_eq!(paths[0].path, vec![eur]);
+        assert_eq!(paths[0].source_amount, 100);
+        assert_eq!(paths[1].source_amount, 200);
+    }
+
+    #[test]
+    fn test_path_length_is_limited() {
+        let assets: Vec<Asset> = (0..8).map(|i| asset(&format!("A{}", i))).collect();
+        let mut market = MarketSnapshot::new();
+        for pair in assets.windows(2) {
+            market
+                .add_offer(offer(&pair[1], &pair[0], 100, (1, 1)))
+                .unwrap();
+        }
+
+        // 5 assets in between is the longest allowed path
+        let paths = market.find_strict_send_paths(&assets[0], 10, &assets[6..7], 10);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].path, assets[1..6].to_vec());
+
+        assert!(market
+            .find_strict_send_paths(&assets[0], 10, &assets[7..8], 10)
+            .is_empty());
+        assert!(market
+            .find_strict_receive_paths(&assets[0..1], &assets[7], 10, 10)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_max_results_keeps_best_paths() {
+        let (xlm, usd, eur, gbp) = (Asset::native(), asset("USD"), asset("EUR"), asset("GBP"));
+        let mut market = MarketSnapshot::new();
+        market
+            .add_offer(offer(&usd, &xlm, 1_000, (4, 1)))
+            .unwrap()
+            .add_offer(offer(&eur, &xlm, 1_000, (1, 1)))
+            .unwrap()
+            .add_offer(offer(&usd, &eur, 1_000, (2, 1)))
+            .unwrap()
+            .add_offer(offer(&gbp, &xlm, 1_000, (1, 1)))
+            .unwrap()
+            .add_offer(offer(&usd, &gbp, 1_000, (1, 1)))
+            .unwrap();
+
+        let paths = market.find_strict_send_paths(&xlm, 100, std::slice::from_ref(&usd), 2);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].path, vec![gbp]);
+        assert_eq!(paths[0].destination_amount, 100);
+        // the route through EUR also crosses one asset but delivers less than GBP
+        assert_eq!(paths[1].path, vec![]);
+        assert_eq!(paths[1].destination_amount, 25);
+
+        assert!(market
+            .find_strict_send_paths(&xlm, 100, std::slice::from_ref(&usd), 0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_search_scales_to_dense_markets() {
+        // every asset trades with every other one; enumerating all simple paths would not finish
+        let assets: Vec<Asset> = (0..40).map(|i| asset(&format!("A{}", i))).collect();
+        let mut market = MarketSnapshot::new();
+        for (i, a) in assets.iter().enumerate() {
+            for b in &assets[i + 1..] {
+                market.add_pool(a, b, pool(1_000_000, 1_000_000)).unwrap();
+            }
+        }
+
+        let paths = market.find_strict_send_paths(&assets[0], 1_000, &assets[39..40], 5);
+        assert!(!paths.is_empty());
+        assert!(paths.len() <= 5);
+        assert!(paths[0].path.is_empty());
+        assert!(paths
+            .windows(2)
+            .all(|w| w[0].destination_amount >= w[1].destination_amount));
+    }
+
+    #[test]
+    fn test_invalid_market_data() {
+        let (xlm, usd) = (Asset::native(), asset("USD"));
+        let mut market = MarketSnapshot::new();
+
+        assert!(market.add_offer(offer(&usd, &xlm, 0, (1, 1))).is_err());
+        assert!(market.add_offer(offer(&usd, &xlm, 1, (0, 1))).is_err());
+        assert!(market.add_offer(offer(&usd, &usd, 1, (1, 1))).is_err());
+        assert!(market.add_pool(&usd, &usd, pool(1, 1)).is_err());
+    }
+}
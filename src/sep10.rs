@@ -0,0 +1,1097 @@
+//! SEP-10 Stellar Web Authentication.
+//!
+//! Builds the challenge transactions an authentication server hands to clients and reads and
+//! verifies the challenges signed by them. See
+//! <https://github.com/stellar/stellar-protocol/blob/master/ecosystem/sep-0010.md>.
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand_core::{OsRng, TryRngCore};
+
+use crate::account::{Account, AccountBehavior};
+use crate::keypair::{Keypair, KeypairBehavior};
+use crate::operation::Operation;
+use crate::transaction::{Transaction, TransactionBehavior};
+use crate::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+use crate::utils::decode_encode_muxed_account::encode_muxed_account_to_address;
+use crate::xdr;
+use crate::xdr::{ReadXdr, WriteXdr};
+
+/// Leeway, in seconds, granted on the challenge time bounds for clock skew.
+pub const GRACE_PERIOD: u64 = 5 * 60;
+const NONCE_LENGTH: usize = 48;
+
+/// Optional parts of a challenge.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChallengeOptions {
+    /// Memo identifying the user of a shared G... account.
+    pub memo: Option<u64>,
+    /// Home domain of the client wallet and the `SIGNING_KEY` from its `stellar.toml`.
+    pub client_domain: Option<(String, String)>,
+}
+
+/// A challenge that passed [read_challenge_tx](WebAuthBehavior::read_challenge_tx).
+#[derive(Debug, Clone)]
+pub struct Challenge {
+    pub tx: Transaction,
+    pub client_account_id: String,
+    pub matched_home_domain: String,
+    pub memo: Option<u64>,
+    /// Source account of the `client_domain` operation, when there is one.
+    pub client_signing_key: Option<String>,
+}
+
+// Define a trait for WebAuth behavior
+pub trait WebAuthBehavior {
+    fn build_challenge_tx(
+        server_keypair: &Keypair,
+        client_account_id: &str,
+        home_domain: &str,
+        web_auth_domain: &str,
+        network_passphrase: &str,
+        timeout: u64,
+        options: &ChallengeOptions,
+    ) -> Result<String, String>;
+    fn read_challenge_tx(
+        challenge: &str,
+        server_account_id: &str,
+        network_passphrase: &str,
+        home_domains: &[&str],
+        web_auth_domain: &str,
+    ) -> Result<Challenge, String>;
+    fn verify_challenge_tx_signers(
+        challenge: &str,
+        server_account_id: &str,
+        network_passphrase: &str,
+        signers: &[&str],
+        home_domains: &[&str],
+        web_auth_domain: &str,
+    ) -> Result<Vec<String>, String>;
+    fn verify_challenge_tx_threshold(
+        challenge: &str,
+        server_account_id: &str,
+        network_passphrase: &str,
+        threshold: u32,
+        signers: &[(&str, u32)],
+        home_domains: &[&str],
+        web_auth_domain: &str,
+    ) -> Result<Vec<String>, String>;
+}
+
+pub struct WebAuth;
+
+impl WebAuthBehavior for WebAuth {
+    /// Builds a challenge for `client_account_id` signed by the server and returns it as a
+    /// base64 encoded transaction envelope, valid for `timeout` seconds.
+    fn build_challenge_tx(
+        server_keypair: &Keypair,
+        client_account_id: &str,
+        home_domain: &str,
+        web_auth_domain: &str,
+        network_passphrase: &str,
+        timeout: u64,
+        options: &ChallengeOptions,
+    ) -> Result<String, String> {
+        let client = xdr::MuxedAccount::from_str(client_account_id)
+            .map_err(|_| "client_account_id is invalid".to_string())?;
+        if options.memo.is_some() && matches!(client, xdr::MuxedAccount::MuxedEd25519(_)) {
+            return Err("memo cannot be used if client_account_id is a muxed account".to_string());
+        }
+
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng
+            .try_fill_bytes(&mut nonce)
+            .map_err(|_| "failed to generate a nonce".to_string())?;
+        let nonce = STANDARD.encode(nonce).into_bytes();
+
+        let now = now()?;
+        let mut server = Account::new(&server_keypair.public_key(), "-1")?;
+        let mut builder = TransactionBuilder::new(
+            &mut server,
+            network_passphrase,
+            Some(xdr::TimeBounds {
+                min_time: xdr::TimePoint(now),
+                max_time: xdr::TimePoint(now + timeout),
+            }),
+        );
+        builder.fee(100u32);
+
+        builder.add_operation(
+            Operation::with_source(client_account_id)
+                .and_then(|op| op.manage_data(&format!("{} auth", home_domain), Some(&nonce)))
+                .map_err(|_| "home_domain is invalid".to_string())?,
+        );
+        builder.add_operation(
+            Operation::with_source(&server_keypair.public_key())
+                .and_then(|op| {
+                    op.manage_data(
+                        "web_auth_domain",
+                        Some(&web_auth_domain.as_bytes().to_vec()),
+                    )
+                })
+                .map_err(|_| "web_auth_domain is invalid".to_string())?,
+        );
+        if let Some((domain, signing_key)) = &options.client_domain {
+            builder.add_operation(
+                Operation::with_source(signing_key)
+                    .and_then(|op| {
+                        op.manage_data("client_domain", Some(&domain.as_bytes().to_vec()))
+                    })
+                    .map_err(|_| "client_domain is invalid".to_string())?,
+            );
+        }
+
+        let mut tx = builder.build();
+        if let Some(memo) = options.memo {
+            tx.memo = Some(xdr::Memo::Id(memo));
+        }
+        tx.sign(std::slice::from_ref(server_keypair));
+
+        tx.to_envelope()
+            .and_then(|envelope| Ok(envelope.to_xdr_base64(xdr::Limits::none())?))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Reads a challenge and checks its structure, time bounds, home domain, web auth domain
+    /// and server signature. Client signatures are not checked.
+    fn read_challenge_tx(
+        challenge: &str,
+        server_account_id: &str,
+        network_passphrase: &str,
+        home_domains: &[&str],
+        web_auth_domain: &str,
+    ) -> Result<Challenge, String> {
+        Self::read_challenge_at(
+            challenge,
+            server_account_id,
+            network_passphrase,
+            home_domains,
+            web_auth_domain,
+            now()?,
+        )
+    }
+
+    /// Verifies that a challenge is signed by the server and by one or more of `signers`,
+    /// and by nothing else. Returns the signers found.
+    fn verify_challenge_tx_signers(
+        challenge: &str,
+        server_account_id: &str,
+        network_passphrase: &str,
+        signers: &[&str],
+        home_domains: &[&str],
+        web_auth_domain: &str,
+    ) -> Result<Vec<String>, String> {
+        Self::verify_signers_at(
+            challenge,
+            server_account_id,
+            network_passphrase,
+            signers,
+            home_domains,
+            web_auth_domain,
+            now()?,
+        )
+    }
+
+    /// Verifies that the client signers of a challenge reach `threshold` with the weights in
+    /// `signers`. Returns the signers found.
+    fn verify_challenge_tx_threshold(
+        challenge: &str,
+        server_account_id: &str,
+        network_passphrase: &str,
+        threshold: u32,
+        signers: &[(&str, u32)],
+        home_domains: &[&str],
+        web_auth_domain: &str,
+    ) -> Result<Vec<String>, String> {
+        let keys: Vec<&str> = signers.iter().map(|(key, _)| *key).collect();
+        let signers_found = Self::verify_challenge_tx_signers(
+            challenge,
+            server_account_id,
+            network_passphrase,
+            &keys,
+            home_domains,
+            web_auth_domain,
+        )?;
+
+        let weight: u32 = signers_found
+            .iter()
+            .filter_map(|found| signers.iter().find(|(key, _)| key == found))
+            .map(|(_, weight)| weight)
+            .sum();
+        if weight < threshold {
+            return Err(format!(
+                "signers with weight {} do not meet threshold {}",
+                weight, threshold
+            ));
+        }
+
+        Ok(signers_found)
+    }
+}
+
+// The checks behind `read_challenge_tx` and `verify_challenge_tx_signers`, at time `now`
+impl WebAuth {
+    fn read_challenge_at(
+        challenge: &str,
+        server_account_id: &str,
+        network_passphrase: &str,
+        home_domains: &[&str],
+        web_auth_domain: &str,
+        now: u64,
+    ) -> Result<Challenge, String> {
+        match xdr::TransactionEnvelope::from_xdr_base64(challenge, xdr::Limits::none()) {
+            Ok(xdr::TransactionEnvelope::Tx(_)) => {}
+            Ok(_) => return Err("challenge cannot be a fee bump or v0 transaction".to_string()),
+            Err(_) => return Err("challenge is not a valid transaction envelope".to_string()),
+        }
+        let tx = Transaction::from_xdr_envelope(challenge, network_passphrase);
+
+        if tx.source.as_deref() != Some(server_account_id) {
+            return Err("transaction source account is not equal to server's account".to_string());
+        }
+        if tx.sequence.as_deref() != Some("0") {
+            return Err("transaction sequence number must be 0".to_string());
+        }
+
+        let time_bounds = tx
+            .time_bounds
+            .clone()
+            .ok_or("transaction requires timebounds")?;
+        if time_bounds.max_time.0 == 0 {
+            return Err("transaction requires non-infinite timebounds".to_string());
+        }
+        if now.saturating_add(GRACE_PERIOD) < time_bounds.min_time.0
+            || now > time_bounds.max_time.0.saturating_add(GRACE_PERIOD)
+        {
+            return Err("transaction is not within range of the specified timebounds".to_string());
+        }
+
+        let operations = tx.operations.clone().unwrap_or_default();
+        let (first, rest) = operations
+            .split_first()
+            .ok_or("transaction requires at least one ManageData operation")?;
+        let (client_account_id, name, nonce) = manage_data(first)?;
+        let client_account_id =
+            client_account_id.ok_or("first operation should have a source account")?;
+
+        let memo = match &tx.memo {
+            None | Some(xdr::Memo::None) => None,
+            Some(xdr::Memo::Id(id)) => Some(*id),
+            Some(_) => return Err("memo must be of type ID".to_string()),
+        };
+        if memo.is_some() && client_account_id.starts_with('M') {
+            return Err("memo cannot be used if the client account is a muxed account".to_string());
+        }
+
+        let nonce = nonce.ok_or("first operation should have a value")?;
+        if nonce.len() != 64 || STANDARD.decode(&nonce).map(|n| n.len()) != Ok(NONCE_LENGTH) {
+            return Err("random nonce encoded as base64 should be 64 bytes long".to_string());
+        }
+        let matched_home_domain = home_domains
+            .iter()
+            .find(|domain| name == format!("{} auth", domain))
+            .ok_or("invalid homeDomains: the transaction's operation key name does not match the expected home domain")?
+            .to_string();
+
+        let mut client_signing_key = None;
+        for op in rest {
+            let (source, name, value) = manage_data(op)?;
+            let source = source.ok_or("operation should have a source account")?;
+            match name.as_str() {
+                "web_auth_domain" if value.as_deref() != Some(web_auth_domain.as_bytes()) => {
+                    return Err(
+                        "'web_auth_domain' operation value does not match web_auth_domain"
+                            .to_string(),
+                    );
+                }
+                "client_domain" => client_signing_key = Some(source.clone()),
+                _ => {}
+            }
+            if source != server_account_id && name != "client_domain" {
+                return Err(
+                    "the transaction has operations that are unrecognized by the server"
+                        .to_string(),
+                );
+            }
+        }
+
+        if !signed_by(&tx, server_account_id) {
+            return Err("transaction not signed by server".to_string());
+        }
+
+        Ok(Challenge {
+            tx,
+            client_account_id,
+            matched_home_domain,
+            memo,
+            client_signing_key,
+        })
+    }
+
+    fn verify_signers_at(
+        challenge: &str,
+        server_account_id: &str,
+        network_passphrase: &str,
+        signers: &[&str],
+        home_domains: &[&str],
+        web_auth_domain: &str,
+        now: u64,
+    ) -> Result<Vec<String>, String> {
+        let challenge = Self::read_challenge_at(
+            challenge,
+            server_account_id,
+            network_passphrase,
+            home_domains,
+            web_auth_domain,
+            now,
+        )?;
+
+        let mut client_signers: Vec<&str> = Vec::new();
+        for signer in signers {
+            if *signer != server_account_id
+                && signer.starts_with('G')
+                && Keypair::from_public_key(signer).is_ok()
+                && !client_signers.contains(signer)
+            {
+                client_signers.push(signer);
+            }
+        }
+        if client_signers.is_empty() {
+            return Err(
+                "no verifiable client signers provided, at least one G... address must be provided"
+                    .to_string(),
+            );
+        }
+
+        let hash = challenge.tx.hash();
+        let mut used = vec![false; challenge.tx.signatures.len()];
+        claim_signature(
+            &hash,
+            &challenge.tx.signatures,
+            &mut used,
+            server_account_id,
+        );
+
+        if let Some(signing_key) = &challenge.client_signing_key {
+            if !claim_signature(&hash, &challenge.tx.signatures, &mut used, signing_key) {
+                return Err(
+                    "transaction not signed by the source account of the 'client_domain' ManageData operation"
+                        .to_string(),
+                );
+            }
+        }
+
+        let signers_found: Vec<String> = client_signers
+            .into_iter()
+            .filter(|signer| claim_signature(&hash, &challenge.tx.signatures, &mut used, signer))
+            .map(String::from)
+            .collect();
+        if signers_found.is_empty() {
+            return Err("transaction not signed by any client signer".to_string());
+        }
+        if used.contains(&false) {
+            return Err("transaction has unrecognized signatures".to_string());
+        }
+
+        Ok(signers_found)
+    }
+}
+
+fn now() -> Result<u64, String> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|e| format!("Error getting current time: {}", e))
+}
+
+// Source, name and value of a manage data operation
+type ManageData = (Option<String>, String, Option<Vec<u8>>);
+
+fn manage_data(op: &xdr::Operation) -> Result<ManageData, String> {
+    let xdr::OperationBody::ManageData(data) = &op.body else {
+        return Err("operation type should be manageData".to_string());
+    };
+
+    Ok((
+        op.source_account
+            .as_ref()
+            .map(encode_muxed_account_to_address),
+        String::from_utf8_lossy(data.data_name.as_slice()).to_string(),
+        data.data_value.as_ref().map(|value| value.to_vec()),
+    ))
+}
+
+fn signed_by(tx: &Transaction, account_id: &str) -> bool {
+    let mut used = vec![false; tx.signatures.len()];
+    claim_signature(&tx.hash(), &tx.signatures, &mut used, account_id)
+}
+
+// Marks the first unused signature made by `account_id` as used
+fn claim_signature(
+    hash: &[u8; 32],
+    signatures: &[xdr::DecoratedSignature],
+    used: &mut [bool],
+    account_id: &str,
+) -> bool {
+    let Ok(keypair) = Keypair::from_public_key(account_id) else {
+        return false;
+    };
+    let hint = keypair.signature_hint();
+
+    for (signature, used) in signatures.iter().zip(used.iter_mut()) {
+        if !*used
+            && hint.as_deref() == Some(signature.hint.0.as_slice())
+            && keypair.verify(hash, &signature.signature.0)
+        {
+            *used = true;
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{NetworkPassphrase, Networks};
+
+    const HOME_DOMAIN: &str = "testanchor.stellar.org";
+    const WEB_AUTH_DOMAIN: &str = "auth.testanchor.stellar.org";
+
+    fn challenge(server: &Keypair, client: &str, options: &ChallengeOptions) -> String {
+        WebAuth::build_challenge_tx(
+            server,
+            client,
+            HOME_DOMAIN,
+            WEB_AUTH_DOMAIN,
+            Networks::testnet(),
+            300,
+            options,
+        )
+        .unwrap()
+    }
+
+    fn sign(challenge: &str, signers: &[Keypair]) -> String {
+        let mut tx = Transaction::from_xdr_envelope(challenge, Networks::testnet());
+        tx.sign(signers);
+        tx.to_envelope()
+            .unwrap()
+            .to_xdr_base64(xdr::Limits::none())
+            .unwrap()
+    }
+
+    fn read(challenge: &str, server: &Keypair) -> Result<Challenge, String> {
+        WebAuth::read_challenge_tx(
+            challenge,
+            &server.public_key(),
+            Networks::testnet(),
+            &[HOME_DOMAIN],
+            WEB_AUTH_DOMAIN,
+        )
+    }
+
+    fn verify_signers(
+        challenge: &str,
+        server: &Keypair,
+        signers: &[&str],
+    ) -> Result<Vec<String>, String> {
+        WebAuth::verify_challenge_tx_signers(
+            challenge,
+            &server.public_key(),
+            Networks::testnet(),
+            signers,
+            &[HOME_DOMAIN],
+            WEB_AUTH_DOMAIN,
+        )
+    }
+
+    // Rebuilds a challenge from raw parts, signed by `server`
+    fn custom_challenge(
+        server: &Keypair,
+        sequence: &str,
+        time_bounds: Option<xdr::TimeBounds>,
+        operations: Vec<xdr::Operation>,
+    ) -> String {
+        let mut account = Account::new(&server.public_key(), sequence).unwrap();
+        let mut builder = TransactionBuilder::new(&mut account, Networks::testnet(), time_bounds);
+        builder.fee(100u32);
+        for op in operations {
+            builder.add_operation(op);
+        }
+        let mut tx = builder.build();
+        tx.sign(std::slice::from_ref(server));
+        tx.to_envelope()
+            .unwrap()
+            .to_xdr_base64(xdr::Limits::none())
+            .unwrap()
+    }
+
+    fn nonce_op(source: &str, name: &str) -> xdr::Operation {
+        Operation::with_source(source)
+            .unwrap()
+            .manage_data(name, Some(&vec![b'A'; 64]))
+            .unwrap()
+    }
+
+    fn current_time_bounds() -> Option<xdr::TimeBounds> {
+        let now = now().unwrap();
+        Some(xdr::TimeBounds {
+            min_time: xdr::TimePoint(now),
+            max_time: xdr::TimePoint(now + 300),
+        })
+    }
+
+    #[test]
+    fn test_builds_challenge() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let envelope = challenge(&server, &client.public_key(), &ChallengeOptions::default());
+        let tx = Transaction::from_xdr_envelope(&envelope, Networks::testnet());
+
+        assert_eq!(tx.source, Some(server.public_key()));
+        assert_eq!(tx.sequence.as_deref(), Some("0"));
+        assert_eq!(tx.signatures.len(), 1);
+        let time_bounds = tx.time_bounds.unwrap();
+        assert_eq!(time_bounds.max_time.0 - time_bounds.min_time.0, 300);
+
+        let operations = tx.operations.unwrap();
+        assert_eq!(operations.len(), 2);
+        let (source, name, nonce) = manage_data(&operations[0]).unwrap();
+        assert_eq!(source, Some(client.public_key()));
+        assert_eq!(name, "testanchor.stellar.org auth");
+        assert_eq!(nonce.unwrap().len(), 64);
+        let (source, name, value) = manage_data(&operations[1]).unwrap();
+        assert_eq!(source, Some(server.public_key()));
+        assert_eq!(name, "web_auth_domain");
+        assert_eq!(value.unwrap(), WEB_AUTH_DOMAIN.as_bytes());
+    }
+
+    #[test]
+    fn test_builds_challenge_with_memo_and_client_domain() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let wallet = Keypair::random().unwrap();
+        let options = ChallengeOptions {
+            memo: Some(100),
+            client_domain: Some(("wallet.example.com".to_string(), wallet.public_key())),
+        };
+        let envelope = challenge(&server, &client.public_key(), &options);
+
+        let read = read(&envelope, &server).unwrap();
+        assert_eq!(read.memo, Some(100));
+        assert_eq!(read.client_signing_key, Some(wallet.public_key()));
+        assert_eq!(read.tx.operations.unwrap().len(), 3);
+
+        let muxed = "MA7QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJVAAAAAAAAAAAAAJLK";
+        assert_eq!(
+            WebAuth::build_challenge_tx(
+                &server,
+                muxed,
+                HOME_DOMAIN,
+                WEB_AUTH_DOMAIN,
+                Networks::testnet(),
+                300,
+                &options,
+            )
+            .unwrap_err(),
+            "memo cannot be used if client_account_id is a muxed account"
+        );
+    }
+
+    #[test]
+    fn test_reads_challenge() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let envelope = challenge(&server, &client.public_key(), &ChallengeOptions::default());
+
+        let read = read(&envelope, &server).unwrap();
+        assert_eq!(read.client_account_id, client.public_key());
+        assert_eq!(read.matched_home_domain, HOME_DOMAIN);
+        assert_eq!(read.memo, None);
+
+        let read = WebAuth::read_challenge_tx(
+            &envelope,
+            &server.public_key(),
+            Networks::testnet(),
+            &["example.com", HOME_DOMAIN],
+            WEB_AUTH_DOMAIN,
+        )
+        .unwrap();
+        assert_eq!(read.matched_home_domain, HOME_DOMAIN);
+    }
+
+    #[test]
+    fn test_read_rejects_wrong_server_domains_and_network() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let envelope = challenge(&server, &client.public_key(), &ChallengeOptions::default());
+
+        assert_eq!(
+            read(&envelope, &client).unwrap_err(),
+            "transaction source account is not equal to server's account"
+        );
+        assert!(WebAuth::read_challenge_tx(
+            &envelope,
+            &server.public_key(),
+            Networks::testnet(),
+            &["example.com"],
+            WEB_AUTH_DOMAIN,
+        )
+        .unwrap_err()
+        .starts_with("invalid homeDomains"));
+        assert_eq!(
+            WebAuth::read_challenge_tx(
+                &envelope,
+                &server.public_key(),
+                Networks::testnet(),
+                &[HOME_DOMAIN],
+                "example.com",
+            )
+            .unwrap_err(),
+            "'web_auth_domain' operation value does not match web_auth_domain"
+        );
+        assert_eq!(
+            WebAuth::read_challenge_tx(
+                &envelope,
+                &server.public_key(),
+                Networks::public(),
+                &[HOME_DOMAIN],
+                WEB_AUTH_DOMAIN,
+            )
+            .unwrap_err(),
+            "transaction not signed by server"
+        );
+        assert_eq!(
+            read("AAAA", &server).unwrap_err(),
+            "challenge is not a valid transaction envelope"
+        );
+    }
+
+    #[test]
+    fn test_read_rejects_invalid_structure() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let auth = format!("{} auth", HOME_DOMAIN);
+
+        let envelope = custom_challenge(
+            &server,
+            "0",
+            current_time_bounds(),
+            vec![nonce_op(&client.public_key(), &auth)],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "transaction sequence number must be 0"
+        );
+
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            None,
+            vec![nonce_op(&client.public_key(), &auth)],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "transaction requires timebounds"
+        );
+
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            Some(xdr::TimeBounds {
+                min_time: xdr::TimePoint(0),
+                max_time: xdr::TimePoint(0),
+            }),
+            vec![nonce_op(&client.public_key(), &auth)],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "transaction requires non-infinite timebounds"
+        );
+
+        let now = now().unwrap();
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            Some(xdr::TimeBounds {
+                min_time: xdr::TimePoint(now - 1000),
+                max_time: xdr::TimePoint(now - GRACE_PERIOD - 1),
+            }),
+            vec![nonce_op(&client.public_key(), &auth)],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "transaction is not within range of the specified timebounds"
+        );
+
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            current_time_bounds(),
+            vec![Operation::new()
+                .manage_data(&auth, Some(&vec![b'A'; 64]))
+                .unwrap()],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "first operation should have a source account"
+        );
+
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            current_time_bounds(),
+            vec![Operation::with_source(&client.public_key())
+                .unwrap()
+                .manage_data(&auth, Some(&vec![b'A'; 32]))
+                .unwrap()],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "random nonce encoded as base64 should be 64 bytes long"
+        );
+
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            current_time_bounds(),
+            vec![
+                nonce_op(&client.public_key(), &auth),
+                nonce_op(&client.public_key(), "other"),
+            ],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "the transaction has operations that are unrecognized by the server"
+        );
+
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            current_time_bounds(),
+            vec![
+                nonce_op(&client.public_key(), &auth),
+                Operation::new().bump_sequence(1).unwrap(),
+            ],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "operation type should be manageData"
+        );
+    }
+
+    #[test]
+    fn test_read_handles_extreme_timebounds() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let auth = format!("{} auth", HOME_DOMAIN);
+
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            Some(xdr::TimeBounds {
+                min_time: xdr::TimePoint(now().unwrap()),
+                max_time: xdr::TimePoint(u64::MAX),
+            }),
+            vec![nonce_op(&client.public_key(), &auth)],
+        );
+        assert!(read(&envelope, &server).is_ok());
+
+        let envelope = custom_challenge(
+            &server,
+            "-1",
+            Some(xdr::TimeBounds {
+                min_time: xdr::TimePoint(u64::MAX),
+                max_time: xdr::TimePoint(u64::MAX),
+            }),
+            vec![nonce_op(&client.public_key(), &auth)],
+        );
+        assert_eq!(
+            read(&envelope, &server).unwrap_err(),
+            "transaction is not within range of the specified timebounds"
+        );
+    }
+
+    #[test]
+    fn test_verify_signers() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let other = Keypair::random().unwrap();
+        let envelope = challenge(&server, &client.public_key(), &ChallengeOptions::default());
+
+        assert_eq!(
+            verify_signers(&envelope, &server, &[&client.public_key()]).unwrap_err(),
+            "transaction not signed by any client signer"
+        );
+
+        let signed = sign(&envelope, &[client.clone(), other.clone()]);
+        assert_eq!(
+            verify_signers(
+                &signed,
+                &server,
+                &[
+                    &client.public_key(),
+                    &other.public_key(),
+                    &client.public_key()
+                ],
+            )
+            .unwrap(),
+            vec![client.public_key(), other.public_key()]
+        );
+        assert_eq!(
+            verify_signers(&signed, &server, &[&client.public_key()]).unwrap_err(),
+            "transaction has unrecognized signatures"
+        );
+        assert_eq!(
+            verify_signers(&signed, &server, &[&server.public_key()]).unwrap_err(),
+            "no verifiable client signers provided, at least one G... address must be provided"
+        );
+    }
+
+    #[test]
+    fn test_verify_signers_requires_client_domain_signature() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let wallet = Keypair::random().unwrap();
+        let options = ChallengeOptions {
+            memo: None,
+            client_domain: Some(("wallet.example.com".to_string(), wallet.public_key())),
+        };
+        let envelope = challenge(&server, &client.public_key(), &options);
+
+        let signed = sign(&envelope, std::slice::from_ref(&client));
+        assert_eq!(
+            verify_signers(&signed, &server, &[&client.public_key()]).unwrap_err(),
+            "transaction not signed by the source account of the 'client_domain' ManageData operation"
+        );
+
+        let signed = sign(&envelope, &[client.clone(), wallet]);
+        assert_eq!(
+            verify_signers(&signed, &server, &[&client.public_key()]).unwrap(),
+            vec![client.public_key()]
+        );
+    }
+
+    #[test]
+    fn test_verify_threshold() {
+        let server = Keypair::random().unwrap();
+        let client = Keypair::random().unwrap();
+        let other = Keypair::random().unwrap();
+        let envelope = challenge(&server, &client.public_key(), &ChallengeOptions::default());
+        let signed = sign(&envelope, &[client.clone(), other.clone()]);
+        let signers = [(client.public_key(), 1), (other.public_key(), 2)];
+        let signers: Vec<(&str, u32)> = signers.iter().map(|(k, w)| (k.as_str(), *w)).collect();
+
+        let verify = |threshold| {
+            WebAuth::verify_challenge_tx_threshold(
+                &signed,
+                &server.public_key(),
+                Networks::testnet(),
+                threshold,
+                &signers,
+                &[HOME_DOMAIN],
+                WEB_AUTH_DOMAIN,
+            )
+        };
+
+        assert_eq!(verify(3).unwrap().len(), 2);
+        assert_eq!(
+            verify(4).unwrap_err(),
+            "signers with weight 3 do not meet threshold 4"
+        );
+    }
+
+    // Fixed challenge vectors. The server, client and other keys use the raw seeds [1; 32],
+    // [2; 32] and [3; 32], the nonce is base64 of the bytes 0..48 and the challenges are valid
+    // from VECTOR_MIN_TIME for 300 seconds, on the test network.
+    const VECTOR_SERVER: &str = "GCFIRY65OQE7DFP5KLNS2PF2LVZMUZYJX4OZIEQ36N2IQANUB5XVYOJR";
+    const VECTOR_SERVER_SECRET: &str = "SAAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQC5MY";
+    const VECTOR_CLIENT: &str = "GCATS5YOVB6ROX2WUNKGNQ2MP3GMXDMKSG2O4N5CLX3A6W4PZGZZI55U";
+    const VECTOR_OTHER: &str = "GDWUSKGGFDI4FRXK5EBTRECZSVQSSWJHHJOGH6JWG3AUMFFMQ435DIAG";
+    const VECTOR_NONCE: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8gISIjJCUmJygpKissLS4v";
+    const VECTOR_MIN_TIME: u64 = 1_700_000_000;
+    /// Signed by the server only.
+    const VECTOR_CHALLENGE: &str = "AAAAAgAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAMgAAAAAAAAAAAAAAAEAAAAAZVPxAAAAAABlU/IsAAAAAAAAAAIAAAABAAAAAIE5dw6ofRdfVqNUZsNMfszLjYqRtO43ol32D1uPybOUAAAACgAAABt0ZXN0YW5jaG9yLnN0ZWxsYXIub3JnIGF1dGgAAAAAAQAAAEBBQUVDQXdRRkJnY0lDUW9MREEwT0R4QVJFaE1VRlJZWEdCa2FHeHdkSGg4Z0lTSWpKQ1VtSnlncEtpc3NMUzR2AAAAAQAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAAoAAAAPd2ViX2F1dGhfZG9tYWluAAAAAAEAAAAbYXV0aC50ZXN0YW5jaG9yLnN0ZWxsYXIub3JnAAAAAAAAAAABtA9vXAAAAEDHrvAmFzYWL0/ktMo2cZDIzfWzr65uGEauB2stZ5/0+3rPCjLftCgxUj/s0KfFbFigW12q50S5rRbqjmrzUrkP";
+    /// VECTOR_CHALLENGE signed by the client.
+    const VECTOR_SIGNED: &str = "AAAAAgAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAMgAAAAAAAAAAAAAAAEAAAAAZVPxAAAAAABlU/IsAAAAAAAAAAIAAAABAAAAAIE5dw6ofRdfVqNUZsNMfszLjYqRtO43ol32D1uPybOUAAAACgAAABt0ZXN0YW5jaG9yLnN0ZWxsYXIub3JnIGF1dGgAAAAAAQAAAEBBQUVDQXdRRkJnY0lDUW9MREEwT0R4QVJFaE1VRlJZWEdCa2FHeHdkSGg4Z0lTSWpKQ1VtSnlncEtpc3NMUzR2AAAAAQAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAAoAAAAPd2ViX2F1dGhfZG9tYWluAAAAAAEAAAAbYXV0aC50ZXN0YW5jaG9yLnN0ZWxsYXIub3JnAAAAAAAAAAACtA9vXAAAAEDHrvAmFzYWL0/ktMo2cZDIzfWzr65uGEauB2stZ5/0+3rPCjLftCgxUj/s0KfFbFigW12q50S5rRbqjmrzUrkPj8mzlAAAAEA05kLL7bMyzEiEId89/Z1eto0O7SEUGH/lLALLxiSAxI8D5OLCOXBrsiGxsdO9l8A/zc2Vs+OIkp6/Jqp3ngEF";
+    /// VECTOR_CHALLENGE signed by the client and by VECTOR_OTHER.
+    const VECTOR_SIGNED_EXTRA: &str = "AAAAAgAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAMgAAAAAAAAAAAAAAAEAAAAAZVPxAAAAAABlU/IsAAAAAAAAAAIAAAABAAAAAIE5dw6ofRdfVqNUZsNMfszLjYqRtO43ol32D1uPybOUAAAACgAAABt0ZXN0YW5jaG9yLnN0ZWxsYXIub3JnIGF1dGgAAAAAAQAAAEBBQUVDQXdRRkJnY0lDUW9MREEwT0R4QVJFaE1VRlJZWEdCa2FHeHdkSGg4Z0lTSWpKQ1VtSnlncEtpc3NMUzR2AAAAAQAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAAoAAAAPd2ViX2F1dGhfZG9tYWluAAAAAAEAAAAbYXV0aC50ZXN0YW5jaG9yLnN0ZWxsYXIub3JnAAAAAAAAAAADtA9vXAAAAEDHrvAmFzYWL0/ktMo2cZDIzfWzr65uGEauB2stZ5/0+3rPCjLftCgxUj/s0KfFbFigW12q50S5rRbqjmrzUrkPj8mzlAAAAEA05kLL7bMyzEiEId89/Z1eto0O7SEUGH/lLALLxiSAxI8D5OLCOXBrsiGxsdO9l8A/zc2Vs+OIkp6/Jqp3ngEFrIc30QAAAECL2VCfy5lO7O5ypYOBJtuv2z0V2hNU5v18izosBRoJZ//X2gewpiCa+aMtaodOflMs0yeZF+wU3JWixZNaMM8M";
+    /// Sequence number 1 instead of 0.
+    const VECTOR_BAD_SEQUENCE: &str = "AAAAAgAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAMgAAAAAAAAAAQAAAAEAAAAAZVPxAAAAAABlU/IsAAAAAAAAAAIAAAABAAAAAIE5dw6ofRdfVqNUZsNMfszLjYqRtO43ol32D1uPybOUAAAACgAAABt0ZXN0YW5jaG9yLnN0ZWxsYXIub3JnIGF1dGgAAAAAAQAAAEBBQUVDQXdRRkJnY0lDUW9MREEwT0R4QVJFaE1VRlJZWEdCa2FHeHdkSGg4Z0lTSWpKQ1VtSnlncEtpc3NMUzR2AAAAAQAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAAoAAAAPd2ViX2F1dGhfZG9tYWluAAAAAAEAAAAbYXV0aC50ZXN0YW5jaG9yLnN0ZWxsYXIub3JnAAAAAAAAAAABtA9vXAAAAEDK5e1EJuThxJFIxTzJxf7CowyjUjICyIPZIWpiuvCzKjCzK3HTuNebcidsFCk6Ms0pVziE5xeKRd45eYYacf8H";
+    /// VECTOR_CHALLENGE without the server signature.
+    const VECTOR_UNSIGNED: &str = "AAAAAgAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAMgAAAAAAAAAAAAAAAEAAAAAZVPxAAAAAABlU/IsAAAAAAAAAAIAAAABAAAAAIE5dw6ofRdfVqNUZsNMfszLjYqRtO43ol32D1uPybOUAAAACgAAABt0ZXN0YW5jaG9yLnN0ZWxsYXIub3JnIGF1dGgAAAAAAQAAAEBBQUVDQXdRRkJnY0lDUW9MREEwT0R4QVJFaE1VRlJZWEdCa2FHeHdkSGg4Z0lTSWpKQ1VtSnlncEtpc3NMUzR2AAAAAQAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAAoAAAAPd2ViX2F1dGhfZG9tYWluAAAAAAEAAAAbYXV0aC50ZXN0YW5jaG9yLnN0ZWxsYXIub3JnAAAAAAAAAAAA";
+    /// A 24 byte nonce.
+    const VECTOR_BAD_NONCE: &str = "AAAAAgAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAMgAAAAAAAAAAAAAAAEAAAAAZVPxAAAAAABlU/IsAAAAAAAAAAIAAAABAAAAAIE5dw6ofRdfVqNUZsNMfszLjYqRtO43ol32D1uPybOUAAAACgAAABt0ZXN0YW5jaG9yLnN0ZWxsYXIub3JnIGF1dGgAAAAAAQAAACBBQUVDQXdRRkJnY0lDUW9MREEwT0R4QVJFaE1VRlJZWAAAAAEAAAAAiojj3XQJ8ZX9UtstPLpdcspnCb8dlBIb83SIAbQPb1wAAAAKAAAAD3dlYl9hdXRoX2RvbWFpbgAAAAABAAAAG2F1dGgudGVzdGFuY2hvci5zdGVsbGFyLm9yZwAAAAAAAAAAAbQPb1wAAABANsemIV1PgJLF8lEgWOfS7BCGUoeGLl42o/EDUs8rrkDzFUX0gvGbvaoCwnG9t42cpnzPcPhssqYtu0ZE6ygFAw==";
+    /// An extra `other` ManageData operation sourced by the client.
+    const VECTOR_UNKNOWN_OPERATION: &str = "AAAAAgAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAASwAAAAAAAAAAAAAAAEAAAAAZVPxAAAAAABlU/IsAAAAAAAAAAMAAAABAAAAAIE5dw6ofRdfVqNUZsNMfszLjYqRtO43ol32D1uPybOUAAAACgAAABt0ZXN0YW5jaG9yLnN0ZWxsYXIub3JnIGF1dGgAAAAAAQAAAEBBQUVDQXdRRkJnY0lDUW9MREEwT0R4QVJFaE1VRlJZWEdCa2FHeHdkSGg4Z0lTSWpKQ1VtSnlncEtpc3NMUzR2AAAAAQAAAACKiOPddAnxlf1S2y08ul1yymcJvx2UEhvzdIgBtA9vXAAAAAoAAAAPd2ViX2F1dGhfZG9tYWluAAAAAAEAAAAbYXV0aC50ZXN0YW5jaG9yLnN0ZWxsYXIub3JnAAAAAAEAAAAAgTl3Dqh9F19Wo1Rmw0x+zMuNipG07jeiXfYPW4/Js5QAAAAKAAAABW90aGVyAAAAAAAAAQAAAAV2YWx1ZQAAAAAAAAAAAAABtA9vXAAAAEDh0Ye73UYYJ+0hBc9Cvns2MgZwX5Xyxe21b94RYzNSCC9mDm/ASCRZiza+2UVJmaf59ZCbwDD1Ae6a/jdtOOoI";
+
+    fn read_vector(
+        challenge: &str,
+        home_domains: &[&str],
+        web_auth_domain: &str,
+        now: u64,
+    ) -> Result<Challenge, String> {
+        WebAuth::read_challenge_at(
+            challenge,
+            VECTOR_SERVER,
+            Networks::testnet(),
+            home_domains,
+            web_auth_domain,
+            now,
+        )
+    }
+
+    fn verify_vector(challenge: &str, signers: &[&str]) -> Result<Vec<String>, String> {
+        WebAuth::verify_signers_at(
+            challenge,
+            VECTOR_SERVER,
+            Networks::testnet(),
+            signers,
+            &[HOME_DOMAIN],
+            WEB_AUTH_DOMAIN,
+            VECTOR_MIN_TIME + 100,
+        )
+    }
+
+    #[test]
+    fn test_reads_fixed_challenge() {
+        assert_eq!(
+            Keypair::from_secret(VECTOR_SERVER_SECRET)
+                .unwrap()
+                .public_key(),
+            VECTOR_SERVER
+        );
+
+        let challenge = read_vector(
+            VECTOR_CHALLENGE,
+            &["example.com", HOME_DOMAIN],
+            WEB_AUTH_DOMAIN,
+            VECTOR_MIN_TIME + 100,
+        )
+        .unwrap();
+        assert_eq!(challenge.client_account_id, VECTOR_CLIENT);
+        assert_eq!(challenge.matched_home_domain, HOME_DOMAIN);
+        assert_eq!(challenge.memo, None);
+        assert_eq!(challenge.client_signing_key, None);
+        assert_eq!(challenge.tx.sequence.as_deref(), Some("0"));
+        let time_bounds = challenge.tx.time_bounds.clone().unwrap();
+        assert_eq!(time_bounds.min_time.0, VECTOR_MIN_TIME);
+        assert_eq!(time_bounds.max_time.0, VECTOR_MIN_TIME + 300);
+        let (_, _, nonce) = manage_data(&challenge.tx.operations.unwrap()[0]).unwrap();
+        assert_eq!(nonce.unwrap(), VECTOR_NONCE.as_bytes());
+
+        // the time bounds are checked with the grace period
+        for now in [
+            VECTOR_MIN_TIME - GRACE_PERIOD,
+            VECTOR_MIN_TIME + 300 + GRACE_PERIOD,
+        ] {
+            assert!(read_vector(VECTOR_CHALLENGE, &[HOME_DOMAIN], WEB_AUTH_DOMAIN, now).is_ok());
+        }
+        for now in [
+            VECTOR_MIN_TIME - GRACE_PERIOD - 1,
+            VECTOR_MIN_TIME + 300 + GRACE_PERIOD + 1,
+        ] {
+            assert_eq!(
+                read_vector(VECTOR_CHALLENGE, &[HOME_DOMAIN], WEB_AUTH_DOMAIN, now).unwrap_err(),
+                "transaction is not within range of the specified timebounds"
+            );
+        }
+        // the challenge has expired by now
+        assert_eq!(
+            WebAuth::read_challenge_tx(
+                VECTOR_CHALLENGE,
+                VECTOR_SERVER,
+                Networks::testnet(),
+                &[HOME_DOMAIN],
+                WEB_AUTH_DOMAIN,
+            )
+            .unwrap_err(),
+            "transaction is not within range of the specified timebounds"
+        );
+    }
+
+    #[test]
+    fn test_fixed_challenge_rejections() {
+        let now = VECTOR_MIN_TIME + 100;
+        let read = |challenge| read_vector(challenge, &[HOME_DOMAIN], WEB_AUTH_DOMAIN, now);
+
+        let cases = [
+            (VECTOR_BAD_SEQUENCE, "transaction sequence number must be 0"),
+            (VECTOR_UNSIGNED, "transaction not signed by server"),
+            (
+                VECTOR_BAD_NONCE,
+                "random nonce encoded as base64 should be 64 bytes long",
+            ),
+            (
+                VECTOR_UNKNOWN_OPERATION,
+                "the transaction has operations that are unrecognized by the server",
+            ),
+        ];
+        for (challenge, error) in cases {
+            assert_eq!(read(challenge).unwrap_err(), error);
+        }
+
+        assert_eq!(
+            read_vector(VECTOR_CHALLENGE, &["example.com"], WEB_AUTH_DOMAIN, now).unwrap_err(),
+            "invalid homeDomains: the transaction's operation key name does not match the expected home domain"
+        );
+        assert_eq!(
+            read_vector(VECTOR_CHALLENGE, &[HOME_DOMAIN], "auth.example.com", now).unwrap_err(),
+            "'web_auth_domain' operation value does not match web_auth_domain"
+        );
+        assert_eq!(
+            WebAuth::read_challenge_at(
+                VECTOR_CHALLENGE,
+                VECTOR_CLIENT,
+                Networks::testnet(),
+                &[HOME_DOMAIN],
+                WEB_AUTH_DOMAIN,
+                now,
+            )
+            .unwrap_err(),
+            "transaction source account is not equal to server's account"
+        );
+        // signatures cover the network passphrase
+        assert_eq!(
+            WebAuth::read_challenge_at(
+                VECTOR_CHALLENGE,
+                VECTOR_SERVER,
+                Networks::public(),
+                &[HOME_DOMAIN],
+                WEB_AUTH_DOMAIN,
+                now,
+            )
+            .unwrap_err(),
+            "transaction not signed by server"
+        );
+    }
+
+    #[test]
+    fn test_verifies_fixed_challenge_signers() {
+        assert_eq!(
+            verify_vector(VECTOR_CHALLENGE, &[VECTOR_CLIENT]).unwrap_err(),
+            "transaction not signed by any client signer"
+        );
+        assert_eq!(
+            verify_vector(VECTOR_SIGNED, &[VECTOR_CLIENT, VECTOR_OTHER]).unwrap(),
+            vec![VECTOR_CLIENT]
+        );
+        assert_eq!(
+            verify_vector(VECTOR_SIGNED, &[VECTOR_OTHER]).unwrap_err(),
+            "transaction not signed by any client signer"
+        );
+        assert_eq!(
+            verify_vector(VECTOR_SIGNED_EXTRA, &[VECTOR_CLIENT]).unwrap_err(),
+            "transaction has unrecognized signatures"
+        );
+        assert_eq!(
+            verify_vector(VECTOR_SIGNED_EXTRA, &[VECTOR_CLIENT, VECTOR_OTHER]).unwrap(),
+            vec![VECTOR_CLIENT, VECTOR_OTHER]
+        );
+        assert_eq!(
+            verify_vector(VECTOR_SIGNED, &[VECTOR_SERVER]).unwrap_err(),
+            "no verifiable client signers provided, at least one G... address must be provided"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! SEP-29 account memo requirements.
+//!
+//! Accounts that need a memo to attribute incoming payments set the `config.memo_required`
+//! data entry to `1`. See <https://github.com/stellar/stellar-protocol/blob/master/ecosystem/sep-0029.md>.
+use crate::transaction::Transaction;
+use crate::utils::decode_encode_muxed_account::encode_muxed_account_to_address;
+use crate::xdr;
+
+pub const MEMO_REQUIRED_DATA_KEY: &str = "config.memo_required";
+pub const MEMO_REQUIRED_VALUE: &[u8] = b"1";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The operation at `index` sends to `destination`, which requires a memo.
+    MemoRequired { index: usize, destination: String },
+    /// The data entry lookup failed.
+    Lookup(String),
+}
+
+// Define a trait for MemoRequired behavior
+pub trait MemoRequiredBehavior {
+    fn check_memo_required<F>(&self, lookup: F) -> Result<(), Error>
+    where
+        F: FnMut(&str, &str) -> Result<Option<Vec<u8>>, String>;
+}
+
+impl MemoRequiredBehavior for Transaction {
+    /// Checks that no payment, path payment or account merge sends to an account that requires
+    /// a memo while the transaction has none.
+    ///
+    /// `lookup` returns the value of a data entry given an account id and the entry name, or
+    /// `None` when the account or the entry does not exist. Muxed destinations are skipped since
+    /// they already identify the recipient.
+    fn check_memo_required<F>(&self, mut lookup: F) -> Result<(), Error>
+    where
+        F: FnMut(&str, &str) -> Result<Option<Vec<u8>>, String>,
+    {
+        if !matches!(self.memo, None | Some(xdr::Memo::None)) {
+            return Ok(());
+        }
+
+        let mut checked = Vec::new();
+        for (index, op) in self.operations.iter().flatten().enumerate() {
+            let destination = match &op.body {
+                xdr::OperationBody::Payment(payment) => &payment.destination,
+                xdr::OperationBody::PathPaymentStrictReceive(payment) => &payment.destination,
+                xdr::OperationBody::PathPaymentStrictSend(payment) => &payment.destination,
+                xdr::OperationBody::AccountMerge(destination) => destination,
+                _ => continue,
+            };
+            if let xdr::MuxedAccount::MuxedEd25519(_) = destination {
+                continue;
+            }
+
+            let destination = encode_muxed_account_to_address(destination);
+            if checked.contains(&destination) {
+                continue;
+            }
+            let value = lookup(&destination, MEMO_REQUIRED_DATA_KEY).map_err(Error::Lookup)?;
+            if value.as_deref() == Some(MEMO_REQUIRED_VALUE) {
+                return Err(Error::MemoRequired { index, destination });
+            }
+            checked.push(destination);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, AccountBehavior};
+    use crate::asset::{Asset, AssetBehavior};
+    use crate::network::{NetworkPassphrase, Networks};
+    use crate::operation::Operation;
+    use crate::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+
+    const SOURCE: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const EXCHANGE: &str = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+    const MUXED_EXCHANGE: &str =
+        "MA7QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJVAAAAAAAAAAAAAJLK";
+
+    fn transaction(operations: Vec<xdr::Operation>, memo: Option<xdr::Memo>) -> Transaction {
+        let mut source = Account::new(SOURCE, "1").unwrap();
+        let mut builder = TransactionBuilder::new(&mut source, Networks::testnet(), None);
+        builder.fee(100_u32);
+        for op in operations {
+            builder.add_operation(op);
+        }
+        let mut tx = builder.build();
+        tx.memo = memo;
+        tx
+    }
+
+    fn lookup(account: &str, key: &str) -> Result<Option<Vec<u8>>, String> {
+        assert_eq!(key, MEMO_REQUIRED_DATA_KEY);
+        if account == EXCHANGE {
+            Ok(Some(MEMO_REQUIRED_VALUE.to_vec()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn payment(destination: &str) -> xdr::Operation {
+        Operation::new()
+            .payment(destination, &Asset::native(), 10)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rejects_payment_without_memo() {
+        let tx = transaction(
+            vec![
+                Operation::new().bump_sequence(5).unwrap(),
+                payment(SOURCE),
+                payment(EXCHANGE),
+            ],
+            None,
+        );
+
+        assert_eq!(
+            tx.check_memo_required(lookup),
+            Err(Error::MemoRequired {
+                index: 2,
+                destination: EXCHANGE.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_checks_path_payments_and_merges() {
+        let path_payment = Operation::new()
+            .path_payment_strict_send(&Asset::native(), 10, EXCHANGE, &Asset::native(), 1, &[])
+            .unwrap();
+        let tx = transaction(vec![path_payment], Some(xdr::Memo::None));
+        assert_eq!(
+            tx.check_memo_required(lookup),
+            Err(Error::MemoRequired {
+                index: 0,
+                destination: EXCHANGE.to_string()
+            })
+        );
+
+        let merge = Operation::new().account_merge(EXCHANGE).unwrap();
+        let tx = transaction(vec![merge], None);
+        assert!(matches!(
+            tx.check_memo_required(lookup),
+            Err(Error::MemoRequired { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_accepts_memo_and_muxed_destinations() {
+        let tx = transaction(vec![payment(EXCHANGE)], Some(xdr::Memo::Id(1)));
+        assert_eq!(tx.check_memo_required(lookup), Ok(()));
+
+        let tx = transaction(vec![payment(MUXED_EXCHANGE)], None);
+        assert_eq!(
+            tx.check_memo_required(|_, _| Ok(Some(MEMO_REQUIRED_VALUE.to_vec()))),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_looks_up_each_destination_once() {
+        let tx = transaction(vec![payment(SOURCE), payment(SOURCE)], None);
+        let mut lookups = 0;
+
+        tx.check_memo_required(|_, _| {
+            lookups += 1;
+            Ok(None)
+        })
+        .unwrap();
+        assert_eq!(lookups, 1);
+
+        assert_eq!(
+            tx.check_memo_required(|_, _| Err("horizon is down".into())),
+            Err(Error::Lookup("horizon is down".into()))
+        );
+    }
+}
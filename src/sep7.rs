@@ -0,0 +1,600 @@
+//! SEP-7 URI scheme to delegate signing.
+//!
+//! Parses and produces `web+stellar:tx` and `web+stellar:pay` URIs, as found in deep links and
+//! QR codes, and signs them with the `URI_REQUEST_SIGNING_KEY` of their origin domain. See
+//! <https://github.com/stellar/stellar-protocol/blob/master/ecosystem/sep-0007.md>.
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::keypair::{Keypair, KeypairBehavior};
+use crate::network::{NetworkPassphrase, Networks};
+use crate::transaction::{Transaction, TransactionBehavior};
+use crate::xdr;
+use crate::xdr::ReadXdr;
+
+pub const SCHEME: &str = "web+stellar:";
+/// Maximum length of the `msg` parameter.
+pub const MAX_MSG_LENGTH: usize = 300;
+const SIGNATURE_PREFIX: &str = "stellar.sep.7 - URI Scheme";
+const MEMO_TYPES: [&str; 4] = ["MEMO_TEXT", "MEMO_ID", "MEMO_HASH", "MEMO_RETURN"];
+
+/// A field of the transaction the wallet should ask the user for, written as a Txrep path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pub path: String,
+    pub id: String,
+    pub hint: String,
+}
+
+/// A `web+stellar:tx` request to sign a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionRequest {
+    /// Base64 encoded `TransactionEnvelope`.
+    pub xdr: String,
+    pub replace: Vec<Replacement>,
+    pub callback: Option<String>,
+    pub pubkey: Option<String>,
+    pub chain: Option<String>,
+    pub msg: Option<String>,
+    pub network_passphrase: Option<String>,
+    pub origin_domain: Option<String>,
+    pub signature: Option<String>,
+}
+
+/// A `web+stellar:pay` request to pay a destination.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PayRequest {
+    pub destination: String,
+    pub amount: Option<String>,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+    pub memo: Option<String>,
+    pub memo_type: Option<String>,
+    pub callback: Option<String>,
+    pub msg: Option<String>,
+    pub network_passphrase: Option<String>,
+    pub origin_domain: Option<String>,
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sep7Uri {
+    Tx(TransactionRequest),
+    Pay(PayRequest),
+}
+
+// Define a trait for Sep7Uri behavior
+pub trait Sep7UriBehavior {
+    fn new_tx(tx: &Transaction) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn new_pay(destination: &str) -> Self
+    where
+        Self: Sized;
+    fn parse(uri: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn to_uri(&self) -> String;
+    fn transaction(&self) -> Result<Transaction, String>;
+    fn network_passphrase(&self) -> String;
+    fn sign(&mut self, keypair: &Keypair) -> Result<&mut Self, String>;
+    fn verify_signature(uri: &str, signing_key: &str) -> bool;
+}
+
+impl Sep7UriBehavior for Sep7Uri {
+    /// Creates a `tx` request for the envelope of `tx`.
+    fn new_tx(tx: &Transaction) -> Result<Self, String> {
+        let envelope = tx.to_envelope().map_err(|e| e.to_string())?;
+        let xdr = xdr::WriteXdr::to_xdr_base64(&envelope, xdr::Limits::none())
+            .map_err(|e| e.to_string())?;
+        let network_passphrase =
+            (tx.network_passphrase != Networks::public()).then(|| tx.network_passphrase.clone());
+
+        Ok(Self::Tx(TransactionRequest {
+            xdr,
+            network_passphrase,
+            ..Default::default()
+        }))
+    }
+
+    /// Creates a `pay` request to `destination`, the other parameters are left to the wallet.
+    fn new_pay(destination: &str) -> Self {
+        Self::Pay(PayRequest {
+            destination: destination.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn parse(uri: &str) -> Result<Self, String> {
+        let rest = uri
+            .strip_prefix(SCHEME)
+            .ok_or("uri must start with web+stellar:")?;
+        let (operation, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let mut params = Vec::new();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            params.push((decode(key)?, decode(value)?));
+        }
+        let get = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        };
+
+        let msg = get("msg");
+        if msg
+            .as_ref()
+            .is_some_and(|msg| msg.chars().count() > MAX_MSG_LENGTH)
+        {
+            return Err(format!(
+                "msg cannot be longer than {} characters",
+                MAX_MSG_LENGTH
+            ));
+        }
+        if let Some(callback) = get("callback") {
+            if !callback.starts_with("url:") {
+                return Err("callback must start with url:".to_string());
+            }
+        }
+
+        match operation {
+            "tx" => {
+                let xdr = get("xdr").ok_or("xdr is required for the tx operation")?;
+                let replace = match get("replace") {
+                    Some(replace) => parse_replacements(&replace)?,
+                    None => Vec::new(),
+                };
+
+                Ok(Self::Tx(TransactionRequest {
+                    xdr,
+                    replace,
+                    callback: get("callback"),
+                    pubkey: get("pubkey"),
+                    chain: get("chain"),
+                    msg,
+                    network_passphrase: get("network_passphrase"),
+                    origin_domain: get("origin_domain"),
+                    signature: get("signature"),
+                }))
+            }
+            "pay" => {
+                let destination =
+                    get("destination").ok_or("destination is required for the pay operation")?;
+                let memo_type = get("memo_type");
+                if memo_type
+                    .as_deref()
+                    .is_some_and(|memo_type| !MEMO_TYPES.contains(&memo_type))
+                {
+                    return Err("memo_type is invalid".to_string());
+                }
+
+                Ok(Self::Pay(PayRequest {
+                    destination,
+                    amount: get("amount"),
+                    asset_code: get("asset_code"),
+                    asset_issuer: get("asset_issuer"),
+                    memo: get("memo"),
+                    memo_type,
+                    callback: get("callback"),
+                    msg,
+                    network_passphrase: get("network_passphrase"),
+                    origin_domain: get("origin_domain"),
+                    signature: get("signature"),
+                }))
+            }
+            _ => Err(format!("operation {} is not supported", operation)),
+        }
+    }
+
+    /// Serializes the request, with the signature last as required for verification.
+    fn to_uri(&self) -> String {
+        let (operation, params) = match self {
+            Self::Tx(tx) => (
+                "tx",
+                vec![
+                    ("xdr", Some(tx.xdr.clone())),
+                    (
+                        "replace",
+                        (!tx.replace.is_empty()).then(|| format_replacements(&tx.replace)),
+                    ),
+                    ("callback", tx.callback.clone()),
+                    ("pubkey", tx.pubkey.clone()),
+                    ("chain", tx.chain.clone()),
+                    ("msg", tx.msg.clone()),
+                    ("network_passphrase", tx.network_passphrase.clone()),
+                    ("origin_domain", tx.origin_domain.clone()),
+                    ("signature", tx.signature.clone()),
+                ],
+            ),
+            Self::Pay(pay) => (
+                "pay",
+                vec![
+                    ("destination", Some(pay.destination.clone())),
+                    ("amount", pay.amount.clone()),
+                    ("asset_code", pay.asset_code.clone()),
+                    ("asset_issuer", pay.asset_issuer.clone()),
+                    ("memo", pay.memo.clone()),
+                    ("memo_type", pay.memo_type.clone()),
+                    ("callback", pay.callback.clone()),
+                    ("msg", pay.msg.clone()),
+                    ("network_passphrase", pay.network_passphrase.clone()),
+                    ("origin_domain", pay.origin_domain.clone()),
+                    ("signature", pay.signature.clone()),
+                ],
+            ),
+        };
+
+        let query: Vec<String> = params
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| format!("{}={}", key, encode(&value))))
+            .collect();
+        format!("{}{}?{}", SCHEME, operation, query.join("&"))
+    }
+
+    /// Decodes the envelope of a `tx` request on the requested network.
+    fn transaction(&self) -> Result<Transaction, String> {
+        let Self::Tx(request) = self else {
+            return Err("only tx requests carry a transaction".to_string());
+        };
+        match xdr::TransactionEnvelope::from_xdr_base64(&request.xdr, xdr::Limits::none()) {
+            Ok(xdr::TransactionEnvelope::TxV0(_)) | Ok(xdr::TransactionEnvelope::Tx(_)) => {}
+            Ok(_) => return Err("fee bump transactions are not supported".to_string()),
+            Err(_) => return Err("xdr is not a valid transaction envelope".to_string()),
+        }
+
+        Ok(Transaction::from_xdr_envelope(
+            &request.xdr,
+            &self.network_passphrase(),
+        ))
+    }
+
+    /// The network the request is for, the public network when not specified.
+    fn network_passphrase(&self) -> String {
+        let passphrase = match self {
+            Self::Tx(tx) => &tx.network_passphrase,
+            Self::Pay(pay) => &pay.network_passphrase,
+        };
+        passphrase
+            .clone()
+            .unwrap_or_else(|| Networks::public().to_string())
+    }
+
+    /// Signs the request with the `URI_REQUEST_SIGNING_KEY` of its `origin_domain`.
+    fn sign(&mut self, keypair: &Keypair) -> Result<&mut Self, String> {
+        let signature = match self {
+            Self::Tx(tx) => &mut tx.signature,
+            Self::Pay(pay) => &mut pay.signature,
+        };
+        *signature = None;
+
+        let payload = signature_payload(&self.to_uri());
+        let signed = keypair.sign(&payload).map_err(|e| e.to_string())?;
+        let signature = match self {
+            Self::Tx(tx) => &mut tx.signature,
+            Self::Pay(pay) => &mut pay.signature,
+        };
+        *signature = Some(STANDARD.encode(signed));
+        Ok(self)
+    }
+
+    /// Verifies the `signature` parameter of `uri` against `signing_key`, the
+    /// `URI_REQUEST_SIGNING_KEY` published by the origin domain.
+    fn verify_signature(uri: &str, signing_key: &str) -> bool {
+        let Some(start) = uri.find("&signature=") else {
+            return false;
+        };
+        let value = &uri[start + "&signature=".len()..];
+        let value = &value[..value.find('&').unwrap_or(value.len())];
+        let unsigned = format!(
+            "{}{}",
+            &uri[..start],
+            &uri[start + "&signature=".len() + value.len()..]
+        );
+
+        let Ok(signature) = decode(value).and_then(|value| {
+            STANDARD
+                .decode(value)
+                .map_err(|_| "signature is not base64".to_string())
+        }) else {
+            return false;
+        };
+        let Ok(keypair) = Keypair::from_public_key(signing_key) else {
+            return false;
+        };
+
+        keypair.verify(&signature_payload(&unsigned), &signature)
+    }
+}
+
+impl std::fmt::Display for Sep7Uri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_uri())
+    }
+}
+
+// 35 zero bytes and the signature type, followed by the prefixed URI
+fn signature_payload(uri: &str) -> Vec<u8> {
+    let mut payload = vec![0u8; 36];
+    payload[35] = 4;
+    payload.extend_from_slice(SIGNATURE_PREFIX.as_bytes());
+    payload.extend_from_slice(uri.as_bytes());
+    payload
+}
+
+// `replace` is `path:id,...;id:hint,...`
+fn parse_replacements(replace: &str) -> Result<Vec<Replacement>, String> {
+    let (fields, hints) = replace.split_once(';').unwrap_or((replace, ""));
+    let hints: Vec<(&str, &str)> = hints
+        .split(',')
+        .filter(|hint| !hint.is_empty())
+        .map(|hint| hint.split_once(':').unwrap_or((hint, "")))
+        .collect();
+
+    fields
+        .split(',')
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            let (path, id) = field
+                .split_once(':')
+                .ok_or_else(|| format!("replace field {} has no reference", field))?;
+            let hint = hints
+                .iter()
+                .find(|(hint_id, _)| *hint_id == id)
+                .map_or("", |(_, hint)| hint);
+            Ok(Replacement {
+                path: path.to_string(),
+                id: id.to_string(),
+                hint: hint.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn format_replacements(replace: &[Replacement]) -> String {
+    let fields: Vec<String> = replace
+        .iter()
+        .map(|r| format!("{}:{}", r.path, r.id))
+        .collect();
+    let mut hints: Vec<String> = Vec::new();
+    for r in replace {
+        let hint = format!("{}:{}", r.id, r.hint);
+        if !hints.contains(&hint) {
+            hints.push(hint);
+        }
+    }
+    format!("{};{}", fields.join(","), hints.join(","))
+}
+
+// Percent-encodes like JavaScript's `encodeURIComponent`, which the reference implementations
+// use; signatures are computed over the encoded URI, so the bytes must match
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'!'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn decode(value: &str) -> Result<String, String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = value.get(i + 1..i + 3).ok_or("invalid percent encoding")?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| "invalid percent encoding")?);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            b => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| "uri is not valid utf-8".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, AccountBehavior};
+    use crate::operation::Operation;
+    use crate::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+
+    const DESTINATION: &str = "GCALNQQBXAPZ2WIRSDDBMSTAKCUH5SG6U76YBFLQLIXJTF7FE5AX7AOO";
+    const SIGNING_SECRET: &str = "SBPOVRVKTTV7W3IOX2FJPSMPCJ5L2WU2YKTP3HCLYPXNI5MDIGREVNYC";
+
+    fn transaction() -> Transaction {
+        let mut account = Account::new(DESTINATION, "1").unwrap();
+        TransactionBuilder::new(&mut account, Networks::testnet(), None)
+            .fee(100u32)
+            .add_operation(Operation::new().bump_sequence(10).unwrap())
+            .build()
+    }
+
+    #[test]
+    fn test_parses_pay_uri() {
+        let uri = "web+stellar:pay?destination=GCALNQQBXAPZ2WIRSDDBMSTAKCUH5SG6U76YBFLQLIXJTF7FE5AX7AOO&amount=120.1234567&memo=skdjfasf&memo_type=MEMO_TEXT&msg=pay%20me%20with%20lumens";
+        let parsed = Sep7Uri::parse(uri).unwrap();
+
+        assert_eq!(
+            parsed,
+            Sep7Uri::Pay(PayRequest {
+                destination: DESTINATION.to_string(),
+                amount: Some("120.1234567".to_string()),
+                memo: Some("skdjfasf".to_string()),
+                memo_type: Some("MEMO_TEXT".to_string()),
+                msg: Some("pay me with lumens".to_string()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(parsed.to_uri(), uri);
+        assert_eq!(parsed.network_passphrase(), Networks::public());
+    }
+
+    #[test]
+    fn test_parses_tx_uri_with_replacements() {
+        let uri = "web+stellar:tx?xdr=AAAA&replace=sourceAccount%3AX%2Coperations%5B0%5D.sourceAccount%3AY%2Coperations%5B1%5D.destination%3AY%3BX%3Aaccount%20on%20which%20to%20create%20the%20trustline%2CY%3Aaccount%20that%20needs%20the%20trustline&callback=url%3Ahttps%3A%2F%2FsomeSigningService.com&network_passphrase=Test%20SDF%20Network%20%3B%20September%202015";
+        let parsed = Sep7Uri::parse(uri).unwrap();
+        let Sep7Uri::Tx(request) = &parsed else {
+            panic!("expected a tx request");
+        };
+
+        assert_eq!(request.xdr, "AAAA");
+        assert_eq!(
+            request.callback.as_deref(),
+            Some("url:https://someSigningService.com")
+        );
+        assert_eq!(request.replace.len(), 3);
+        assert_eq!(
+            request.replace[1],
+            Replacement {
+                path: "operations[0].sourceAccount".to_string(),
+                id: "Y".to_string(),
+                hint: "account that needs the trustline".to_string(),
+            }
+        );
+        assert_eq!(parsed.network_passphrase(), Networks::testnet());
+        assert_eq!(parsed.to_uri(), uri);
+    }
+
+    #[test]
+    fn test_tx_request_round_trips_transaction() {
+        let tx = transaction();
+        let uri = Sep7Uri::new_tx(&tx).unwrap().to_uri();
+
+        let decoded = Sep7Uri::parse(&uri).unwrap().transaction().unwrap();
+        assert_eq!(decoded.network_passphrase, Networks::testnet());
+        assert_eq!(decoded.hash(), tx.hash());
+
+        assert_eq!(
+            Sep7Uri::new_pay(DESTINATION).transaction().unwrap_err(),
+            "only tx requests carry a transaction"
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_uris() {
+        assert!(Sep7Uri::parse("https://example.com").is_err());
+        assert_eq!(
+            Sep7Uri::parse("web+stellar:sign?xdr=AAAA").unwrap_err(),
+            "operation sign is not supported"
+        );
+        assert_eq!(
+            Sep7Uri::parse("web+stellar:tx?callback=url%3Ahttps").unwrap_err(),
+            "xdr is required for the tx operation"
+        );
+        assert_eq!(
+            Sep7Uri::parse("web+stellar:tx?xdr=AAAA&callback=https%3A%2F%2Fexample.com")
+                .unwrap_err(),
+            "callback must start with url:"
+        );
+        assert_eq!(
+            Sep7Uri::parse(&format!(
+                "web+stellar:pay?destination={}&memo_type=MEMO_BYTES",
+                DESTINATION
+            ))
+            .unwrap_err(),
+            "memo_type is invalid"
+        );
+        assert!(Sep7Uri::parse(&format!(
+            "web+stellar:pay?destination={}&msg={}",
+            DESTINATION,
+            "a".repeat(301)
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_signs_and_verifies() {
+        let keypair = Keypair::from_secret(SIGNING_SECRET).unwrap();
+        let mut uri = Sep7Uri::new_tx(&transaction()).unwrap();
+        if let Sep7Uri::Tx(request) = &mut uri {
+            request.callback = Some("url:https://someSigningService.com".to_string());
+            request.origin_domain = Some("someDomain.com".to_string());
+        }
+        let signed = uri.sign(&keypair).unwrap().to_uri();
+
+        assert!(signed.contains("&signature="));
+        assert!(Sep7Uri::verify_signature(&signed, &keypair.public_key()));
+        assert!(!Sep7Uri::verify_signature(&signed, DESTINATION));
+        assert!(!Sep7Uri::verify_signature(
+            &signed.replace("someDomain.com", "otherDomain.com"),
+            &keypair.public_key()
+        ));
+
+        // signing again replaces the previous signature
+        let resigned = Sep7Uri::parse(&signed)
+            .unwrap()
+            .sign(&keypair)
+            .unwrap()
+            .to_uri();
+        assert_eq!(resigned, signed);
+    }
+
+    #[test]
+    fn test_verifies_sep7_example_signatures() {
+        let keypair = Keypair::from_secret(SIGNING_SECRET).unwrap();
+
+        // the example of the SEP-7 request signing section
+        let uri = format!(
+            "web+stellar:pay?destination={}&amount=120.1234567&memo=skdjfasf&memo_type=MEMO_TEXT&msg=pay%20me%20with%20lumens&origin_domain=someDomain.com",
+            DESTINATION
+        );
+        let signature = "tbsLtlK%2FfouvRWk2UWFP47yHYeI1g1NEC%2FfEQvuXG6V8P%2BbeLxplYbOVtTk1g94Wp97cHZ3pVJy%2FtZNYobl3Cw%3D%3D";
+        let signed = Sep7Uri::parse(&uri)
+            .unwrap()
+            .sign(&keypair)
+            .unwrap()
+            .to_uri();
+        assert_eq!(signed, format!("{}&signature={}", uri, signature));
+        assert!(Sep7Uri::verify_signature(&signed, &keypair.public_key()));
+
+        // the same request without memo_type, as in the JS SDK tests
+        let uri = uri.replace("&memo_type=MEMO_TEXT", "");
+        let signed = format!(
+            "{}&signature={}",
+            uri,
+            "JTlGMGzxUv90P2SWxUY9xo%2BLlbXaDloend6gkpyylY8X4bUNf6%2F9mFTMJs7JKqSDPRtejlK1kQvrsJfRZSJeAQ%3D%3D"
+        );
+        assert_eq!(
+            Sep7Uri::parse(&uri)
+                .unwrap()
+                .sign(&keypair)
+                .unwrap()
+                .to_uri(),
+            signed
+        );
+        assert!(Sep7Uri::verify_signature(&signed, &keypair.public_key()));
+    }
+
+    #[test]
+    fn test_encodes_like_encode_uri_component() {
+        assert_eq!(
+            encode("a b+c/d?e=f&g:h~i!j*k'l(m)n,o;p@q#r$s"),
+            "a%20b%2Bc%2Fd%3Fe%3Df%26g%3Ah~i!j*k'l(m)n%2Co%3Bp%40q%23r%24s"
+        );
+        assert_eq!(encode("pay me with lumens"), "pay%20me%20with%20lumens");
+        assert_eq!(encode("三"), "%E4%B8%89");
+        assert_eq!(decode(&encode("a b+c (d)!")).unwrap(), "a b+c (d)!");
+    }
+}
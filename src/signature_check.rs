@@ -0,0 +1,422 @@
+//! Offline check of whether a signed transaction meets the thresholds of its source accounts.
+use crate::hashing::{HashingBehavior, Sha256Hasher};
+use crate::keypair::{Keypair, KeypairBehavior};
+use crate::transaction::{Transaction, TransactionBehavior};
+use crate::xdr;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThresholdLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// The signers and thresholds of an account at the time of the check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSigners {
+    pub account_id: String,
+    pub master_weight: u8,
+    pub low_threshold: u8,
+    pub med_threshold: u8,
+    pub high_threshold: u8,
+    pub signers: Vec<xdr::Signer>,
+}
+
+/// Signature weight collected for one account against the threshold it needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountAuthorization {
+    pub account_id: String,
+    pub level: ThresholdLevel,
+    pub threshold: u32,
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationReport {
+    pub accounts: Vec<AccountAuthorization>,
+}
+
+// Define a trait for AccountSigners behavior
+pub trait AccountSignersBehavior {
+    fn from_account_entry(entry: &xdr::AccountEntry) -> Self;
+    fn threshold(&self, level: ThresholdLevel) -> u32;
+}
+
+// Define a trait for AccountAuthorization behavior
+pub trait AccountAuthorizationBehavior {
+    fn missing_weight(&self) -> u32;
+    fn is_authorized(&self) -> bool;
+}
+
+// Define a trait for AuthorizationReport behavior
+pub trait AuthorizationReportBehavior {
+    fn is_authorized(&self) -> bool;
+    fn missing(&self) -> Vec<(String, u32)>;
+}
+
+// Define a trait for SignatureCheck behavior
+pub trait SignatureCheckBehavior {
+    fn check_signatures(&self, accounts: &[AccountSigners]) -> Result<AuthorizationReport, String>;
+}
+
+/// Returns the threshold level the source account of `op` must meet.
+pub fn operation_threshold(op: &xdr::Operation) -> ThresholdLevel {
+    match &op.body {
+        xdr::OperationBody::AllowTrust(_)
+        | xdr::OperationBody::SetTrustLineFlags(_)
+        | xdr::OperationBody::BumpSequence(_)
+        | xdr::OperationBody::ClaimClaimableBalance(_)
+        | xdr::OperationBody::Inflation
+        | xdr::OperationBody::ExtendFootprintTtl(_)
+        | xdr::OperationBody::RestoreFootprint(_) => ThresholdLevel::Low,
+        xdr::OperationBody::AccountMerge(_) => ThresholdLevel::High,
+        xdr::OperationBody::SetOptions(op)
+            if op.master_weight.is_some()
+                || op.low_threshold.is_some()
+                || op.med_threshold.is_some()
+                || op.high_threshold.is_some()
+                || op.signer.is_some() =>
+        {
+            ThresholdLevel::High
+        }
+        _ => ThresholdLevel::Medium,
+    }
+}
+
+fn account_id(account: &xdr::MuxedAccount) -> String {
+    let key = match account {
+        xdr::MuxedAccount::Ed25519(key) => key.0,
+        xdr::MuxedAccount::MuxedEd25519(muxed) => muxed.ed25519.0,
+    };
+    stellar_strkey::ed25519::PublicKey(key).to_string()
+}
+
+fn hint(key: &[u8]) -> [u8; 4] {
+    key[key.len() - 4..].try_into().unwrap()
+}
+
+// Whether `signature` satisfies `signer` for a transaction with hash `tx_hash`
+fn satisfies(signer: &xdr::SignerKey, signature: &xdr::DecoratedSignature, tx_hash: &[u8]) -> bool {
+    let verify = |key: &[u8; 32], data: &[u8]| {
+        Keypair::new_from_public_key(key.to_vec())
+            .map(|kp| kp.verify(data, &signature.signature.0))
+            .unwrap_or(false)
+    };
+    match signer {
+        xdr::SignerKey::Ed25519(key) => signature.hint.0 == hint(&key.0) && verify(&key.0, tx_hash),
+        xdr::SignerKey::PreAuthTx(_) => false,
+        xdr::SignerKey::HashX(x) => {
+            signature.hint.0 == hint(&x.0) && Sha256Hasher::hash(&signature.signature.0) == x.0
+        }
+        xdr::SignerKey::Ed25519SignedPayload(signed) => {
+            let mut payload_hint = [0u8; 4];
+            let tail = &signed.payload[signed.payload.len().saturating_sub(4)..];
+            payload_hint[..tail.len()].copy_from_slice(tail);
+            let key_hint = hint(&signed.ed25519.0);
+            let expected: Vec<u8> = key_hint
+                .iter()
+                .zip(payload_hint)
+                .map(|(a, b)| a ^ b)
+                .collect();
+            signature.hint.0 == expected.as_slice() && verify(&signed.ed25519.0, &signed.payload)
+        }
+    }
+}
+
+impl AccountSignersBehavior for AccountSigners {
+    fn from_account_entry(entry: &xdr::AccountEntry) -> Self {
+        let [master_weight, low_threshold, med_threshold, high_threshold] = entry.thresholds.0;
+        Self {
+            account_id: entry.account_id.to_string(),
+            master_weight,
+            low_threshold,
+            med_threshold,
+            high_threshold,
+            signers: entry.signers.to_vec(),
+        }
+    }
+
+    fn threshold(&self, level: ThresholdLevel) -> u32 {
+        u32::from(match level {
+            ThresholdLevel::Low => self.low_threshold,
+            ThresholdLevel::Medium => self.med_threshold,
+            ThresholdLevel::High => self.high_threshold,
+        })
+    }
+}
+
+impl AccountAuthorizationBehavior for AccountAuthorization {
+    /// The weight still needed. Even a zero threshold needs one valid signature.
+    fn missing_weight(&self) -> u32 {
+        self.threshold.max(1).saturating_sub(self.weight)
+    }
+
+    fn is_authorized(&self) -> bool {
+        self.missing_weight() == 0
+    }
+}
+
+impl AuthorizationReportBehavior for AuthorizationReport {
+    fn is_authorized(&self) -> bool {
+        self.accounts.iter().all(|account| account.is_authorized())
+    }
+
+    /// Lists the accounts still short of their threshold and by how much.
+    fn missing(&self) -> Vec<(String, u32)> {
+        self.accounts
+            .iter()
+            .filter(|account| !account.is_authorized())
+            .map(|account| (account.account_id.clone(), account.missing_weight()))
+            .collect()
+    }
+}
+
+impl SignatureCheckBehavior for Transaction {
+    /// Sums the weight of the signers of each source account that signed the transaction.
+    ///
+    /// The transaction source needs at least the low threshold, and every operation source
+    /// the level of its operation. Pre-authorized transaction signers count when their hash
+    /// matches the transaction. Every source account must be in `accounts`.
+    fn check_signatures(&self, accounts: &[AccountSigners]) -> Result<AuthorizationReport, String> {
+        let source = self.source.as_deref().ok_or("transaction has no source")?;
+        let source = xdr::MuxedAccount::from_str(source).map_err(|_| "invalid source")?;
+        let mut levels = vec![(account_id(&source), ThresholdLevel::Low)];
+        for op in self.operations.iter().flatten() {
+            let op_source = op.source_account.as_ref().unwrap_or(&source);
+            let op_source = account_id(op_source);
+            let level = operation_threshold(op);
+            match levels.iter_mut().find(|(account, _)| account == &op_source) {
+                Some((_, current)) => *current = (*current).max(level),
+                None => levels.push((op_source, level)),
+            }
+        }
+
+        let tx_hash = self.hash();
+        let mut report = AuthorizationReport { accounts: vec![] };
+        for (account_id, level) in levels {
+            let signers = accounts
+                .iter()
+                .find(|account| account.account_id == account_id)
+                .ok_or_else(|| format!("no signers for account {}", account_id))?;
+
+            let master = stellar_strkey::ed25519::PublicKey::from_string(&account_id)
+                .map_err(|_| format!("invalid account {}", account_id))?;
+            let mut candidates = vec![(
+                xdr::SignerKey::Ed25519(xdr::Uint256(master.0)),
+                u32::from(signers.master_weight),
+            )];
+            candidates.extend(signers.signers.iter().map(|s| (s.key.clone(), s.weight)));
+
+            let weight = candidates
+                .iter()
+                .filter(|(key, weight)| {
+                    *weight > 0
+                        && match key {
+                            xdr::SignerKey::PreAuthTx(hash) => hash.0 == tx_hash,
+                            _ => self
+                                .signatures
+                                .iter()
+                                .any(|signature| satisfies(key, signature, &tx_hash)),
+                        }
+                })
+                .map(|(_, weight)| (*weight).min(255))
+                .sum();
+
+            report.accounts.push(AccountAuthorization {
+                account_id,
+                level,
+                threshold: signers.threshold(level),
+                weight,
+            });
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, AccountBehavior};
+    use crate::network::{NetworkPassphrase, Networks};
+    use crate::operation::Operation;
+    use crate::signer_key::{SignerKey, SignerKeyBehavior};
+    use crate::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+
+    fn signers(
+        keypair: &Keypair,
+        thresholds: [u8; 4],
+        extra: Vec<(xdr::SignerKey, u32)>,
+    ) -> AccountSigners {
+        AccountSigners {
+            account_id: keypair.public_key(),
+            master_weight: thresholds[0],
+            low_threshold: thresholds[1],
+            med_threshold: thresholds[2],
+            high_threshold: thresholds[3],
+            signers: extra
+                .into_iter()
+                .map(|(key, weight)| xdr::Signer { key, weight })
+                .collect(),
+        }
+    }
+
+    fn ed25519(keypair: &Keypair) -> xdr::SignerKey {
+        SignerKey::decode_address(&keypair.public_key())
+    }
+
+    fn transaction(source: &Keypair, operations: Vec<xdr::Operation>) -> Transaction {
+        let mut account = Account::new(&source.public_key(), "1").unwrap();
+        let mut builder = TransactionBuilder::new(&mut account, Networks::testnet(), None);
+        builder.fee(100_u32);
+        for op in operations {
+            builder.add_operation(op);
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_operation_thresholds() {
+        let op = Operation::new();
+        assert_eq!(
+            operation_threshold(&op.bump_sequence(1).unwrap()),
+            ThresholdLevel::Low
+        );
+        assert_eq!(
+            operation_threshold(&op.manage_data("a", Some(&b"b".to_vec())).unwrap()),
+            ThresholdLevel::Medium
+        );
+        assert_eq!(
+            operation_threshold(
+                &op.set_options(
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some("x.com"),
+                    None
+                )
+                .unwrap()
+            ),
+            ThresholdLevel::Medium
+        );
+        assert_eq!(
+            operation_threshold(
+                &op.set_options(None, None, None, Some(1), None, None, None, None, None)
+                    .unwrap()
+            ),
+            ThresholdLevel::High
+        );
+    }
+
+    #[test]
+    fn test_multisig_weights() {
+        let source = Keypair::random().unwrap();
+        let cosigner = Keypair::random().unwrap();
+        let stranger = Keypair::random().unwrap();
+        let accounts = [signers(
+            &source,
+            [1, 1, 2, 3],
+            vec![(ed25519(&cosigner), 1)],
+        )];
+        let mut tx = transaction(
+            &source,
+            vec![Operation::new().manage_data("a", None).unwrap()],
+        );
+
+        tx.sign(std::slice::from_ref(&source));
+        let report = tx.check_signatures(&accounts).unwrap();
+        assert_eq!(
+            report.accounts,
+            vec![AccountAuthorization {
+                account_id: source.public_key(),
+                level: ThresholdLevel::Medium,
+                threshold: 2,
+                weight: 1,
+            }]
+        );
+        assert_eq!(report.missing(), vec![(source.public_key(), 1)]);
+
+        tx.sign(&[stranger, cosigner]);
+        let report = tx.check_signatures(&accounts).unwrap();
+        assert!(report.is_authorized());
+        assert_eq!(report.accounts[0].weight, 2);
+    }
+
+    #[test]
+    fn test_operation_sources_need_their_own_signatures() {
+        let source = Keypair::random().unwrap();
+        let other = Keypair::random().unwrap();
+        let merge = Operation::with_source(&other.public_key())
+            .unwrap()
+            .account_merge(&source.public_key())
+            .unwrap();
+        let mut tx = transaction(&source, vec![merge]);
+        tx.sign(std::slice::from_ref(&source));
+        let accounts = [
+            signers(&source, [1, 0, 0, 0], vec![]),
+            signers(&other, [5, 1, 2, 3], vec![]),
+        ];
+
+        let report = tx.check_signatures(&accounts).unwrap();
+        assert!(!report.is_authorized());
+        assert_eq!(report.missing(), vec![(other.public_key(), 3)]);
+        assert_eq!(report.accounts[1].level, ThresholdLevel::High);
+
+        tx.sign(&[other]);
+        assert!(tx.check_signatures(&accounts).unwrap().is_authorized());
+
+        assert_eq!(
+            tx.check_signatures(&accounts[..1]).unwrap_err(),
+            format!("no signers for account {}", accounts[1].account_id)
+        );
+    }
+
+    #[test]
+    fn test_pre_auth_hash_x_and_signed_payload_signers() {
+        let source = Keypair::random().unwrap();
+        let payload_signer = Keypair::random().unwrap();
+        let mut tx = transaction(&source, vec![Operation::new().bump_sequence(9).unwrap()]);
+        let payload = vec![1, 2, 3, 4, 5];
+        let signed_payload =
+            xdr::SignerKey::Ed25519SignedPayload(xdr::SignerKeyEd25519SignedPayload {
+                ed25519: xdr::Uint256(payload_signer.raw_public_key().clone().try_into().unwrap()),
+                payload: payload.clone().try_into().unwrap(),
+            });
+
+        let pre_auth = signers(&source, [0, 1, 1, 1], vec![(tx.pre_auth_signer_key(), 1)]);
+        assert!(tx.check_signatures(&[pre_auth]).unwrap().is_authorized());
+
+        let hash_x = signers(&source, [0, 1, 1, 1], vec![(SignerKey::hash_x(b"open"), 1)]);
+        tx.sign_hashx(b"wrong").unwrap();
+        assert!(!tx
+            .check_signatures(std::slice::from_ref(&hash_x))
+            .unwrap()
+            .is_authorized());
+        tx.sign_hashx(b"open").unwrap();
+        assert!(tx.check_signatures(&[hash_x]).unwrap().is_authorized());
+
+        let payload_account = signers(&source, [0, 1, 1, 1], vec![(signed_payload, 1)]);
+        tx.signatures
+            .push(payload_signer.sign_payload_decorated(&payload));
+        assert!(tx
+            .check_signatures(&[payload_account])
+            .unwrap()
+            .is_authorized());
+    }
+
+    #[test]
+    fn test_zero_threshold_still_needs_a_signature() {
+        let source = Keypair::random().unwrap();
+        let tx = transaction(&source, vec![Operation::new().bump_sequence(9).unwrap()]);
+        let report = tx
+            .check_signatures(&[signers(&source, [1, 0, 0, 0], vec![])])
+            .unwrap();
+
+        assert_eq!(report.missing(), vec![(source.public_key(), 1)]);
+    }
+}
@@ -1,6 +1,7 @@
 use core::panic;
 use std::{collections::HashMap, str::FromStr};
 
+use crate::hashing::{HashingBehavior, Sha256Hasher};
 use crate::xdr;
 use crate::xdr::{SignerKey as XDRSignerKey, SignerKeyEd25519SignedPayload};
 use stellar_strkey::{
@@ -14,6 +15,7 @@ pub struct SignerKey;
 pub trait SignerKeyBehavior {
     fn decode_address(address: &str) -> XDRSignerKey;
     fn encode_signer_key(signer_key: &XDRSignerKey) -> String;
+    fn hash_x(preimage: &[u8]) -> XDRSignerKey;
 }
 
 impl SignerKeyBehavior for SignerKey {
@@ -56,6 +58,11 @@ impl SignerKeyBehavior for SignerKey {
             }
         }
     }
+
+    /// Creates a hash(x) signer satisfied by revealing `preimage`.
+    fn hash_x(preimage: &[u8]) -> XDRSignerKey {
+        XDRSignerKey::HashX(xdr::Uint256(Sha256Hasher::hash(preimage)))
+    }
 }
 
 fn assert_panic<F: FnOnce(), S: AsRef<str>>(f: F, expected_msg: S) {
@@ -137,6 +144,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn creates_hash_x_signer_from_preimage() {
+        let preimage = b"secret for the swap";
+        let key = SignerKey::hash_x(preimage);
+
+        assert_eq!(
+            key,
+            XDRSignerKey::HashX(xdr::Uint256(Sha256Hasher::hash(preimage)))
+        );
+        assert!(SignerKey::encode_signer_key(&key).starts_with('X'));
+    }
+
     #[test]
     fn error_cases_for_invalid_strkey() {
         let strkey = "G47QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJVP2I";
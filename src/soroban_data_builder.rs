@@ -34,8 +34,124 @@ pub trait SorobanDataBuilderBehavior {
     fn get_read_write(&self) -> Vec<xdr::LedgerKey>;
     fn build(&self) -> xdr::SorobanTransactionData;
     fn get_footprint(&self) -> &xdr::LedgerFootprint;
+    fn try_new(
+        soroban_data: Option<Either<String, xdr::SorobanTransactionData>>,
+    ) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn try_from_xdr(data: Either<String, Vec<u8>>) -> Result<xdr::SorobanTransactionData, String>;
+    fn merge(&mut self, other: &xdr::SorobanTransactionData) -> &mut Self;
+    fn dedupe(&mut self) -> &mut Self;
+    fn validate(&self, limits: &SorobanLimits) -> Result<(), String>;
+    fn get_archived_entries(&self) -> Vec<u32>;
+    fn set_archived_entries(&mut self, indices: Vec<u32>) -> Result<&mut Self, String>;
+    fn mark_archived(&mut self, key: &xdr::LedgerKey) -> Result<&mut Self, String>;
+}
+
+/// Per-transaction Soroban resource limits of a network.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SorobanLimits {
+    pub tx_max_instructions: i64,
+    pub tx_max_footprint_entries: u32,
+    pub tx_max_disk_read_entries: u32,
+    pub tx_max_disk_read_bytes: u32,
+    pub tx_max_write_ledger_entries: u32,
+    pub tx_max_write_bytes: u32,
+}
+
+// Define a trait for SorobanLimits behavior
+pub trait SorobanLimitsBehavior {
+    fn from_config_settings(settings: &[xdr::ConfigSettingEntry]) -> Result<Self, String>
+    where
+        Self: Sized;
+}
+
+impl SorobanLimitsBehavior for SorobanLimits {
+    fn from_config_settings(settings: &[xdr::ConfigSettingEntry]) -> Result<Self, String> {
+        let mut limits = Self::default();
+        let (mut compute, mut ledger_cost, mut ledger_cost_ext) = (false, false, false);
+        for setting in settings {
+            match setting {
+                xdr::ConfigSettingEntry::ContractComputeV0(s) => {
+                    limits.tx_max_instructions = s.tx_max_instructions;
+                    compute = true;
+                }
+                xdr::ConfigSettingEntry::ContractLedgerCostV0(s) => {
+                    limits.tx_max_disk_read_entries = s.tx_max_disk_read_entries;
+                    limits.tx_max_disk_read_bytes = s.tx_max_disk_read_bytes;
+                    limits.tx_max_write_ledger_entries = s.tx_max_write_ledger_entries;
+                    limits.tx_max_write_bytes = s.tx_max_write_bytes;
+                    ledger_cost = true;
+                }
+                xdr::ConfigSettingEntry::ContractLedgerCostExtV0(s) => {
+                    limits.tx_max_footprint_entries = s.tx_max_footprint_entries;
+                    ledger_cost_ext = true;
+                }
+                _ => {}
+            }
+        }
+        for (found, name) in [
+            (compute, "ContractComputeV0"),
+            (ledger_cost, "ContractLedgerCostV0"),
+            (ledger_cost_ext, "ContractLedgerCostExtV0"),
+        ] {
+            if !found {
+                return Err(format!("missing config setting: {}", name));
+            }
+        }
+        Ok(limits)
+    }
+}
+
+fn is_persistent_soroban_key(key: &xdr::LedgerKey) -> bool {
+    match key {
+        xdr::LedgerKey::ContractData(data) => {
+            data.durability == xdr::ContractDataDurability::Persistent
+        }
+        xdr::LedgerKey::ContractCode(_) => true,
+        _ => false,
+    }
+}
+
+fn has_duplicates(keys: &[xdr::LedgerKey]) -> bool {
+    keys.iter()
+        .enumerate()
+        .any(|(i, key)| keys[..i].contains(key))
+}
+
+impl SorobanDataBuilder {
+    fn archived_keys(&self) -> Vec<xdr::LedgerKey> {
+        let read_write = &self.data.resources.footprint.read_write;
+        self.get_archived_entries()
+            .into_iter()
+            .filter_map(|index| read_write.get(index as usize).cloned())
+            .collect()
+    }
+
+    /// Points the archived-entry indices at `keys` in the current read-write footprint.
+    fn set_archived_keys(&mut self, keys: &[xdr::LedgerKey]) {
+        let indices: Vec<u32> = self
+            .data
+            .resources
+            .footprint
+            .read_write
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| keys.contains(key))
+            .map(|(index, _)| index as u32)
+            .collect();
+        self.data.ext = if indices.is_empty() {
+            xdr::SorobanTransactionDataExt::V0
+        } else {
+            xdr::SorobanTransactionDataExt::V1(xdr::SorobanResourcesExtV0 {
+                archived_soroban_entries: indices.try_into().unwrap(),
+            })
+        };
+    }
 }
 impl SorobanDataBuilderBehavior for SorobanDataBuilder {
+    /// Creates a builder, panicking on invalid encoded data. Prefer `try_new`, which returns
+    /// an error instead.
     fn new(soroban_data: Option<Either<String, xdr::SorobanTransactionData>>) -> Self {
         let data = match soroban_data {
             Some(Either::Left(encoded_data)) => {
@@ -81,17 +197,13 @@ impl SorobanDataBuilderBehavior for SorobanDataBuilder {
         Self { data }
     }
 
+    /// Decodes Soroban data, panicking on invalid XDR. Prefer `try_from_xdr`, which returns an
+    /// error instead.
     fn from_xdr(data: Either<String, Vec<u8>>) -> xdr::SorobanTransactionData {
-        match data {
-            Either::Left(encoded) => {
-                xdr::SorobanTransactionData::from_xdr_base64(encoded, xdr::Limits::none()).unwrap()
-            }
-            Either::Right(raw) => {
-                xdr::SorobanTransactionData::from_xdr(raw, xdr::Limits::none()).unwrap()
-            }
-        }
+        Self::try_from_xdr(data).unwrap()
     }
 
+    /// Adds keys to the footprint without duplicates. Keys in both lists stay read-write only.
     fn append_footprint(
         &mut self,
         read_only: Vec<xdr::LedgerKey>,
@@ -107,6 +219,7 @@ impl SorobanDataBuilderBehavior for SorobanDataBuilder {
 
         // Set the combined footprints
         self.set_footprint(Some(current_read_only), Some(current_read_write))
+            .dedupe()
     }
 
     fn set_footprint(
@@ -133,8 +246,12 @@ impl SorobanDataBuilderBehavior for SorobanDataBuilder {
         self
     }
 
+    /// Replaces the read-write footprint. Archived-entry indices follow their keys and are
+    /// dropped for keys no longer in the footprint.
     fn set_read_write(&mut self, read_write: Vec<xdr::LedgerKey>) -> &mut Self {
+        let archived = self.archived_keys();
         self.data.resources.footprint.read_write = read_write.try_into().unwrap();
+        self.set_archived_keys(&archived);
         self
     }
 
@@ -164,6 +281,225 @@ impl SorobanDataBuilderBehavior for SorobanDataBuilder {
         self.data.resources.write_bytes = write_bytes;
         self
     }
+    fn try_new(
+        soroban_data: Option<Either<String, xdr::SorobanTransactionData>>,
+    ) -> Result<Self, String> {
+        match soroban_data {
+            Some(Either::Left(encoded)) if !encoded.is_empty() => Ok(Self {
+                data: Self::try_from_xdr(Either::Left(encoded))?,
+            }),
+            Some(Either::Right(data)) => Ok(Self { data }),
+            _ => Ok(Self::new(None)),
+        }
+    }
+
+    fn try_from_xdr(data: Either<String, Vec<u8>>) -> Result<xdr::SorobanTransactionData, String> {
+        match data {
+            Either::Left(encoded) => {
+                xdr::SorobanTransactionData::from_xdr_base64(encoded, xdr::Limits::none())
+            }
+            Either::Right(raw) => xdr::SorobanTransactionData::from_xdr(raw, xdr::Limits::none()),
+        }
+        .map_err(|e| format!("invalid soroban data: {}", e))
+    }
+
+    /// Merges another transaction's footprint, resources and resource fee into this one.
+    /// Resources and fees are added up; the footprint is deduplicated.
+    fn merge(&mut self, other: &xdr::SorobanTransactionData) -> &mut Self {
+        let mut archived = self.archived_keys();
+        archived.extend(
+            SorobanDataBuilder {
+                data: other.clone(),
+            }
+            .archived_keys(),
+        );
+
+        let footprint = &other.resources.footprint;
+        self.append_footprint(footprint.read_only.to_vec(), footprint.read_write.to_vec());
+        let resources = &mut self.data.resources;
+        resources.instructions = resources
+            .instructions
+            .saturating_add(other.resources.instructions);
+        resources.disk_read_bytes = resources
+            .disk_read_bytes
+            .saturating_add(other.resources.disk_read_bytes);
+        resources.write_bytes = resources
+            .write_bytes
+            .saturating_add(other.resources.write_bytes);
+        self.data.resource_fee = self.data.resource_fee.saturating_add(other.resource_fee);
+
+        self.set_archived_keys(&archived);
+        self
+    }
+
+    /// Removes duplicate footprint keys, moving keys found in both lists to read-write.
+    fn dedupe(&mut self) -> &mut Self {
+        let footprint = &self.data.resources.footprint;
+        let mut read_write: Vec<xdr::LedgerKey> = Vec::new();
+        for key in footprint.read_write.iter() {
+            if !read_write.contains(key) {
+                read_write.push(key.clone());
+            }
+        }
+        let mut read_only: Vec<xdr::LedgerKey> = Vec::new();
+        for key in footprint.read_only.iter() {
+            if !read_write.contains(key) && !read_only.contains(key) {
+                read_only.push(key.clone());
+            }
+        }
+
+        self.set_read_only(read_only).set_read_write(read_write)
+    }
+
+    /// Checks that the footprint is well formed and that the resources are within `limits`.
+    /// Only classic entries and archived entries count as disk reads, as in protocol 23.
+    fn validate(&self, limits: &SorobanLimits) -> Result<(), String> {
+        let resources = &self.data.resources;
+        let footprint = &resources.footprint;
+        if has_duplicates(&footprint.read_only) || has_duplicates(&footprint.read_write) {
+            return Err("footprint has duplicate keys".to_string());
+        }
+        if footprint
+            .read_only
+            .iter()
+            .any(|key| footprint.read_write.contains(key))
+        {
+            return Err("footprint key is both read-only and read-write".to_string());
+        }
+        if let Some(key) = footprint
+            .read_only
+            .iter()
+            .chain(footprint.read_write.iter())
+            .find(|key| {
+                !matches!(
+                    key,
+                    xdr::LedgerKey::Account(_)
+                        | xdr::LedgerKey::Trustline(_)
+                        | xdr::LedgerKey::ContractData(_)
+                        | xdr::LedgerKey::ContractCode(_)
+                )
+            })
+        {
+            return Err(format!("invalid footprint key type: {}", key.name()));
+        }
+
+        let archived = self.get_archived_entries();
+        if archived.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("archived entry indices must be strictly increasing".to_string());
+        }
+        for index in &archived {
+            match footprint.read_write.get(*index as usize) {
+                Some(key) if is_persistent_soroban_key(key) => {}
+                Some(_) => {
+                    return Err(format!(
+                        "archived entry {} is not a persistent contract entry",
+                        index
+                    ))
+                }
+                None => return Err(format!("archived entry {} is out of range", index)),
+            }
+        }
+        if self.data.resource_fee < 0 {
+            return Err("resource fee cannot be negative".to_string());
+        }
+
+        let classic_entries = footprint
+            .read_only
+            .iter()
+            .chain(footprint.read_write.iter())
+            .filter(|key| {
+                !matches!(
+                    key,
+                    xdr::LedgerKey::ContractData(_) | xdr::LedgerKey::ContractCode(_)
+                )
+            })
+            .count();
+        let checks = [
+            (
+                "instructions",
+                i64::from(resources.instructions),
+                limits.tx_max_instructions,
+            ),
+            (
+                "footprint entries",
+                (footprint.read_only.len() + footprint.read_write.len()) as i64,
+                limits.tx_max_footprint_entries.into(),
+            ),
+            (
+                "disk read entries",
+                (classic_entries + archived.len()) as i64,
+                limits.tx_max_disk_read_entries.into(),
+            ),
+            (
+                "disk read bytes",
+                resources.disk_read_bytes.into(),
+                limits.tx_max_disk_read_bytes.into(),
+            ),
+            (
+                "write entries",
+                footprint.read_write.len() as i64,
+                limits.tx_max_write_ledger_entries.into(),
+            ),
+            (
+                "write bytes",
+                resources.write_bytes.into(),
+                limits.tx_max_write_bytes.into(),
+            ),
+        ];
+        for (name, value, limit) in checks {
+            if value > limit {
+                return Err(format!("{} {} exceed the limit of {}", name, value, limit));
+            }
+        }
+        Ok(())
+    }
+
+    /// Indices into the read-write footprint of archived entries to restore automatically.
+    fn get_archived_entries(&self) -> Vec<u32> {
+        match &self.data.ext {
+            xdr::SorobanTransactionDataExt::V0 => Vec::new(),
+            xdr::SorobanTransactionDataExt::V1(ext) => ext.archived_soroban_entries.to_vec(),
+        }
+    }
+
+    fn set_archived_entries(&mut self, mut indices: Vec<u32>) -> Result<&mut Self, String> {
+        indices.sort_unstable();
+        indices.dedup();
+        let read_write = &self.data.resources.footprint.read_write;
+        let keys = indices
+            .iter()
+            .map(|index| {
+                read_write
+                    .get(*index as usize)
+                    .filter(|key| is_persistent_soroban_key(key))
+                    .cloned()
+                    .ok_or_else(|| {
+                        format!(
+                            "archived entry {} is not a persistent contract entry in the read-write footprint",
+                            index
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        self.set_archived_keys(&keys);
+        Ok(self)
+    }
+
+    /// Marks a read-write footprint key as archived, so protocol 23 restores it before the
+    /// invocation.
+    fn mark_archived(&mut self, key: &xdr::LedgerKey) -> Result<&mut Self, String> {
+        let index = self
+            .data
+            .resources
+            .footprint
+            .read_write
+            .iter()
+            .position(|k| k == key)
+            .ok_or("archived entries must be in the read-write footprint")?;
+        let mut indices = self.get_archived_entries();
+        indices.push(index as u32);
+        self.set_archived_entries(indices)
+    }
 }
 #[cfg(test)]
 mod tests {
@@ -292,29 +628,26 @@ mod tests {
             .set_footprint(Some(vec![key.clone()]), Some(vec![key.clone()]))
             .append_footprint(vec![key.clone(), key.clone()], vec![]);
 
-        // Test the builder's current state
-        assert_eq!(builder.get_read_only().len(), 3);
-        assert_eq!(builder.get_read_write().len(), 1);
-
-        // Verify read_only contains three copies of the key
-        assert_eq!(builder.get_read_only()[0], key);
-        assert_eq!(builder.get_read_only()[1], key);
-        assert_eq!(builder.get_read_only()[2], key);
-
-        // Verify read_write contains one copy of the key
-        assert_eq!(builder.get_read_write()[0], key);
+        // The key is already read-write, so it is not added to read_only
+        assert!(builder.get_read_only().is_empty());
+        assert_eq!(builder.get_read_write(), vec![key.clone()]);
 
         // Build and verify the final state
         let built = builder.build();
-
-        // Verify the built data has the same footprint structure
-        assert_eq!(built.resources.footprint.read_only.len(), 3);
-        assert_eq!(built.resources.footprint.read_write.len(), 1);
-
-        assert_eq!(built.resources.footprint.read_only[0], key);
-        assert_eq!(built.resources.footprint.read_only[1], key);
-        assert_eq!(built.resources.footprint.read_only[2], key);
-        assert_eq!(built.resources.footprint.read_write[0], key);
+        assert!(built.resources.footprint.read_only.is_empty());
+        assert_eq!(
+            built.resources.footprint.read_write.to_vec(),
+            vec![key.clone()]
+        );
+
+        // Appended duplicates are dropped and keys written later are promoted to read-write
+        let other = persistent(1);
+        let mut builder = SorobanDataBuilder::new(None);
+        builder
+            .append_footprint(vec![key.clone(), key.clone()], vec![])
+            .append_footprint(vec![other.clone()], vec![key.clone()]);
+        assert_eq!(builder.get_read_only(), &vec![other]);
+        assert_eq!(builder.get_read_write(), vec![key]);
     }
 
     #[test]
@@ -335,4 +668,202 @@ mod tests {
         assert_eq!(first.resource_fee, 0); // Default value
         assert_eq!(second.resource_fee, 100); // Modified value
     }
+    fn data_key(byte: u8, durability: xdr::ContractDataDurability) -> xdr::LedgerKey {
+        xdr::LedgerKey::ContractData(xdr::LedgerKeyContractData {
+            contract: xdr::ScAddress::Contract(xdr::ContractId(xdr::Hash([byte; 32]))),
+            key: xdr::ScVal::LedgerKeyContractInstance,
+            durability,
+        })
+    }
+
+    fn persistent(byte: u8) -> xdr::LedgerKey {
+        data_key(byte, xdr::ContractDataDurability::Persistent)
+    }
+
+    fn limits() -> SorobanLimits {
+        SorobanLimits {
+            tx_max_instructions: 100_000_000,
+            tx_max_footprint_entries: 4,
+            tx_max_disk_read_entries: 2,
+            tx_max_disk_read_bytes: 200_000,
+            tx_max_write_ledger_entries: 2,
+            tx_max_write_bytes: 130_000,
+        }
+    }
+
+    #[test]
+    fn test_dedupes_and_promotes_keys() {
+        let mut builder = SorobanDataBuilder::new(None);
+        builder
+            .set_footprint(
+                Some(vec![persistent(1), persistent(2), persistent(1)]),
+                Some(vec![persistent(3), persistent(2), persistent(3)]),
+            )
+            .mark_archived(&persistent(2))
+            .unwrap();
+        assert_eq!(builder.get_archived_entries(), vec![1]);
+        assert!(builder.validate(&limits()).is_err());
+
+        builder.dedupe();
+
+        assert_eq!(builder.get_read_only(), &vec![persistent(1)]);
+        assert_eq!(builder.get_read_write(), vec![persistent(3), persistent(2)]);
+        assert_eq!(builder.get_archived_entries(), vec![1]);
+        assert_eq!(builder.validate(&limits()), Ok(()));
+    }
+
+    #[test]
+    fn test_merges_soroban_data() {
+        let mut first = SorobanDataBuilder::new(None);
+        first
+            .set_footprint(Some(vec![persistent(1)]), Some(vec![persistent(2)]))
+            .set_resources(100, 10, 20)
+            .set_refundable_fee(1000)
+            .mark_archived(&persistent(2))
+            .unwrap();
+        let mut second = SorobanDataBuilder::new(None);
+        second
+            .set_footprint(
+                Some(vec![persistent(2), persistent(4)]),
+                Some(vec![persistent(1), persistent(3)]),
+            )
+            .set_resources(50, 5, 5)
+            .set_refundable_fee(500)
+            .mark_archived(&persistent(3))
+            .unwrap();
+
+        let merged = first.merge(&second.build()).build();
+
+        let footprint = &merged.resources.footprint;
+        assert_eq!(footprint.read_only.to_vec(), vec![persistent(4)]);
+        assert_eq!(
+            footprint.read_write.to_vec(),
+            vec![persistent(2), persistent(1), persistent(3)]
+        );
+        assert_eq!(merged.resources.instructions, 150);
+        assert_eq!(merged.resources.disk_read_bytes, 15);
+        assert_eq!(merged.resources.write_bytes, 25);
+        assert_eq!(merged.resource_fee, 1500);
+        assert_eq!(first.get_archived_entries(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_archived_entries() {
+        let temporary = data_key(9, xdr::ContractDataDurability::Temporary);
+        let mut builder = SorobanDataBuilder::new(None);
+        builder.set_footprint(
+            Some(vec![persistent(1)]),
+            Some(vec![persistent(2), temporary.clone(), persistent(3)]),
+        );
+
+        assert!(builder.mark_archived(&persistent(1)).is_err());
+        assert!(builder.mark_archived(&temporary).is_err());
+        assert!(builder.set_archived_entries(vec![5]).is_err());
+        builder.set_archived_entries(vec![2, 0, 2]).unwrap();
+        assert_eq!(builder.get_archived_entries(), vec![0, 2]);
+        assert!(matches!(
+            builder.build().ext,
+            xdr::SorobanTransactionDataExt::V1(_)
+        ));
+
+        builder.set_read_write(vec![persistent(3), temporary]);
+        assert_eq!(builder.get_archived_entries(), vec![0]);
+        builder.set_read_write(vec![]);
+        assert_eq!(builder.build().ext, xdr::SorobanTransactionDataExt::V0);
+    }
+
+    #[test]
+    fn test_validates_against_limits() {
+        let account = xdr::LedgerKey::Account(xdr::LedgerKeyAccount {
+            account_id: xdr::AccountId(xdr::PublicKey::PublicKeyTypeEd25519(xdr::Uint256([0; 32]))),
+        });
+        let mut builder = SorobanDataBuilder::new(None);
+        builder
+            .set_footprint(
+                Some(vec![account.clone(), persistent(1)]),
+                Some(vec![persistent(2)]),
+            )
+            .set_resources(1000, 100, 100)
+            .mark_archived(&persistent(2))
+            .unwrap();
+        assert_eq!(builder.validate(&limits()), Ok(()));
+
+        let strict = SorobanLimits {
+            tx_max_disk_read_entries: 1,
+            ..limits()
+        };
+        assert_eq!(
+            builder.validate(&strict).unwrap_err(),
+            "disk read entries 2 exceed the limit of 1"
+        );
+        let strict = SorobanLimits {
+            tx_max_instructions: 999,
+            ..limits()
+        };
+        assert!(builder.validate(&strict).is_err());
+
+        builder.append_footprint(vec![persistent(3), persistent(4)], vec![]);
+        assert_eq!(
+            builder.validate(&limits()).unwrap_err(),
+            "footprint entries 5 exceed the limit of 4"
+        );
+
+        let ttl = xdr::LedgerKey::Ttl(xdr::LedgerKeyTtl {
+            key_hash: xdr::Hash([0; 32]),
+        });
+        builder.set_footprint(Some(vec![ttl]), Some(vec![]));
+        assert_eq!(
+            builder.validate(&limits()).unwrap_err(),
+            "invalid footprint key type: Ttl"
+        );
+    }
+
+    #[test]
+    fn test_try_from_xdr_and_limits_from_config() {
+        assert!(SorobanDataBuilder::try_from_xdr(Either::Left("AAAA".to_string())).is_err());
+        assert!(SorobanDataBuilder::try_from_xdr(Either::Right(vec![1, 2])).is_err());
+        assert!(SorobanDataBuilder::try_new(Some(Either::Left("bad".to_string()))).is_err());
+        assert_eq!(
+            SorobanDataBuilder::try_new(None).unwrap().build(),
+            SorobanDataBuilder::new(None).build()
+        );
+
+        assert_eq!(
+            SorobanLimits::from_config_settings(&[]).unwrap_err(),
+            "missing config setting: ContractComputeV0"
+        );
+        let limits = SorobanLimits::from_config_settings(&[
+            xdr::ConfigSettingEntry::ContractComputeV0(xdr::ConfigSettingContractComputeV0 {
+                ledger_max_instructions: 0,
+                tx_max_instructions: 100_000_000,
+                fee_rate_per_instructions_increment: 0,
+                tx_memory_limit: 0,
+            }),
+            xdr::ConfigSettingEntry::ContractLedgerCostV0(xdr::ConfigSettingContractLedgerCostV0 {
+                ledger_max_disk_read_entries: 0,
+                ledger_max_disk_read_bytes: 0,
+                ledger_max_write_ledger_entries: 0,
+                ledger_max_write_bytes: 0,
+                tx_max_disk_read_entries: 2,
+                tx_max_disk_read_bytes: 200_000,
+                tx_max_write_ledger_entries: 2,
+                tx_max_write_bytes: 130_000,
+                fee_disk_read_ledger_entry: 0,
+                fee_write_ledger_entry: 0,
+                fee_disk_read1_kb: 0,
+                soroban_state_target_size_bytes: 0,
+                rent_fee1_kb_soroban_state_size_low: 0,
+                rent_fee1_kb_soroban_state_size_high: 0,
+                soroban_state_rent_fee_growth_factor: 0,
+            }),
+            xdr::ConfigSettingEntry::ContractLedgerCostExtV0(
+                xdr::ConfigSettingContractLedgerCostExtV0 {
+                    tx_max_footprint_entries: 4,
+                    fee_write1_kb: 0,
+                },
+            ),
+        ])
+        .unwrap();
+        assert_eq!(limits, self::limits());
+    }
 }
@@ -0,0 +1,693 @@
+//! Soroban resource fees computed from the network fee configuration.
+//!
+//! The formulas follow stellar-core (protocol 23 and later), so fees can be budgeted from
+//! the `ConfigSettingEntry` ledger entries without simulating every transaction.
+use crate::soroban_data_builder::{SorobanDataBuilder, SorobanDataBuilderBehavior};
+use crate::xdr;
+
+pub const INSTRUCTIONS_INCREMENT: i64 = 10_000;
+pub const DATA_SIZE_1KB_INCREMENT: i64 = 1024;
+/// Size of a transaction result that is charged as historical data on top of the envelope.
+pub const TX_BASE_RESULT_SIZE: u32 = 300;
+/// Size of a TTL entry written when an entry's lifetime is extended.
+pub const TTL_ENTRY_SIZE: u32 = 48;
+pub const MINIMUM_RENT_FEE_PER_1KB: i64 = 1000;
+const CODE_ENTRY_RENT_DISCOUNT_FACTOR: i64 = 3;
+
+/// Fee rates of the network, taken from its config setting entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeConfiguration {
+    pub fee_per_instruction_increment: i64,
+    pub fee_per_disk_read_entry: i64,
+    pub fee_per_write_entry: i64,
+    pub fee_per_disk_read_1kb: i64,
+    pub fee_per_write_1kb: i64,
+    pub fee_per_historical_1kb: i64,
+    pub fee_per_contract_event_1kb: i64,
+    pub fee_per_transaction_size_1kb: i64,
+    pub fee_per_rent_1kb: i64,
+    pub persistent_rent_rate_denominator: i64,
+    pub temporary_rent_rate_denominator: i64,
+}
+
+/// The resources a transaction is charged for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionResources {
+    pub instructions: u32,
+    pub disk_read_entries: u32,
+    pub write_entries: u32,
+    pub disk_read_bytes: u32,
+    pub write_bytes: u32,
+    pub contract_events_size_bytes: u32,
+    /// Size of the whole signed transaction envelope.
+    pub transaction_size_bytes: u32,
+}
+
+/// A change of size or lifetime of a ledger entry, which is charged rent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LedgerEntryRentChange {
+    pub is_persistent: bool,
+    pub is_code_entry: bool,
+    /// Zero for new entries.
+    pub old_size_bytes: u32,
+    pub new_size_bytes: u32,
+    /// Zero for new entries.
+    pub old_live_until_ledger: u32,
+    pub new_live_until_ledger: u32,
+}
+
+/// The resource fee of a transaction. Rent is part of the refundable fee.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceFee {
+    pub non_refundable: i64,
+    pub refundable: i64,
+}
+
+impl ResourceFee {
+    /// The `resource_fee` to declare in the transaction's Soroban data.
+    pub fn total(&self) -> i64 {
+        self.non_refundable.saturating_add(self.refundable)
+    }
+}
+
+// Define a trait for FeeConfiguration behavior
+pub trait FeeConfigurationBehavior {
+    fn from_config_settings(settings: &[xdr::ConfigSettingEntry]) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn resource_fee(&self, resources: &TransactionResources) -> ResourceFee;
+    fn rent_fee(&self, changes: &[LedgerEntryRentChange], current_ledger: u32) -> i64;
+    fn set_resource_fee(
+        &self,
+        builder: &mut SorobanDataBuilder,
+        is_restore: bool,
+        transaction_size_bytes: u32,
+        contract_events_size_bytes: u32,
+        rent_fee: i64,
+    ) -> ResourceFee;
+}
+
+// Define a trait for TransactionResources behavior
+pub trait TransactionResourcesBehavior {
+    fn new(
+        data: &xdr::SorobanTransactionData,
+        is_restore: bool,
+        transaction_size_bytes: u32,
+        contract_events_size_bytes: u32,
+    ) -> Self;
+}
+
+fn fee_per_increment(resource_value: u32, fee_rate: i64, increment: i64) -> i64 {
+    div_ceil(
+        i64::from(resource_value).saturating_mul(fee_rate),
+        increment,
+    )
+}
+
+fn div_ceil(num: i64, denom: i64) -> i64 {
+    let denom = denom.max(1);
+    let quotient = num / denom;
+    if num % denom > 0 {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+/// The rent fee per 1KB for a given Soroban state size. It grows linearly from the low to the
+/// high rate until the target size, and faster beyond it.
+pub fn rent_fee_per_1kb(
+    soroban_state_size_bytes: i64,
+    ledger_cost: &xdr::ConfigSettingContractLedgerCostV0,
+) -> i64 {
+    let target = ledger_cost.soroban_state_target_size_bytes;
+    let low = ledger_cost.rent_fee1_kb_soroban_state_size_low;
+    let high = ledger_cost.rent_fee1_kb_soroban_state_size_high;
+    let multiplier = high.saturating_sub(low);
+
+    let fee = if soroban_state_size_bytes < target {
+        div_ceil(multiplier.saturating_mul(soroban_state_size_bytes), target).saturating_add(low)
+    } else {
+        let over_target = soroban_state_size_bytes.saturating_sub(target);
+        let growth = div_ceil(
+            multiplier
+                .saturating_mul(over_target)
+                .saturating_mul(ledger_cost.soroban_state_rent_fee_growth_factor.into()),
+            target,
+        );
+        high.saturating_add(growth)
+    };
+    fee.max(MINIMUM_RENT_FEE_PER_1KB)
+}
+
+fn is_soroban_key(key: &xdr::LedgerKey) -> bool {
+    matches!(
+        key,
+        xdr::LedgerKey::ContractData(_) | xdr::LedgerKey::ContractCode(_)
+    )
+}
+
+impl TransactionResourcesBehavior for TransactionResources {
+    /// Collects the resources declared in Soroban data. Live Soroban entries are read from
+    /// memory, so only classic entries and archived entries being restored count as disk reads.
+    /// A `restore_footprint` transaction (`is_restore`) reads every read-write entry from disk.
+    fn new(
+        data: &xdr::SorobanTransactionData,
+        is_restore: bool,
+        transaction_size_bytes: u32,
+        contract_events_size_bytes: u32,
+    ) -> Self {
+        let resources = &data.resources;
+        let footprint = &resources.footprint;
+        let classic_entries = footprint
+            .read_only
+            .iter()
+            .chain(footprint.read_write.iter())
+            .filter(|key| !is_soroban_key(key))
+            .count();
+        let archived_entries = match &data.ext {
+            xdr::SorobanTransactionDataExt::V0 => 0,
+            xdr::SorobanTransactionDataExt::V1(ext) => ext.archived_soroban_entries.len(),
+        };
+
+        let disk_read_entries = if is_restore {
+            footprint.read_write.len()
+        } else {
+            classic_entries + archived_entries
+        };
+
+        Self {
+            instructions: resources.instructions,
+            disk_read_entries: disk_read_entries as u32,
+            write_entries: footprint.read_write.len() as u32,
+            disk_read_bytes: resources.disk_read_bytes,
+            write_bytes: resources.write_bytes,
+            contract_events_size_bytes,
+            transaction_size_bytes,
+        }
+    }
+}
+
+impl FeeConfiguration {
+    fn rent_fee_for_size_and_ledgers(
+        &self,
+        change: &LedgerEntryRentChange,
+        size_bytes: u32,
+        rent_ledgers: u32,
+    ) -> i64 {
+        let num = i64::from(size_bytes)
+            .saturating_mul(self.fee_per_rent_1kb)
+            .saturating_mul(rent_ledgers.into());
+        let rate_denominator = if change.is_persistent {
+            self.persistent_rent_rate_denominator
+        } else {
+            self.temporary_rent_rate_denominator
+        };
+        let mut denom = DATA_SIZE_1KB_INCREMENT.saturating_mul(rate_denominator);
+        if change.is_code_entry {
+            denom = denom.saturating_mul(CODE_ENTRY_RENT_DISCOUNT_FACTOR);
+        }
+        div_ceil(num, denom)
+    }
+
+    fn rent_fee_for_change(&self, change: &LedgerEntryRentChange, current_ledger: u32) -> i64 {
+        let is_new = change.old_size_bytes == 0 && change.old_live_until_ledger == 0;
+        let mut fee = 0i64;
+
+        // Pay for the extension at the new size.
+        if change.old_live_until_ledger < change.new_live_until_ledger {
+            let extended_from = if is_new {
+                current_ledger.saturating_sub(1)
+            } else {
+                change.old_live_until_ledger
+            };
+            if let Some(ledgers) = change.new_live_until_ledger.checked_sub(extended_from) {
+                fee = fee.saturating_add(self.rent_fee_for_size_and_ledgers(
+                    change,
+                    change.new_size_bytes,
+                    ledgers,
+                ));
+            }
+        }
+
+        // Top up the already paid ledgers when the entry grew.
+        if !is_new && change.new_size_bytes > change.old_size_bytes {
+            if let Some(ledgers) = change.old_live_until_ledger.checked_sub(current_ledger) {
+                fee = fee.saturating_add(self.rent_fee_for_size_and_ledgers(
+                    change,
+                    change.new_size_bytes - change.old_size_bytes,
+                    ledgers.saturating_add(1),
+                ));
+            }
+        }
+        fee
+    }
+}
+
+impl FeeConfigurationBehavior for FeeConfiguration {
+    /// Reads the fee rates from the network's config setting entries. The rent rate is derived
+    /// from the average of the live Soroban state size window.
+    fn from_config_settings(settings: &[xdr::ConfigSettingEntry]) -> Result<Self, String> {
+        let mut compute = None;
+        let mut ledger_cost = None;
+        let mut ledger_cost_ext = None;
+        let mut historical = None;
+        let mut events = None;
+        let mut bandwidth = None;
+        let mut archival = None;
+        let mut state_size_window = None;
+        for setting in settings {
+            match setting {
+                xdr::ConfigSettingEntry::ContractComputeV0(s) => compute = Some(s),
+                xdr::ConfigSettingEntry::ContractLedgerCostV0(s) => ledger_cost = Some(s),
+                xdr::ConfigSettingEntry::ContractLedgerCostExtV0(s) => ledger_cost_ext = Some(s),
+                xdr::ConfigSettingEntry::ContractHistoricalDataV0(s) => historical = Some(s),
+                xdr::ConfigSettingEntry::ContractEventsV0(s) => events = Some(s),
+                xdr::ConfigSettingEntry::ContractBandwidthV0(s) => bandwidth = Some(s),
+                xdr::ConfigSettingEntry::StateArchival(s) => archival = Some(s),
+                xdr::ConfigSettingEntry::LiveSorobanStateSizeWindow(s) => {
+                    state_size_window = Some(s)
+                }
+                _ => {}
+            }
+        }
+        let missing = |name: &str| format!("missing config setting: {}", name);
+        let compute = compute.ok_or_else(|| missing("ContractComputeV0"))?;
+        let ledger_cost = ledger_cost.ok_or_else(|| missing("ContractLedgerCostV0"))?;
+        let ledger_cost_ext = ledger_cost_ext.ok_or_else(|| missing("ContractLedgerCostExtV0"))?;
+        let historical = historical.ok_or_else(|| missing("ContractHistoricalDataV0"))?;
+        let events = events.ok_or_else(|| missing("ContractEventsV0"))?;
+        let bandwidth = bandwidth.ok_or_else(|| missing("ContractBandwidthV0"))?;
+        let archival = archival.ok_or_else(|| missing("StateArchival"))?;
+        let window = state_size_window.ok_or_else(|| missing("LiveSorobanStateSizeWindow"))?;
+
+        let state_size = if window.is_empty() {
+            0
+        } else {
+            let sum: u128 = window.iter().map(|&size| u128::from(size)).sum();
+            i64::try_from(sum / window.len() as u128).unwrap_or(i64::MAX)
+        };
+
+        Ok(Self {
+            fee_per_instruction_increment: compute.fee_rate_per_instructions_increment,
+            fee_per_disk_read_entry: ledger_cost.fee_disk_read_ledger_entry,
+            fee_per_write_entry: ledger_cost.fee_write_ledger_entry,
+            fee_per_disk_read_1kb: ledger_cost.fee_disk_read1_kb,
+            fee_per_write_1kb: ledger_cost_ext.fee_write1_kb,
+            fee_per_historical_1kb: historical.fee_historical1_kb,
+            fee_per_contract_event_1kb: events.fee_contract_events1_kb,
+            fee_per_transaction_size_1kb: bandwidth.fee_tx_size1_kb,
+            fee_per_rent_1kb: rent_fee_per_1kb(state_size, ledger_cost),
+            persistent_rent_rate_denominator: archival.persistent_rent_rate_denominator,
+            temporary_rent_rate_denominator: archival.temp_rent_rate_denominator,
+        })
+    }
+
+    /// Computes the resource fee without rent; add `rent_fee` to the refundable part.
+    fn resource_fee(&self, resources: &TransactionResources) -> ResourceFee {
+        let compute_fee = fee_per_increment(
+            resources.instructions,
+            self.fee_per_instruction_increment,
+            INSTRUCTIONS_INCREMENT,
+        );
+        let read_entries_fee = self
+            .fee_per_disk_read_entry
+            .saturating_mul(resources.disk_read_entries.into());
+        let write_entries_fee = self
+            .fee_per_write_entry
+            .saturating_mul(resources.write_entries.into());
+        let read_bytes_fee = fee_per_increment(
+            resources.disk_read_bytes,
+            self.fee_per_disk_read_1kb,
+            DATA_SIZE_1KB_INCREMENT,
+        );
+        let write_bytes_fee = fee_per_increment(
+            resources.write_bytes,
+            self.fee_per_write_1kb,
+            DATA_SIZE_1KB_INCREMENT,
+        );
+        let historical_fee = fee_per_increment(
+            resources
+                .transaction_size_bytes
+                .saturating_add(TX_BASE_RESULT_SIZE),
+            self.fee_per_historical_1kb,
+            DATA_SIZE_1KB_INCREMENT,
+        );
+        let bandwidth_fee = fee_per_increment(
+            resources.transaction_size_bytes,
+            self.fee_per_transaction_size_1kb,
+            DATA_SIZE_1KB_INCREMENT,
+        );
+        let events_fee = fee_per_increment(
+            resources.contract_events_size_bytes,
+            self.fee_per_contract_event_1kb,
+            DATA_SIZE_1KB_INCREMENT,
+        );
+
+        ResourceFee {
+            non_refundable: [
+                compute_fee,
+                read_entries_fee,
+                write_entries_fee,
+                read_bytes_fee,
+                write_bytes_fee,
+                historical_fee,
+                bandwidth_fee,
+            ]
+            .into_iter()
+            .fold(0i64, i64::saturating_add),
+            refundable: events_fee,
+        }
+    }
+
+    /// Computes the rent for entries created, grown or extended at `current_ledger`, including
+    /// the writes of their TTL entries.
+    fn rent_fee(&self, changes: &[LedgerEntryRentChange], current_ledger: u32) -> i64 {
+        let mut fee = 0i64;
+        let mut extended_entries = 0u32;
+        for change in changes {
+            fee = fee.saturating_add(self.rent_fee_for_change(change, current_ledger));
+            if change.old_live_until_ledger < change.new_live_until_ledger {
+                extended_entries = extended_entries.saturating_add(1);
+            }
+        }
+
+        fee = fee.saturating_add(
+            self.fee_per_write_entry
+                .saturating_mul(extended_entries.into()),
+        );
+        fee.saturating_add(fee_per_increment(
+            extended_entries.saturating_mul(TTL_ENTRY_SIZE),
+            self.fee_per_write_1kb,
+            DATA_SIZE_1KB_INCREMENT,
+        ))
+    }
+
+    /// Computes the fee for the resources declared in `builder` and sets it as its resource fee.
+    /// `is_restore` is set for `restore_footprint` transactions.
+    fn set_resource_fee(
+        &self,
+        builder: &mut SorobanDataBuilder,
+        is_restore: bool,
+        transaction_size_bytes: u32,
+        contract_events_size_bytes: u32,
+        rent_fee: i64,
+    ) -> ResourceFee {
+        let resources = TransactionResources::new(
+            &builder.build(),
+            is_restore,
+            transaction_size_bytes,
+            contract_events_size_bytes,
+        );
+        let mut fee = self.resource_fee(&resources);
+        fee.refundable = fee.refundable.saturating_add(rent_fee);
+        builder.set_refundable_fee(fee.total());
+        fee
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soroban_data_builder::Either;
+
+    fn config() -> FeeConfiguration {
+        FeeConfiguration {
+            fee_per_instruction_increment: 25,
+            fee_per_disk_read_entry: 6250,
+            fee_per_write_entry: 10000,
+            fee_per_disk_read_1kb: 1786,
+            fee_per_write_1kb: 3500,
+            fee_per_historical_1kb: 16235,
+            fee_per_contract_event_1kb: 10000,
+            fee_per_transaction_size_1kb: 1624,
+            fee_per_rent_1kb: 2048,
+            persistent_rent_rate_denominator: 1024,
+            temporary_rent_rate_denominator: 2048,
+        }
+    }
+
+    fn contract_data_key(byte: u8) -> xdr::LedgerKey {
+        xdr::LedgerKey::ContractData(xdr::LedgerKeyContractData {
+            contract: xdr::ScAddress::Contract(xdr::ContractId(xdr::Hash([byte; 32]))),
+            key: xdr::ScVal::LedgerKeyContractInstance,
+            durability: xdr::ContractDataDurability::Persistent,
+        })
+    }
+
+    fn account_key() -> xdr::LedgerKey {
+        xdr::LedgerKey::Account(xdr::LedgerKeyAccount {
+            account_id: xdr::AccountId(xdr::PublicKey::PublicKeyTypeEd25519(xdr::Uint256([0; 32]))),
+        })
+    }
+
+    #[test]
+    fn test_resource_fee() {
+        let resources = TransactionResources {
+            instructions: 1_000_001,
+            disk_read_entries: 2,
+            write_entries: 1,
+            disk_read_bytes: 2048,
+            write_bytes: 1024,
+            contract_events_size_bytes: 512,
+            transaction_size_bytes: 724,
+        };
+
+        let fee = config().resource_fee(&resources);
+
+        // compute: ceil(1_000_001 * 25 / 10_000) = 2501
+        // entries: 2 * 6250 + 10000 = 22500
+        // bytes: 2 * 1786 + 3500 = 7072
+        // historical: ceil(1024 * 16235 / 1024) = 16235
+        // bandwidth: ceil(724 * 1624 / 1024) = 1149
+        assert_eq!(fee.non_refundable, 2501 + 22500 + 7072 + 16235 + 1149);
+        assert_eq!(fee.refundable, 5000);
+        assert_eq!(fee.total(), fee.non_refundable + 5000);
+        assert_eq!(
+            config().resource_fee(&TransactionResources::default()),
+            ResourceFee {
+                non_refundable: 4757,
+                refundable: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_rent_fee() {
+        let config = config();
+
+        // A new 1KB persistent entry living 1024 ledgers from ledger 1000.
+        let created = LedgerEntryRentChange {
+            is_persistent: true,
+            new_size_bytes: 1024,
+            new_live_until_ledger: 2023,
+            ..Default::default()
+        };
+        let ttl_write = 10000 + div_ceil(48 * 3500, 1024);
+        assert_eq!(
+            config.rent_fee(std::slice::from_ref(&created), 1000),
+            2048 + ttl_write
+        );
+
+        // Code entries are discounted and temporary entries pay half the rate.
+        let code = LedgerEntryRentChange {
+            is_code_entry: true,
+            ..created.clone()
+        };
+        assert_eq!(config.rent_fee(&[code], 1000), 683 + ttl_write);
+        let temporary = LedgerEntryRentChange {
+            is_persistent: false,
+            ..created.clone()
+        };
+        assert_eq!(config.rent_fee(&[temporary], 1000), 1024 + ttl_write);
+
+        // Growing an entry without extending it only tops up the prepaid ledgers.
+        let grown = LedgerEntryRentChange {
+            is_persistent: true,
+            old_size_bytes: 1024,
+            new_size_bytes: 2048,
+            old_live_until_ledger: 2023,
+            new_live_until_ledger: 2023,
+            ..Default::default()
+        };
+        assert_eq!(config.rent_fee(std::slice::from_ref(&grown), 1000), 2048);
+        assert_eq!(config.rent_fee(&[grown], 3000), 0);
+        assert_eq!(config.rent_fee(&[], 1000), 0);
+    }
+
+    #[test]
+    fn test_resources_from_soroban_data() {
+        let mut builder = SorobanDataBuilder::new(None);
+        builder
+            .set_footprint(
+                Some(vec![contract_data_key(1), account_key()]),
+                Some(vec![contract_data_key(2)]),
+            )
+            .set_resources(500_000, 1000, 200);
+        let mut data = builder.build();
+
+        let resources = TransactionResources::new(&data, false, 600, 100);
+        assert_eq!(
+            resources,
+            TransactionResources {
+                instructions: 500_000,
+                disk_read_entries: 1,
+                write_entries: 1,
+                disk_read_bytes: 1000,
+                write_bytes: 200,
+                contract_events_size_bytes: 100,
+                transaction_size_bytes: 600,
+            }
+        );
+
+        data.ext = xdr::SorobanTransactionDataExt::V1(xdr::SorobanResourcesExtV0 {
+            archived_soroban_entries: vec![0].try_into().unwrap(),
+        });
+        assert_eq!(
+            TransactionResources::new(&data, false, 600, 100).disk_read_entries,
+            2
+        );
+
+        // restoring reads every read-write entry from disk
+        let mut builder = SorobanDataBuilder::new(None);
+        builder.set_footprint(
+            Some(vec![]),
+            Some(vec![
+                contract_data_key(1),
+                contract_data_key(2),
+                account_key(),
+            ]),
+        );
+        let data = builder.build();
+        assert_eq!(
+            TransactionResources::new(&data, false, 600, 0).disk_read_entries,
+            1
+        );
+        assert_eq!(
+            TransactionResources::new(&data, true, 600, 0).disk_read_entries,
+            3
+        );
+    }
+
+    #[test]
+    fn test_sets_resource_fee_on_builder() {
+        let config = config();
+        let mut builder = SorobanDataBuilder::new(None);
+        builder
+            .set_footprint(Some(vec![account_key()]), Some(vec![contract_data_key(1)]))
+            .set_resources(1_000_000, 0, 512);
+
+        let fee = config.set_resource_fee(&mut builder, false, 500, 0, 1234);
+
+        let expected =
+            config.resource_fee(&TransactionResources::new(&builder.build(), false, 500, 0));
+        assert_eq!(fee.non_refundable, expected.non_refundable);
+        assert_eq!(fee.refundable, 1234);
+        assert_eq!(builder.build().resource_fee, fee.total());
+
+        let rebuilt = SorobanDataBuilder::new(Some(Either::Right(builder.build())));
+        assert_eq!(rebuilt.build().resource_fee, fee.total());
+
+        // a restore pays for reading both archived entries from disk
+        let mut builder = SorobanDataBuilder::new(None);
+        builder.set_footprint(
+            Some(vec![]),
+            Some(vec![contract_data_key(1), contract_data_key(2)]),
+        );
+        let invoke = config.set_resource_fee(&mut builder, false, 500, 0, 0);
+        let restore = config.set_resource_fee(&mut builder, true, 500, 0, 0);
+        assert_eq!(
+            restore.non_refundable - invoke.non_refundable,
+            2 * config.fee_per_disk_read_entry
+        );
+    }
+
+    #[test]
+    fn test_from_config_settings() {
+        let ledger_cost = xdr::ConfigSettingContractLedgerCostV0 {
+            ledger_max_disk_read_entries: 0,
+            ledger_max_disk_read_bytes: 0,
+            ledger_max_write_ledger_entries: 0,
+            ledger_max_write_bytes: 0,
+            tx_max_disk_read_entries: 0,
+            tx_max_disk_read_bytes: 0,
+            tx_max_write_ledger_entries: 0,
+            tx_max_write_bytes: 0,
+            fee_disk_read_ledger_entry: 6250,
+            fee_write_ledger_entry: 10000,
+            fee_disk_read1_kb: 1786,
+            soroban_state_target_size_bytes: 1000,
+            rent_fee1_kb_soroban_state_size_low: 2000,
+            rent_fee1_kb_soroban_state_size_high: 10000,
+            soroban_state_rent_fee_growth_factor: 2,
+        };
+        let mut settings = vec![
+            xdr::ConfigSettingEntry::ContractComputeV0(xdr::ConfigSettingContractComputeV0 {
+                ledger_max_instructions: 0,
+                tx_max_instructions: 0,
+                fee_rate_per_instructions_increment: 25,
+                tx_memory_limit: 0,
+            }),
+            xdr::ConfigSettingEntry::ContractLedgerCostV0(ledger_cost.clone()),
+            xdr::ConfigSettingEntry::ContractLedgerCostExtV0(
+                xdr::ConfigSettingContractLedgerCostExtV0 {
+                    tx_max_footprint_entries: 0,
+                    fee_write1_kb: 3500,
+                },
+            ),
+            xdr::ConfigSettingEntry::ContractHistoricalDataV0(
+                xdr::ConfigSettingContractHistoricalDataV0 {
+                    fee_historical1_kb: 16235,
+                },
+            ),
+            xdr::ConfigSettingEntry::ContractEventsV0(xdr::ConfigSettingContractEventsV0 {
+                tx_max_contract_events_size_bytes: 0,
+                fee_contract_events1_kb: 10000,
+            }),
+            xdr::ConfigSettingEntry::ContractBandwidthV0(xdr::ConfigSettingContractBandwidthV0 {
+                ledger_max_txs_size_bytes: 0,
+                tx_max_size_bytes: 0,
+                fee_tx_size1_kb: 1624,
+            }),
+            xdr::ConfigSettingEntry::StateArchival(xdr::StateArchivalSettings {
+                max_entry_ttl: 0,
+                min_temporary_ttl: 0,
+                min_persistent_ttl: 0,
+                persistent_rent_rate_denominator: 1024,
+                temp_rent_rate_denominator: 2048,
+                max_entries_to_archive: 0,
+                live_soroban_state_size_window_sample_size: 0,
+                live_soroban_state_size_window_sample_period: 0,
+                eviction_scan_size: 0,
+                starting_eviction_scan_level: 0,
+            }),
+        ];
+        assert_eq!(
+            FeeConfiguration::from_config_settings(&settings).unwrap_err(),
+            "missing config setting: LiveSorobanStateSizeWindow"
+        );
+
+        settings.push(xdr::ConfigSettingEntry::LiveSorobanStateSizeWindow(
+            vec![200, 300].try_into().unwrap(),
+        ));
+        let config = FeeConfiguration::from_config_settings(&settings).unwrap();
+
+        // The average state size of 250 bytes is a quarter of the target.
+        assert_eq!(
+            config,
+            FeeConfiguration {
+                fee_per_rent_1kb: 4000,
+                ..self::config()
+            }
+        );
+        assert_eq!(rent_fee_per_1kb(0, &ledger_cost), 2000);
+        assert_eq!(rent_fee_per_1kb(1000, &ledger_cost), 10000);
+        assert_eq!(rent_fee_per_1kb(1500, &ledger_cost), 18000);
+        let cheap = xdr::ConfigSettingContractLedgerCostV0 {
+            rent_fee1_kb_soroban_state_size_low: 0,
+            rent_fee1_kb_soroban_state_size_high: 0,
+            ..ledger_cost
+        };
+        assert_eq!(rent_fee_per_1kb(0, &cheap), MINIMUM_RENT_FEE_PER_1KB);
+    }
+}
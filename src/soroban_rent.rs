@@ -0,0 +1,404 @@
+//! Rent and TTL of Soroban ledger entries, and restoring archived entries before invoking.
+use serde::{Deserialize, Serialize};
+
+use crate::account::Account;
+use crate::operation::Operation;
+use crate::soroban_data_builder::{Either, SorobanDataBuilder, SorobanDataBuilderBehavior};
+use crate::soroban_fee::{FeeConfiguration, FeeConfigurationBehavior, LedgerEntryRentChange};
+use crate::transaction::Transaction;
+use crate::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+use crate::xdr::{self, Limits, ReadXdr, WriteXdr};
+
+/// A ledger entry paying rent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RentEntry {
+    pub is_persistent: bool,
+    pub is_code_entry: bool,
+    pub size_bytes: u32,
+    pub live_until_ledger: u32,
+}
+
+/// Computes TTLs and rent from the network's fee and state archival settings.
+#[derive(Debug, Clone)]
+pub struct RentCalculator {
+    pub fees: FeeConfiguration,
+    pub settings: xdr::StateArchivalSettings,
+}
+
+/// The restore preamble of a `simulateTransaction` response, returned when the footprint
+/// contains archived entries that must be restored first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePreamble {
+    /// Base64 `SorobanTransactionData` for the restore transaction.
+    pub transaction_data: String,
+    pub min_resource_fee: String,
+}
+
+// Define a trait for RentEntry behavior
+pub trait RentEntryBehavior {
+    fn from_ledger_entry(entry: &xdr::LedgerEntry, live_until_ledger: u32) -> Result<Self, String>
+    where
+        Self: Sized;
+}
+
+// Define a trait for RentCalculator behavior
+pub trait RentCalculatorBehavior {
+    fn new(fees: FeeConfiguration, settings: xdr::StateArchivalSettings) -> Self;
+    fn extended_live_until(
+        &self,
+        current_ledger: u32,
+        extend_to: u32,
+        live_until_ledger: u32,
+    ) -> Result<u32, String>;
+    fn restored_live_until(&self, current_ledger: u32) -> u32;
+    fn extend_fee(
+        &self,
+        entries: &[RentEntry],
+        current_ledger: u32,
+        extend_to: u32,
+    ) -> Result<i64, String>;
+    fn restore_fee(&self, entries: &[RentEntry], current_ledger: u32) -> i64;
+}
+
+// Define a trait for RestorePreamble behavior
+pub trait RestorePreambleBehavior {
+    fn soroban_data(&self) -> Result<xdr::SorobanTransactionData, String>;
+    fn restore_transaction(
+        &self,
+        source: &mut Account,
+        network: &str,
+        base_fee: u32,
+        timeout_seconds: i64,
+    ) -> Result<Transaction, String>;
+}
+
+impl RentEntryBehavior for RentEntry {
+    /// Describes a contract data or code entry by its XDR size. Contract code is also charged
+    /// for its in-memory size on the network, which this does not include.
+    fn from_ledger_entry(entry: &xdr::LedgerEntry, live_until_ledger: u32) -> Result<Self, String> {
+        let (is_persistent, is_code_entry) = match &entry.data {
+            xdr::LedgerEntryData::ContractData(data) => (
+                data.durability == xdr::ContractDataDurability::Persistent,
+                false,
+            ),
+            xdr::LedgerEntryData::ContractCode(_) => (true, true),
+            _ => return Err("only contract data and code entries pay rent".to_string()),
+        };
+        let size_bytes = entry
+            .to_xdr(Limits::none())
+            .map_err(|e| e.to_string())?
+            .len();
+
+        Ok(Self {
+            is_persistent,
+            is_code_entry,
+            size_bytes: size_bytes as u32,
+            live_until_ledger,
+        })
+    }
+}
+
+impl RentCalculatorBehavior for RentCalculator {
+    fn new(fees: FeeConfiguration, settings: xdr::StateArchivalSettings) -> Self {
+        Self { fees, settings }
+    }
+
+    /// The `live_until_ledger` of an entry after an `extend_footprint_ttl(extend_to)` applied
+    /// in `current_ledger`. Entries already living longer are left unchanged.
+    fn extended_live_until(
+        &self,
+        current_ledger: u32,
+        extend_to: u32,
+        live_until_ledger: u32,
+    ) -> Result<u32, String> {
+        if extend_to > self.settings.max_entry_ttl.saturating_sub(1) {
+            return Err(format!(
+                "extend_to cannot exceed {} ledgers",
+                self.settings.max_entry_ttl.saturating_sub(1)
+            ));
+        }
+        let extended = current_ledger
+            .checked_add(extend_to)
+            .ok_or("live_until_ledger overflows u32")?;
+        Ok(extended.max(live_until_ledger))
+    }
+
+    /// The `live_until_ledger` of an entry restored in `current_ledger`.
+    fn restored_live_until(&self, current_ledger: u32) -> u32 {
+        current_ledger
+            .saturating_add(self.settings.min_persistent_ttl)
+            .saturating_sub(1)
+    }
+
+    /// The rent fee of extending `entries` with `extend_footprint_ttl(extend_to)`.
+    fn extend_fee(
+        &self,
+        entries: &[RentEntry],
+        current_ledger: u32,
+        extend_to: u32,
+    ) -> Result<i64, String> {
+        self.extended_live_until(current_ledger, extend_to, 0)?;
+        let changes = entries
+            .iter()
+            .map(|entry| {
+                Ok(LedgerEntryRentChange {
+                    is_persistent: entry.is_persistent,
+                    is_code_entry: entry.is_code_entry,
+                    old_size_bytes: entry.size_bytes,
+                    new_size_bytes: entry.size_bytes,
+                    old_live_until_ledger: entry.live_until_ledger,
+                    new_live_until_ledger: self.extended_live_until(
+                        current_ledger,
+                        extend_to,
+                        entry.live_until_ledger,
+                    )?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(self.fees.rent_fee(&changes, current_ledger))
+    }
+
+    /// The rent fee of restoring archived `entries`, which pay rent as if newly created.
+    fn restore_fee(&self, entries: &[RentEntry], current_ledger: u32) -> i64 {
+        let live_until = self.restored_live_until(current_ledger);
+        let changes: Vec<_> = entries
+            .iter()
+            .map(|entry| LedgerEntryRentChange {
+                is_persistent: true,
+                is_code_entry: entry.is_code_entry,
+                old_size_bytes: 0,
+                new_size_bytes: entry.size_bytes,
+                old_live_until_ledger: 0,
+                new_live_until_ledger: live_until,
+            })
+            .collect();
+        self.fees.rent_fee(&changes, current_ledger)
+    }
+}
+
+impl RestorePreambleBehavior for RestorePreamble {
+    /// Decodes the preamble's Soroban data, with a resource fee of at least `min_resource_fee`.
+    fn soroban_data(&self) -> Result<xdr::SorobanTransactionData, String> {
+        let data =
+            xdr::SorobanTransactionData::from_xdr_base64(&self.transaction_data, Limits::none())
+                .map_err(|e| format!("invalid restore transaction data: {}", e))?;
+        let min_resource_fee: i64 = self
+            .min_resource_fee
+            .parse()
+            .map_err(|_| format!("invalid min_resource_fee: {}", self.min_resource_fee))?;
+        if data.resources.footprint.read_write.is_empty() {
+            return Err("restore preamble has no archived entries".to_string());
+        }
+
+        let mut builder = SorobanDataBuilder::try_new(Some(Either::Right(data)))?;
+        let resource_fee = builder.build().resource_fee.max(min_resource_fee);
+        Ok(builder
+            .set_read_only(Vec::new())
+            .set_refundable_fee(resource_fee)
+            .build())
+    }
+
+    /// Builds the `restore_footprint` transaction to submit before re-simulating and submitting
+    /// the invocation. Its fee is `base_fee` plus the resource fee.
+    fn restore_transaction(
+        &self,
+        source: &mut Account,
+        network: &str,
+        base_fee: u32,
+        timeout_seconds: i64,
+    ) -> Result<Transaction, String> {
+        let data = self.soroban_data()?;
+        let fee = u32::try_from(data.resource_fee)
+            .ok()
+            .and_then(|resource_fee| base_fee.checked_add(resource_fee))
+            .ok_or("fee overflows u32")?;
+        let operation = Operation::new()
+            .restore_footprint()
+            .map_err(|e| format!("{:?}", e))?;
+
+        let mut builder = TransactionBuilder::new(source, network, None);
+        builder
+            .fee(fee)
+            .add_operation(operation)
+            .set_soroban_data(data)
+            .set_timeout(timeout_seconds)?;
+        Ok(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::AccountBehavior;
+    use crate::contract::{ContractBehavior, Contracts};
+    use crate::network::{NetworkPassphrase, Networks};
+
+    const SOURCE: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const CONTRACT: &str = "CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXE";
+
+    fn calculator() -> RentCalculator {
+        RentCalculator::new(
+            FeeConfiguration {
+                fee_per_write_entry: 10000,
+                fee_per_write_1kb: 3500,
+                fee_per_rent_1kb: 2048,
+                persistent_rent_rate_denominator: 1024,
+                temporary_rent_rate_denominator: 2048,
+                ..Default::default()
+            },
+            xdr::StateArchivalSettings {
+                max_entry_ttl: 3_110_400,
+                min_temporary_ttl: 17_280,
+                min_persistent_ttl: 1024,
+                persistent_rent_rate_denominator: 1024,
+                temp_rent_rate_denominator: 2048,
+                max_entries_to_archive: 0,
+                live_soroban_state_size_window_sample_size: 0,
+                live_soroban_state_size_window_sample_period: 0,
+                eviction_scan_size: 0,
+                starting_eviction_scan_level: 0,
+            },
+        )
+    }
+
+    fn preamble(read_write: Vec<xdr::LedgerKey>, resource_fee: i64) -> RestorePreamble {
+        let mut builder = SorobanDataBuilder::new(None);
+        builder
+            .set_footprint(None, Some(read_write))
+            .set_resources(0, 1000, 1000)
+            .set_refundable_fee(resource_fee);
+        RestorePreamble {
+            transaction_data: builder.build().to_xdr_base64(Limits::none()).unwrap(),
+            min_resource_fee: "5000".to_string(),
+        }
+    }
+
+    // TTL writes of one extended entry: one write entry plus ceil(48 * 3500 / 1024).
+    const TTL_WRITE: i64 = 10000 + 165;
+
+    #[test]
+    fn test_extended_live_until() {
+        let calculator = calculator();
+        assert_eq!(calculator.extended_live_until(1000, 500, 1200), Ok(1500));
+        assert_eq!(calculator.extended_live_until(1000, 500, 2000), Ok(2000));
+        assert!(calculator.extended_live_until(1000, 3_110_400, 0).is_err());
+        assert!(calculator
+            .extended_live_until(u32::MAX, 1, u32::MAX)
+            .is_err());
+        assert_eq!(calculator.restored_live_until(1000), 2023);
+    }
+
+    #[test]
+    fn test_extend_fee() {
+        let calculator = calculator();
+        let entry = RentEntry {
+            is_persistent: true,
+            size_bytes: 1024,
+            live_until_ledger: 1999,
+            ..Default::default()
+        };
+
+        // Extending from 1999 to 3023 pays 1024 ledgers of 1KB at 2048 / 1024 per ledger.
+        assert_eq!(
+            calculator.extend_fee(std::slice::from_ref(&entry), 1000, 2023),
+            Ok(2048 + TTL_WRITE)
+        );
+        // Entries already living long enough pay nothing.
+        assert_eq!(calculator.extend_fee(&[entry], 1000, 500), Ok(0));
+        assert!(calculator.extend_fee(&[], 1000, 3_110_400).is_err());
+    }
+
+    #[test]
+    fn test_restore_fee() {
+        let calculator = calculator();
+        let entries = [
+            RentEntry {
+                is_persistent: true,
+                size_bytes: 1024,
+                ..Default::default()
+            },
+            RentEntry {
+                is_persistent: true,
+                is_code_entry: true,
+                size_bytes: 3072,
+                ..Default::default()
+            },
+        ];
+
+        // Restored entries live 1024 ledgers; code entries get a third of the rate.
+        assert_eq!(
+            calculator.restore_fee(&entries, 1000),
+            2048 + 2048 + 2 * 10000 + 329
+        );
+    }
+
+    #[test]
+    fn test_rent_entry_from_ledger_entry() {
+        let key = Contracts::new(CONTRACT).unwrap().get_footprint();
+        let xdr::LedgerKey::ContractData(key) = key else {
+            panic!("expected a contract data key");
+        };
+        let entry = xdr::LedgerEntry {
+            last_modified_ledger_seq: 0,
+            data: xdr::LedgerEntryData::ContractData(xdr::ContractDataEntry {
+                ext: xdr::ExtensionPoint::V0,
+                contract: key.contract,
+                key: key.key,
+                durability: xdr::ContractDataDurability::Temporary,
+                val: xdr::ScVal::U32(1),
+            }),
+            ext: xdr::LedgerEntryExt::V0,
+        };
+
+        let rent_entry = RentEntry::from_ledger_entry(&entry, 42).unwrap();
+        assert!(!rent_entry.is_persistent);
+        assert!(!rent_entry.is_code_entry);
+        assert_eq!(
+            rent_entry.size_bytes as usize,
+            entry.to_xdr(Limits::none()).unwrap().len()
+        );
+        assert_eq!(rent_entry.live_until_ledger, 42);
+    }
+
+    #[test]
+    fn test_restore_transaction_from_preamble() {
+        let key = Contracts::new(CONTRACT).unwrap().get_footprint();
+        let mut source = Account::new(SOURCE, "1").unwrap();
+
+        let tx = preamble(vec![key.clone()], 1000)
+            .restore_transaction(&mut source, Networks::testnet(), 100, 30)
+            .unwrap();
+
+        assert_eq!(tx.fee, 5100);
+        assert_eq!(source.sequence_number(), "2");
+        let data = tx.soroban_data.unwrap();
+        assert_eq!(data.resource_fee, 5000);
+        assert_eq!(data.resources.footprint.read_write.to_vec(), vec![key]);
+        assert!(data.resources.footprint.read_only.is_empty());
+        assert!(matches!(
+            tx.operations.unwrap()[0].body,
+            xdr::OperationBody::RestoreFootprint(_)
+        ));
+    }
+
+    #[test]
+    fn test_restore_preamble_errors() {
+        let mut source = Account::new(SOURCE, "1").unwrap();
+        let invalid = RestorePreamble {
+            transaction_data: "not xdr".to_string(),
+            min_resource_fee: "1".to_string(),
+        };
+        assert!(invalid
+            .restore_transaction(&mut source, Networks::testnet(), 100, 30)
+            .is_err());
+        assert_eq!(
+            preamble(vec![], 0).soroban_data().unwrap_err(),
+            "restore preamble has no archived entries"
+        );
+        let json = r#"{"transactionData":"AAAA","minResourceFee":"100"}"#;
+        let parsed: RestorePreamble = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.min_resource_fee, "100");
+        assert_eq!(source.sequence_number(), "1");
+    }
+}
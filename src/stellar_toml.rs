@@ -0,0 +1,352 @@
+//! Typed model of the SEP-1 `stellar.toml` file.
+//!
+//! See <https://github.com/stellar/stellar-protocol/blob/master/ecosystem/sep-0001.md>.
+use serde::{Deserialize, Serialize};
+
+use crate::address::{Address, AddressTrait};
+use crate::asset::{Asset, AssetBehavior};
+use crate::keypair::{Keypair, KeypairBehavior};
+
+/// A parsed `stellar.toml` document. Fields that are not modelled are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct StellarToml {
+    pub version: Option<String>,
+    pub network_passphrase: Option<String>,
+    pub federation_server: Option<String>,
+    pub auth_server: Option<String>,
+    pub transfer_server: Option<String>,
+    #[serde(rename = "TRANSFER_SERVER_SEP0024")]
+    pub transfer_server_sep0024: Option<String>,
+    pub kyc_server: Option<String>,
+    pub web_auth_endpoint: Option<String>,
+    pub signing_key: Option<String>,
+    pub horizon_url: Option<String>,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    pub uri_request_signing_key: Option<String>,
+    pub direct_payment_server: Option<String>,
+    pub anchor_quote_server: Option<String>,
+    pub documentation: Option<Documentation>,
+    #[serde(default)]
+    pub principals: Vec<PointOfContact>,
+    #[serde(default)]
+    pub currencies: Vec<Currency>,
+    #[serde(default)]
+    pub validators: Vec<Validator>,
+}
+
+/// The `[DOCUMENTATION]` table describing the organization.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct Documentation {
+    pub org_name: Option<String>,
+    pub org_dba: Option<String>,
+    pub org_url: Option<String>,
+    pub org_logo: Option<String>,
+    pub org_description: Option<String>,
+    pub org_physical_address: Option<String>,
+    pub org_physical_address_attestation: Option<String>,
+    pub org_phone_number: Option<String>,
+    pub org_phone_number_attestation: Option<String>,
+    pub org_keybase: Option<String>,
+    pub org_twitter: Option<String>,
+    pub org_github: Option<String>,
+    pub org_official_email: Option<String>,
+    pub org_support_email: Option<String>,
+    pub org_licensing_authority: Option<String>,
+    pub org_license_type: Option<String>,
+    pub org_license_number: Option<String>,
+}
+
+/// An entry of the `[[PRINCIPALS]]` list.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PointOfContact {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub keybase: Option<String>,
+    pub telegram: Option<String>,
+    pub twitter: Option<String>,
+    pub github: Option<String>,
+    pub id_photo_hash: Option<String>,
+    pub verification_photo_hash: Option<String>,
+}
+
+/// An entry of the `[[CURRENCIES]]` list.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Currency {
+    pub code: Option<String>,
+    pub code_template: Option<String>,
+    pub issuer: Option<String>,
+    pub contract: Option<String>,
+    pub status: Option<String>,
+    pub display_decimals: Option<u8>,
+    pub name: Option<String>,
+    pub desc: Option<String>,
+    pub conditions: Option<String>,
+    pub image: Option<String>,
+    pub fixed_number: Option<u64>,
+    pub max_number: Option<u64>,
+    pub is_unlimited: Option<bool>,
+    pub is_asset_anchored: Option<bool>,
+    pub anchor_asset_type: Option<String>,
+    pub anchor_asset: Option<String>,
+    pub attestation_of_reserve: Option<String>,
+    pub redemption_instructions: Option<String>,
+    #[serde(default)]
+    pub collateral_addresses: Vec<String>,
+    pub regulated: Option<bool>,
+    pub approval_server: Option<String>,
+    pub approval_criteria: Option<String>,
+}
+
+/// An entry of the `[[VALIDATORS]]` list.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct Validator {
+    pub alias: Option<String>,
+    pub display_name: Option<String>,
+    pub public_key: Option<String>,
+    pub host: Option<String>,
+    pub history: Option<String>,
+}
+
+// Define a trait for StellarToml behavior
+pub trait StellarTomlBehavior {
+    fn parse(toml: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn validate(&self) -> Result<(), String>;
+    fn assets(&self) -> Result<Vec<Asset>, String>;
+}
+
+// Define a trait for Currency behavior
+pub trait CurrencyBehavior {
+    fn to_asset(&self) -> Result<Asset, String>;
+}
+
+fn validate_public_key(field: &str, key: &str) -> Result<(), String> {
+    Keypair::from_public_key(key)
+        .map(|_| ())
+        .map_err(|_| format!("{} is not a valid public key: {}", field, key))
+}
+
+// Contract tokens are listed with a `code` and a `contract` but no `issuer`
+fn is_classic(currency: &Currency) -> bool {
+    currency.code.is_some() && !(currency.contract.is_some() && currency.issuer.is_none())
+}
+
+impl StellarTomlBehavior for StellarToml {
+    /// Parses and validates a `stellar.toml` document.
+    fn parse(toml: &str) -> Result<Self, String> {
+        let stellar_toml: Self = toml::from_str(toml).map_err(|e| e.to_string())?;
+        stellar_toml.validate()?;
+        Ok(stellar_toml)
+    }
+
+    /// Checks that every account, signing key and currency is well formed.
+    fn validate(&self) -> Result<(), String> {
+        for account in &self.accounts {
+            validate_public_key("ACCOUNTS", account)?;
+        }
+        if let Some(key) = &self.signing_key {
+            validate_public_key("SIGNING_KEY", key)?;
+        }
+        if let Some(key) = &self.uri_request_signing_key {
+            validate_public_key("URI_REQUEST_SIGNING_KEY", key)?;
+        }
+        for currency in &self.currencies {
+            if is_classic(currency) {
+                currency.to_asset()?;
+            }
+            if let Some(contract) = &currency.contract {
+                match Address::new(contract) {
+                    Ok(_) if contract.starts_with('C') => {}
+                    _ => return Err(format!("CURRENCIES contract is not valid: {}", contract)),
+                }
+            }
+        }
+        for validator in &self.validators {
+            if let Some(key) = &validator.public_key {
+                validate_public_key("VALIDATORS PUBLIC_KEY", key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the classic assets listed under `[[CURRENCIES]]`, skipping templates and
+    /// contract-only tokens.
+    fn assets(&self) -> Result<Vec<Asset>, String> {
+        self.currencies
+            .iter()
+            .filter(|currency| is_classic(currency))
+            .map(CurrencyBehavior::to_asset)
+            .collect()
+    }
+}
+
+impl CurrencyBehavior for Currency {
+    fn to_asset(&self) -> Result<Asset, String> {
+        let code = self.code.as_deref().ok_or("CURRENCIES entry has no code")?;
+        match &self.issuer {
+            None if code == "XLM" || code == "native" => Ok(Asset::native()),
+            None => Err(format!("CURRENCIES entry {} has no issuer", code)),
+            Some(issuer) => {
+                validate_public_key("CURRENCIES issuer", issuer)?;
+                Asset::new(code, Some(issuer))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISSUER: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const SIGNER: &str = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+
+    fn example() -> String {
+        format!(
+            r#"
+VERSION = "2.0.0"
+NETWORK_PASSPHRASE = "Public Global Stellar Network ; September 2015"
+FEDERATION_SERVER = "https://stellar.example.com/federation"
+WEB_AUTH_ENDPOINT = "https://auth.example.com"
+SIGNING_KEY = "{signer}"
+URI_REQUEST_SIGNING_KEY = "{signer}"
+ACCOUNTS = ["{issuer}", "{signer}"]
+UNKNOWN_FIELD = 42
+
+[DOCUMENTATION]
+ORG_NAME = "Example Organization"
+ORG_URL = "https://example.com"
+ORG_OFFICIAL_EMAIL = "info@example.com"
+
+[[CURRENCIES]]
+code = "USD"
+issuer = "{issuer}"
+display_decimals = 2
+is_asset_anchored = true
+anchor_asset_type = "fiat"
+
+[[CURRENCIES]]
+code_template = "BTC????????"
+issuer = "{issuer}"
+
+[[CURRENCIES]]
+contract = "CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXE"
+
+[[CURRENCIES]]
+code = "TOKEN"
+contract = "CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXE"
+display_decimals = 7
+
+[[VALIDATORS]]
+ALIAS = "example-1"
+DISPLAY_NAME = "Example 1"
+PUBLIC_KEY = "{signer}"
+HOST = "core.example.com:11625"
+HISTORY = "https://history.example.com/core1/"
+"#,
+            issuer = ISSUER,
+            signer = SIGNER
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        let stellar_toml = StellarToml::parse(&example()).unwrap();
+
+        assert_eq!(stellar_toml.version.as_deref(), Some("2.0.0"));
+        assert_eq!(stellar_toml.signing_key.as_deref(), Some(SIGNER));
+        assert_eq!(
+            stellar_toml.web_auth_endpoint.as_deref(),
+            Some("https://auth.example.com")
+        );
+        assert_eq!(stellar_toml.accounts, vec![ISSUER, SIGNER]);
+        assert_eq!(
+            stellar_toml.documentation.unwrap().org_name.as_deref(),
+            Some("Example Organization")
+        );
+        assert_eq!(stellar_toml.currencies.len(), 4);
+        assert_eq!(stellar_toml.currencies[3].code.as_deref(), Some("TOKEN"));
+        assert_eq!(
+            stellar_toml.currencies[3].contract.as_deref(),
+            Some("CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXE")
+        );
+        assert_eq!(stellar_toml.currencies[0].display_decimals, Some(2));
+        assert_eq!(
+            stellar_toml.currencies[1].code_template.as_deref(),
+            Some("BTC????????")
+        );
+        assert_eq!(
+            stellar_toml.validators[0].alias.as_deref(),
+            Some("example-1")
+        );
+    }
+
+    #[test]
+    fn test_assets() {
+        let stellar_toml = StellarToml::parse(&example()).unwrap();
+
+        // the TOKEN contract currency is not a classic asset
+        assert_eq!(
+            stellar_toml.assets().unwrap(),
+            vec![Asset::new("USD", Some(ISSUER)).unwrap()]
+        );
+        let native = Currency {
+            code: Some("XLM".into()),
+            ..Default::default()
+        };
+        assert!(native.to_asset().unwrap().is_native());
+    }
+
+    #[test]
+    fn test_contract_currency() {
+        let stellar_toml = StellarToml::parse(
+            "[[CURRENCIES]]\ncode = \"TOKEN\"\ncontract = \"CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXE\"",
+        )
+        .unwrap();
+        assert!(stellar_toml.assets().unwrap().is_empty());
+
+        let err = StellarToml::parse("[[CURRENCIES]]\ncode = \"TOKEN\"\ncontract = \"CABC\"")
+            .unwrap_err();
+        assert_eq!(err, "CURRENCIES contract is not valid: CABC");
+    }
+
+    #[test]
+    fn test_empty_document() {
+        assert_eq!(StellarToml::parse("").unwrap(), StellarToml::default());
+    }
+
+    #[test]
+    fn test_rejects_invalid_keys() {
+        let err = StellarToml::parse("SIGNING_KEY = \"GABC\"").unwrap_err();
+        assert_eq!(err, "SIGNING_KEY is not a valid public key: GABC");
+
+        let err =
+            StellarToml::parse(&format!("ACCOUNTS = [\"{}\", \"nope\"]", ISSUER)).unwrap_err();
+        assert_eq!(err, "ACCOUNTS is not a valid public key: nope");
+
+        let err =
+            StellarToml::parse("[[CURRENCIES]]\ncode = \"USD\"\nissuer = \"GABC\"").unwrap_err();
+        assert_eq!(err, "CURRENCIES issuer is not a valid public key: GABC");
+
+        let err = StellarToml::parse("[[CURRENCIES]]\ncode = \"USD\"").unwrap_err();
+        assert_eq!(err, "CURRENCIES entry USD has no issuer");
+
+        let err =
+            StellarToml::parse(&format!("[[CURRENCIES]]\ncontract = \"{}\"", ISSUER)).unwrap_err();
+        assert_eq!(err, format!("CURRENCIES contract is not valid: {}", ISSUER));
+
+        let err = StellarToml::parse("[[VALIDATORS]]\nPUBLIC_KEY = \"GABC\"").unwrap_err();
+        assert_eq!(err, "VALIDATORS PUBLIC_KEY is not a valid public key: GABC");
+    }
+
+    #[test]
+    fn test_rejects_malformed_toml() {
+        assert!(StellarToml::parse("ACCOUNTS = [").is_err());
+        assert!(StellarToml::parse("ACCOUNTS = \"not a list\"").is_err());
+    }
+}
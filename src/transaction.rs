@@ -11,9 +11,11 @@ use xdr::Limits;
 use xdr::SorobanTransactionData;
 
 use crate::account::Account;
+use crate::claimable_balance_id::{ClaimableBalanceId, ClaimableBalanceIdBehavior};
 use crate::hashing::Sha256Hasher;
 use crate::keypair::Keypair;
 use crate::keypair::KeypairBehavior;
+use crate::txrep;
 use crate::xdr;
 use crate::xdr::ReadXdr;
 use crate::xdr::WriteXdr;
@@ -47,10 +49,127 @@ pub trait TransactionBehavior {
     fn sign(&mut self, keypairs: &[Keypair]);
     fn to_envelope(&self) -> Result<xdr::TransactionEnvelope, Box<dyn Error>>;
     fn from_xdr_envelope(xdr: &str, network: &str) -> Self;
+    fn get_claimable_balance_id(&self, op_index: usize) -> Result<ClaimableBalanceId, String>;
+    fn to_txrep(&self) -> Result<String, Box<dyn Error>>;
+    fn from_txrep(txrep: &str, network: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn pre_auth_signer_key(&self) -> xdr::SignerKey;
+    fn sign_hashx(&mut self, preimage: &[u8]) -> Result<(), String>;
     //TODO: XDR Conversion, Proper From and To
 }
 
 impl Transaction {
+    // Whether `to_envelope` can encode the transaction without panicking
+    fn is_encodable(&self) -> bool {
+        let source = self.source.as_deref().map(xdr::MuxedAccount::from_str);
+        let sequence = self.sequence.as_deref().map(str::parse::<i64>);
+        matches!(
+            self.envelope_type,
+            xdr::EnvelopeType::TxV0 | xdr::EnvelopeType::Tx
+        ) && matches!(source, Some(Ok(_)))
+            && matches!(sequence, Some(Ok(_)))
+            && self.operations.as_ref().map_or(0, Vec::len) <= 100
+            && self.signatures.len() <= 20
+    }
+
+    fn write_fields(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transaction {{")?;
+
+        // Network information
+        writeln!(f, "  Network: {}", self.network_passphrase)?;
+
+        // Source account
+        if let Some(source) = &self.source {
+            writeln!(f, "  Source Account: {}", source)?;
+        }
+
+        // Fee
+        writeln!(f, "  Fee: {}", self.fee)?;
+
+        // Sequence number
+        if let Some(sequence) = &self.sequence {
+            writeln!(f, "  Sequence Number: {}", sequence)?;
+        }
+
+        // Memo
+        if let Some(memo) = &self.memo {
+            write!(f, "  Memo: ")?;
+            match memo {
+                xdr::Memo::Text(text) => writeln!(f, "TEXT: {:?}", text)?,
+                xdr::Memo::Id(id) => writeln!(f, "ID: {}", id)?,
+                xdr::Memo::Hash(hash) => writeln!(f, "HASH: {:?}", hash)?,
+                xdr::Memo::Return(ret) => writeln!(f, "RETURN: {:?}", ret)?,
+                xdr::Memo::None => writeln!(f, "NONE")?,
+            }
+        }
+
+        // Time bounds
+        if let Some(time_bounds) = &self.time_bounds {
+            writeln!(f, "  Time Bounds: {{")?;
+            writeln!(f, "    Min Time: {:?}", time_bounds.min_time)?;
+            writeln!(f, "    Max Time: {:?}", time_bounds.max_time)?;
+            writeln!(f, "  }}")?;
+        }
+
+        // Ledger bounds
+        if let Some(ledger_bounds) = &self.ledger_bounds {
+            writeln!(f, "  Ledger Bounds: {{")?;
+            writeln!(f, "    Min Ledger: {}", ledger_bounds.min_ledger)?;
+            writeln!(f, "    Max Ledger: {}", ledger_bounds.max_ledger)?;
+            writeln!(f, "  }}")?;
+        }
+
+        // Min account sequence
+        if let Some(min_seq) = &self.min_account_sequence {
+            writeln!(f, "  Min Account Sequence: {}", min_seq)?;
+        }
+
+        // Min account sequence age
+        if let Some(age) = &self.min_account_sequence_age {
+            writeln!(f, "  Min Account Sequence Age: {}", age)?;
+        }
+
+        // Min account sequence ledger gap
+        if let Some(gap) = &self.min_account_sequence_ledger_gap {
+            writeln!(f, "  Min Account Sequence Ledger Gap: {}", gap)?;
+        }
+
+        // Operations
+        if let Some(operations) = &self.operations {
+            writeln!(f, "  Operations: [")?;
+            for (i, op) in operations.iter().enumerate() {
+                writeln!(f, "    {}. {:?}", i + 1, op)?;
+            }
+            writeln!(f, "  ]")?;
+        }
+
+        // Signatures
+        writeln!(f, "  Signatures: [")?;
+        for (i, sig) in self.signatures.iter().enumerate() {
+            writeln!(
+                f,
+                "    {}. Hint: {:?}, Signature: {:?}",
+                i + 1,
+                sig.hint,
+                sig.signature
+            )?;
+        }
+        writeln!(f, "  ]")?;
+
+        // Transaction hash
+        if let Some(hash) = &self.hash {
+            writeln!(f, "  Hash: {:?}", hash)?;
+        }
+
+        // Soroban data
+        if let Some(soroban_data) = &self.soroban_data {
+            writeln!(f, "  Soroban Data: {:?}", soroban_data)?;
+        }
+
+        write!(f, "}}")
+    }
+
     fn to_tx(&self) -> xdr::Transaction {
         let cond = match (&self.time_bounds, &self.ledger_bounds) {
             (None, None) => xdr::Preconditions::None,
@@ -154,8 +273,20 @@ impl TransactionBehavior for Transaction {
 
         let envelope = match self.envelope_type {
             xdr::EnvelopeType::TxV0 => {
+                let tx = self.to_tx();
+                let xdr::MuxedAccount::Ed25519(source_account_ed25519) = tx.source_account else {
+                    return Err("v0 transactions cannot have a muxed source account".into());
+                };
                 let transaction_v0 = xdr::TransactionV0Envelope {
-                    tx: xdr::TransactionV0::from_xdr_base64(&raw_tx, xdr::Limits::none()).unwrap(), // Make a copy of tx
+                    tx: xdr::TransactionV0 {
+                        source_account_ed25519,
+                        fee: tx.fee,
+                        seq_num: tx.seq_num,
+                        time_bounds: self.time_bounds.clone(),
+                        memo: tx.memo,
+                        operations: tx.operations,
+                        ext: xdr::TransactionV0Ext::V0,
+                    },
                     signatures,
                 };
                 xdr::TransactionEnvelope::TxV0(transaction_v0)
@@ -253,110 +384,84 @@ impl TransactionBehavior for Transaction {
                     extra_signers: None,
                     operations: Some(tx_env.tx.operations.to_vec()),
                     hash: None,
-                    soroban_data: None,
+                    soroban_data: match tx_env.tx.ext {
+                        xdr::TransactionExt::V1(data) => Some(data),
+                        xdr::TransactionExt::V0 => None,
+                    },
                 }
             }
             _ => panic!("Invalid envelope type"),
         }
     }
-}
-
-impl fmt::Display for Transaction {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Transaction {{")?;
-
-        // Network information
-        writeln!(f, "  Network: {}", self.network_passphrase)?;
 
-        // Source account
-        if let Some(source) = &self.source {
-            writeln!(f, "  Source Account: {}", source)?;
+    /// Computes the id of the claimable balance created by the `create_claimable_balance`
+    /// operation at `op_index`, before the transaction is submitted.
+    fn get_claimable_balance_id(&self, op_index: usize) -> Result<ClaimableBalanceId, String> {
+        let operations = self.operations.clone().unwrap_or_default();
+        match operations.get(op_index) {
+            Some(xdr::Operation {
+                body: xdr::OperationBody::CreateClaimableBalance(_),
+                ..
+            }) => {}
+            Some(_) => return Err("expected a create_claimable_balance operation".to_string()),
+            None => return Err("operation index is out of bounds".to_string()),
         }
 
-        // Fee
-        writeln!(f, "  Fee: {}", self.fee)?;
-
-        // Sequence number
-        if let Some(sequence) = &self.sequence {
-            writeln!(f, "  Sequence Number: {}", sequence)?;
-        }
-
-        // Memo
-        if let Some(memo) = &self.memo {
-            write!(f, "  Memo: ")?;
-            match memo {
-                xdr::Memo::Text(text) => writeln!(f, "TEXT: {:?}", text)?,
-                xdr::Memo::Id(id) => writeln!(f, "ID: {}", id)?,
-                xdr::Memo::Hash(hash) => writeln!(f, "HASH: {:?}", hash)?,
-                xdr::Memo::Return(ret) => writeln!(f, "RETURN: {:?}", ret)?,
-                xdr::Memo::None => writeln!(f, "NONE")?,
-            }
-        }
-
-        // Time bounds
-        if let Some(time_bounds) = &self.time_bounds {
-            writeln!(f, "  Time Bounds: {{")?;
-            writeln!(f, "    Min Time: {:?}", time_bounds.min_time)?;
-            writeln!(f, "    Max Time: {:?}", time_bounds.max_time)?;
-            writeln!(f, "  }}")?;
-        }
-
-        // Ledger bounds
-        if let Some(ledger_bounds) = &self.ledger_bounds {
-            writeln!(f, "  Ledger Bounds: {{")?;
-            writeln!(f, "    Min Ledger: {}", ledger_bounds.min_ledger)?;
-            writeln!(f, "    Max Ledger: {}", ledger_bounds.max_ledger)?;
-            writeln!(f, "  }}")?;
-        }
-
-        // Min account sequence
-        if let Some(min_seq) = &self.min_account_sequence {
-            writeln!(f, "  Min Account Sequence: {}", min_seq)?;
-        }
-
-        // Min account sequence age
-        if let Some(age) = &self.min_account_sequence_age {
-            writeln!(f, "  Min Account Sequence Age: {}", age)?;
-        }
+        let source = self.source.as_ref().ok_or("transaction has no source")?;
+        let sequence = self
+            .sequence
+            .as_ref()
+            .ok_or("transaction has no sequence")?;
+        let op_index = u32::try_from(op_index).map_err(|e| e.to_string())?;
+        ClaimableBalanceId::new(source, sequence, op_index)
+    }
 
-        // Min account sequence ledger gap
-        if let Some(gap) = &self.min_account_sequence_ledger_gap {
-            writeln!(f, "  Min Account Sequence Ledger Gap: {}", gap)?;
-        }
+    /// Returns the pre-authorized transaction signer for this transaction. Add it to the
+    /// source account before submitting; it is removed once the transaction is applied.
+    fn pre_auth_signer_key(&self) -> xdr::SignerKey {
+        xdr::SignerKey::PreAuthTx(xdr::Uint256(self.hash()))
+    }
 
-        // Operations
-        if let Some(operations) = &self.operations {
-            writeln!(f, "  Operations: [")?;
-            for (i, op) in operations.iter().enumerate() {
-                writeln!(f, "    {}. {:?}", i + 1, op)?;
-            }
-            writeln!(f, "  ]")?;
-        }
+    /// Adds the signature revealing `preimage` for a hash(x) signer.
+    fn sign_hashx(&mut self, preimage: &[u8]) -> Result<(), String> {
+        let signature = xdr::Signature::try_from(preimage.to_vec())
+            .map_err(|_| "preimage must be at most 64 bytes".to_string())?;
+        let hash = Sha256Hasher::hash(preimage);
+        let mut hint = [0u8; 4];
+        hint.copy_from_slice(&hash[28..]);
+
+        self.signatures.push(DecoratedSignature {
+            hint: xdr::SignatureHint(hint),
+            signature,
+        });
+        Ok(())
+    }
 
-        // Signatures
-        writeln!(f, "  Signatures: [")?;
-        for (i, sig) in self.signatures.iter().enumerate() {
-            writeln!(
-                f,
-                "    {}. Hint: {:?}, Signature: {:?}",
-                i + 1,
-                sig.hint,
-                sig.signature
-            )?;
-        }
-        writeln!(f, "  ]")?;
+    /// Renders the transaction envelope in the SEP-11 Txrep format.
+    fn to_txrep(&self) -> Result<String, Box<dyn Error>> {
+        Ok(txrep::to_txrep(&self.to_envelope()?))
+    }
 
-        // Transaction hash
-        if let Some(hash) = &self.hash {
-            writeln!(f, "  Hash: {:?}", hash)?;
+    /// Parses a transaction from its SEP-11 Txrep. Fee bump envelopes are rejected.
+    fn from_txrep(txrep: &str, network: &str) -> Result<Self, String> {
+        let envelope = txrep::from_txrep(txrep)?;
+        if let xdr::TransactionEnvelope::TxFeeBump(_) = envelope {
+            return Err("fee bump envelopes are not supported".to_string());
         }
+        let envelope = envelope
+            .to_xdr_base64(Limits::none())
+            .map_err(|e| e.to_string())?;
+        Ok(Self::from_xdr_envelope(&envelope, network))
+    }
+}
 
-        // Soroban data
-        if let Some(soroban_data) = &self.soroban_data {
-            writeln!(f, "  Soroban Data: {:?}", soroban_data)?;
+impl fmt::Display for Transaction {
+    /// Renders the transaction as Txrep, or as a plain field listing when it cannot be encoded.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.is_encodable().then(|| self.to_txrep()) {
+            Some(Ok(txrep)) => f.write_str(&txrep),
+            _ => self.write_fields(f),
         }
-
-        write!(f, "}}")
     }
 }
 
@@ -374,6 +479,7 @@ mod tests {
     use crate::{
         account::{Account, AccountBehavior},
         asset::{Asset, AssetBehavior},
+        claimant::{Claimant, ClaimantBehavior},
         keypair::{self, Keypair},
         network::{NetworkPassphrase, Networks},
         operation::{self, Operation},
@@ -401,6 +507,7 @@ mod tests {
                     .unwrap(),
             )
             .add_memo("Happy birthday!")
+            .unwrap()
             .set_timeout(TIMEOUT_INFINITE)
             .unwrap()
             .build();
@@ -418,7 +525,7 @@ mod tests {
             )
             .build();
 
-        tx.sign(&[signer.clone()]);
+        tx.sign(std::slice::from_ref(&signer));
         let sig = &tx.signatures[0].signature.0;
         let verified = signer.verify(&tx.hash(), sig);
         assert!(verified);
@@ -441,6 +548,108 @@ mod tests {
         assert_eq!(source_account.0.len(), 32);
     }
 
+    #[test]
+    fn computes_claimable_balance_id() {
+        let mut source = Account::new(
+            "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB",
+            "1234",
+        )
+        .unwrap();
+        let claimant = Claimant::new(
+            Some("GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2"),
+            None,
+        )
+        .unwrap();
+
+        let tx = TransactionBuilder::new(&mut source, Networks::testnet(), None)
+            .fee(100_u32)
+            .add_operation(Operation::new().bump_sequence(1).unwrap())
+            .add_operation(
+                Operation::new()
+                    .create_claimable_balance(&Asset::native(), 10 * operation::ONE, vec![claimant])
+                    .unwrap(),
+            )
+            .build();
+
+        let balance_id = tx.get_claimable_balance_id(1).unwrap();
+        assert_eq!(
+            balance_id,
+            ClaimableBalanceId::new(
+                "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB",
+                "1235",
+                1
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            tx.get_claimable_balance_id(0).unwrap_err(),
+            "expected a create_claimable_balance operation"
+        );
+        assert_eq!(
+            tx.get_claimable_balance_id(2).unwrap_err(),
+            "operation index is out of bounds"
+        );
+    }
+
+    #[test]
+    fn builds_pre_auth_and_hash_x_signatures() {
+        let mut source = Account::new(
+            "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB",
+            "1234",
+        )
+        .unwrap();
+        let mut tx = TransactionBuilder::new(&mut source, Networks::testnet(), None)
+            .fee(100_u32)
+            .add_operation(Operation::new().bump_sequence(1).unwrap())
+            .build();
+
+        assert_eq!(
+            tx.pre_auth_signer_key(),
+            xdr::SignerKey::PreAuthTx(xdr::Uint256(tx.hash()))
+        );
+
+        let preimage = b"swap secret";
+        tx.sign_hashx(preimage).unwrap();
+        let hash = Sha256Hasher::hash(preimage);
+        assert_eq!(tx.signatures[0].hint.0, hash[28..]);
+        assert_eq!(tx.signatures[0].signature.0.as_slice(), preimage);
+        assert_eq!(
+            tx.sign_hashx(&[0; 65]).unwrap_err(),
+            "preimage must be at most 64 bytes"
+        );
+    }
+
+    #[test]
+    fn round_trips_through_txrep() {
+        let mut source = Account::new(
+            "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB",
+            "1234",
+        )
+        .unwrap();
+        let signer = Keypair::random().unwrap();
+        let mut tx = TransactionBuilder::new(&mut source, Networks::testnet(), None)
+            .fee(100_u32)
+            .add_operation(Operation::new().bump_sequence(1).unwrap())
+            .build();
+        tx.sign(std::slice::from_ref(&signer));
+
+        let txrep = tx.to_txrep().unwrap();
+        assert!(txrep.contains("tx.operations[0].body.type: BUMP_SEQUENCE\n"));
+        assert_eq!(tx.to_string(), txrep);
+
+        // transactions that cannot be encoded still display
+        let mut incomplete = tx.clone();
+        incomplete.sequence = None;
+        assert!(incomplete.to_string().starts_with("Transaction {\n"));
+        incomplete.sequence = tx.sequence.clone();
+        incomplete.envelope_type = xdr::EnvelopeType::Scp;
+        assert!(incomplete.to_string().contains("  Fee: 100\n"));
+
+        let parsed = Transaction::from_txrep(&txrep, Networks::testnet()).unwrap();
+        assert_eq!(parsed.hash(), tx.hash());
+        assert_eq!(parsed.signatures, tx.signatures);
+    }
+
     #[test]
     fn calculates_correct_hash_with_non_utf8_strings() {
         let xdr = "AAAAAAtjwtJadppTmm0NtAU99BFxXXfzPO1N/SqR43Z8aXqXAAAAZAAIj6YAAAACAAAAAAAAAAEAAAAB0QAAAAAAAAEAAAAAAAAAAQAAAADLa6390PDAqg3qDLpshQxS+uVw3ytSgKRirQcInPWt1QAAAAAAAAAAA1Z+AAAAAAAAAAABfGl6lwAAAEBC655+8Izq54MIZrXTVF/E1ycHgQWpVcBD+LFkuOjjJd995u/7wM8sFqQqambL0/ME2FTOtxMO65B9i3eAIu4P";
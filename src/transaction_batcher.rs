@@ -0,0 +1,383 @@
+//! Splits long operation lists into as many transactions as needed.
+//!
+//! Operations are packed in order into transactions of at most `max_operations` operations
+//! and, optionally, `max_size` bytes of unsigned envelope XDR. Sponsorship sandwiches and
+//! groups added with `add_group` are never split across transactions.
+use std::str::FromStr;
+
+use crate::account::{Account, AccountBehavior};
+use crate::transaction::Transaction;
+use crate::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+use crate::xdr::{self, Limits, WriteXdr};
+
+/// The most operations a transaction can hold.
+pub const MAX_OPERATIONS: usize = 100;
+
+/// Builds a series of transactions sharing fee, memo and timeout from a list of operations.
+pub struct TransactionBatcher {
+    network_passphrase: String,
+    fee: u32,
+    memo: Option<xdr::Memo>,
+    timeout: Option<i64>,
+    max_operations: usize,
+    max_size: Option<usize>,
+    groups: Vec<Vec<xdr::Operation>>,
+    open_group: Vec<xdr::Operation>,
+    open_sponsorships: usize,
+}
+
+// Define a trait for TransactionBatcher behavior
+pub trait TransactionBatcherBehavior {
+    fn new(network: &str, fee: u32) -> Self;
+    fn memo(&mut self, memo: xdr::Memo) -> &mut Self;
+    fn set_timeout(&mut self, timeout_seconds: i64) -> Result<&mut Self, String>;
+    fn max_operations(&mut self, max_operations: usize) -> Result<&mut Self, String>;
+    fn max_size(&mut self, max_size: usize) -> &mut Self;
+    fn add_operation(&mut self, operation: xdr::Operation) -> &mut Self;
+    fn add_operations<I>(&mut self, operations: I) -> &mut Self
+    where
+        I: IntoIterator<Item = xdr::Operation>;
+    fn add_group(&mut self, operations: Vec<xdr::Operation>) -> &mut Self;
+    fn build(&mut self, source: &mut Account) -> Result<Vec<Transaction>, String>;
+}
+
+fn xdr_len<T: WriteXdr>(value: &T) -> Result<usize, String> {
+    value
+        .to_xdr(Limits::none())
+        .map(|bytes| bytes.len())
+        .map_err(|e| e.to_string())
+}
+
+impl TransactionBatcher {
+    fn push_group(&mut self, operations: Vec<xdr::Operation>) {
+        if self.open_sponsorships > 0 {
+            self.open_group.extend(operations);
+        } else if !operations.is_empty() {
+            self.groups.push(operations);
+        }
+    }
+
+    /// The size of an unsigned envelope without operations, with room for time bounds.
+    fn envelope_overhead(&self, source: &Account) -> Result<usize, String> {
+        let tx = xdr::Transaction {
+            source_account: xdr::MuxedAccount::from_str(&source.account_id())
+                .map_err(|e| e.to_string())?,
+            fee: 0,
+            seq_num: xdr::SequenceNumber(0),
+            cond: xdr::Preconditions::Time(xdr::TimeBounds {
+                min_time: xdr::TimePoint(0),
+                max_time: xdr::TimePoint(0),
+            }),
+            memo: self.memo.clone().unwrap_or(xdr::Memo::None),
+            operations: xdr::VecM::default(),
+            ext: xdr::TransactionExt::V0,
+        };
+        let envelope = xdr::TransactionEnvelope::Tx(xdr::TransactionV1Envelope {
+            tx,
+            signatures: xdr::VecM::default(),
+        });
+        xdr_len(&envelope)
+    }
+
+    /// Packs the queued groups into batches of operations, one per transaction.
+    fn pack(&self, source: &Account) -> Result<Vec<Vec<xdr::Operation>>, String> {
+        let overhead = self.envelope_overhead(source)?;
+        let mut batches: Vec<Vec<xdr::Operation>> = Vec::new();
+        let mut size = overhead;
+
+        for group in &self.groups {
+            let group_size = group.iter().map(xdr_len).sum::<Result<usize, _>>()?;
+            let too_big = |ops: usize, size: usize| {
+                ops > self.max_operations || self.max_size.is_some_and(|max| size > max)
+            };
+            if too_big(group.len(), overhead + group_size) {
+                return Err(format!(
+                    "a group of {} operations does not fit in one transaction",
+                    group.len()
+                ));
+            }
+
+            match batches.last_mut() {
+                Some(batch) if !too_big(batch.len() + group.len(), size + group_size) => {
+                    batch.extend(group.iter().cloned());
+                    size += group_size;
+                }
+                _ => {
+                    batches.push(group.clone());
+                    size = overhead + group_size;
+                }
+            }
+        }
+
+        Ok(batches)
+    }
+}
+
+impl TransactionBatcherBehavior for TransactionBatcher {
+    /// Creates a batcher charging `fee` stroops per operation.
+    fn new(network: &str, fee: u32) -> Self {
+        Self {
+            network_passphrase: network.to_string(),
+            fee,
+            memo: None,
+            timeout: None,
+            max_operations: MAX_OPERATIONS,
+            max_size: None,
+            groups: Vec::new(),
+            open_group: Vec::new(),
+            open_sponsorships: 0,
+        }
+    }
+
+    fn memo(&mut self, memo: xdr::Memo) -> &mut Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    fn set_timeout(&mut self, timeout_seconds: i64) -> Result<&mut Self, String> {
+        if timeout_seconds < 0 {
+            return Err("timeout cannot be negative".to_string());
+        }
+        self.timeout = Some(timeout_seconds);
+        Ok(self)
+    }
+
+    fn max_operations(&mut self, max_operations: usize) -> Result<&mut Self, String> {
+        if !(1..=MAX_OPERATIONS).contains(&max_operations) {
+            return Err(format!(
+                "max_operations must be between 1 and {}",
+                MAX_OPERATIONS
+            ));
+        }
+        self.max_operations = max_operations;
+        Ok(self)
+    }
+
+    /// Limits the unsigned envelope XDR of each transaction to `max_size` bytes. Each
+    /// signature added later takes another 72 bytes.
+    fn max_size(&mut self, max_size: usize) -> &mut Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Queues an operation. Operations from a `BeginSponsoringFutureReserves` up to its
+    /// matching `EndSponsoringFutureReserves` are kept in the same transaction.
+    fn add_operation(&mut self, operation: xdr::Operation) -> &mut Self {
+        match operation.body {
+            xdr::OperationBody::BeginSponsoringFutureReserves(_) => {
+                self.open_sponsorships += 1;
+                self.open_group.push(operation);
+            }
+            xdr::OperationBody::EndSponsoringFutureReserves if self.open_sponsorships > 0 => {
+                self.open_sponsorships -= 1;
+                self.open_group.push(operation);
+                if self.open_sponsorships == 0 {
+                    let group = std::mem::take(&mut self.open_group);
+                    self.groups.push(group);
+                }
+            }
+            _ => self.push_group(vec![operation]),
+        }
+        self
+    }
+
+    fn add_operations<I>(&mut self, operations: I) -> &mut Self
+    where
+        I: IntoIterator<Item = xdr::Operation>,
+    {
+        for operation in operations {
+            self.add_operation(operation);
+        }
+        self
+    }
+
+    /// Queues operations that must be submitted in the same transaction.
+    fn add_group(&mut self, operations: Vec<xdr::Operation>) -> &mut Self {
+        self.push_group(operations);
+        self
+    }
+
+    /// Builds the queued operations into transactions with consecutive sequence numbers
+    /// taken from `source`. The queue is emptied; on error `source` is left untouched.
+    fn build(&mut self, source: &mut Account) -> Result<Vec<Transaction>, String> {
+        if self.open_sponsorships > 0 {
+            return Err("sponsorship sandwich has not been ended".to_string());
+        }
+        let batches = self.pack(source)?;
+        let max_batch = batches.iter().map(Vec::len).max().unwrap_or(0);
+        self.fee
+            .checked_mul(max_batch as u32)
+            .ok_or("fee overflows u32")?;
+
+        let mut transactions = Vec::with_capacity(batches.len());
+        for batch in batches {
+            let mut builder = TransactionBuilder::new(source, &self.network_passphrase, None);
+            builder.fee(self.fee);
+            if let Some(memo) = &self.memo {
+                builder.set_memo(memo.clone())?;
+            }
+            if let Some(timeout) = self.timeout {
+                builder.set_timeout(timeout)?;
+            }
+            for operation in batch {
+                builder.add_operation(operation);
+            }
+            transactions.push(builder.build());
+        }
+        self.groups.clear();
+
+        Ok(transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::{Asset, AssetBehavior};
+    use crate::network::{NetworkPassphrase, Networks};
+    use crate::operation::Operation;
+    use crate::transaction::TransactionBehavior;
+
+    const SOURCE: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const DESTINATION: &str = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+
+    fn payment() -> xdr::Operation {
+        Operation::new()
+            .payment(DESTINATION, &Asset::native(), 10)
+            .unwrap()
+    }
+
+    fn sandwich() -> Vec<xdr::Operation> {
+        vec![
+            Operation::new()
+                .begin_sponsoring_future_reserves(DESTINATION)
+                .unwrap(),
+            Operation::with_source(DESTINATION)
+                .unwrap()
+                .bump_sequence(1)
+                .unwrap(),
+            Operation::with_source(DESTINATION)
+                .unwrap()
+                .end_sponsoring_future_reserves()
+                .unwrap(),
+        ]
+    }
+
+    fn op_counts(transactions: &[Transaction]) -> Vec<usize> {
+        transactions
+            .iter()
+            .map(|tx| tx.operations.as_ref().unwrap().len())
+            .collect()
+    }
+
+    #[test]
+    fn test_splits_at_operation_limit_with_consecutive_sequences() {
+        let mut source = Account::new(SOURCE, "10").unwrap();
+        let mut batcher = TransactionBatcher::new(Networks::testnet(), 100);
+        batcher
+            .memo(xdr::Memo::Id(1))
+            .set_timeout(0)
+            .unwrap()
+            .add_operations((0..250).map(|_| payment()));
+
+        let transactions = batcher.build(&mut source).unwrap();
+
+        assert_eq!(op_counts(&transactions), vec![100, 100, 50]);
+        let sequences: Vec<_> = transactions
+            .iter()
+            .map(|tx| tx.sequence.clone().unwrap())
+            .collect();
+        assert_eq!(sequences, vec!["11", "12", "13"]);
+        assert!(transactions
+            .iter()
+            .all(|tx| tx.memo == Some(xdr::Memo::Id(1))));
+        assert_eq!(transactions[2].fee, 5000);
+        assert_eq!(source.sequence_number(), "13");
+        assert!(batcher.build(&mut source).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_keeps_sponsorship_sandwiches_together() {
+        let mut source = Account::new(SOURCE, "1").unwrap();
+        let mut batcher = TransactionBatcher::new(Networks::testnet(), 100);
+        batcher
+            .max_operations(4)
+            .unwrap()
+            .add_operations((0..3).map(|_| payment()))
+            .add_operations(sandwich())
+            .add_operation(payment());
+
+        let transactions = batcher.build(&mut source).unwrap();
+
+        assert_eq!(op_counts(&transactions), vec![3, 4]);
+        assert_eq!(
+            transactions[1].operations.as_ref().unwrap()[..3],
+            sandwich()[..]
+        );
+    }
+
+    #[test]
+    fn test_keeps_groups_together() {
+        let mut source = Account::new(SOURCE, "1").unwrap();
+        let mut batcher = TransactionBatcher::new(Networks::testnet(), 100);
+        batcher
+            .max_operations(3)
+            .unwrap()
+            .add_operation(payment())
+            .add_group(vec![payment(), payment(), payment()])
+            .add_operation(payment());
+
+        let transactions = batcher.build(&mut source).unwrap();
+
+        assert_eq!(op_counts(&transactions), vec![1, 3, 1]);
+    }
+
+    #[test]
+    fn test_respects_size_budget() {
+        let mut source = Account::new(SOURCE, "1").unwrap();
+        let max_size = 1000;
+        let mut batcher = TransactionBatcher::new(Networks::testnet(), 100);
+        batcher
+            .max_size(max_size)
+            .add_operations((0..30).map(|_| payment()));
+
+        let transactions = batcher.build(&mut source).unwrap();
+
+        assert!(transactions.len() > 1);
+        assert_eq!(op_counts(&transactions).iter().sum::<usize>(), 30);
+        for tx in &transactions {
+            let size = xdr_len(&tx.to_envelope().unwrap()).unwrap();
+            assert!(size <= max_size, "{} > {}", size, max_size);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unsplittable_batches() {
+        let mut source = Account::new(SOURCE, "1").unwrap();
+
+        let mut batcher = TransactionBatcher::new(Networks::testnet(), 100);
+        batcher.add_operations(sandwich().into_iter().take(2));
+        assert_eq!(
+            batcher.build(&mut source).unwrap_err(),
+            "sponsorship sandwich has not been ended"
+        );
+
+        let mut batcher = TransactionBatcher::new(Networks::testnet(), 100);
+        batcher
+            .max_operations(2)
+            .unwrap()
+            .add_operations(sandwich());
+        assert_eq!(
+            batcher.build(&mut source).unwrap_err(),
+            "a group of 3 operations does not fit in one transaction"
+        );
+
+        let mut batcher = TransactionBatcher::new(Networks::testnet(), u32::MAX);
+        batcher.add_operations([payment(), payment()]);
+        assert_eq!(batcher.build(&mut source).unwrap_err(), "fee overflows u32");
+        assert!(TransactionBatcher::new(Networks::testnet(), 100)
+            .max_operations(101)
+            .is_err());
+        assert_eq!(source.sequence_number(), "1");
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::hash_map::ValuesMut;
 use std::error::Error;
+use std::fmt;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -10,6 +12,7 @@ use crate::account::Account;
 use crate::account::AccountBehavior;
 use crate::hashing::Sha256Hasher;
 use crate::keypair::Keypair;
+use crate::operation::Operation;
 use crate::transaction::Transaction;
 use crate::utils::decode_encode_muxed_account::decode_address_fully_to_muxed_account;
 use crate::utils::decode_encode_muxed_account::decode_address_to_muxed_account;
@@ -38,6 +41,7 @@ pub struct TransactionBuilder<'a> {
     extra_signers: Option<Vec<xdr::AccountId>>,
     operations: Option<Vec<xdr::Operation>>,
     soroban_data: Option<xdr::SorobanTransactionData>,
+    open_sponsorships: Vec<String>,
 }
 
 // Define a trait for TransactionBuilder behavior
@@ -52,12 +56,21 @@ pub trait TransactionBuilderBehavior<'a> {
     fn fee(&mut self, fee: impl Into<u32>) -> &mut Self;
     fn add_operation(&mut self, operation: xdr::Operation) -> &mut Self;
     fn build(&mut self) -> Transaction;
-    fn add_memo(&mut self, memo_text: &str) -> &mut Self;
+    fn add_memo(&mut self, memo_text: &str) -> Result<&mut Self, String>;
+    fn set_memo<M>(&mut self, memo: M) -> Result<&mut Self, String>
+    where
+        M: TryInto<xdr::Memo>,
+        M::Error: fmt::Display;
     fn set_timeout(&mut self, timeout_seconds: i64) -> Result<&mut Self, String>;
     fn set_time_bounds(&mut self, time_bounds: xdr::TimeBounds) -> &mut Self;
     fn set_ledger_bounds(&mut self, ledger_bounds: xdr::LedgerBounds) -> &mut Self;
     fn set_soroban_data(&mut self, soroban_data: xdr::SorobanTransactionData) -> &mut Self;
     fn clear_operations(&mut self) -> &mut Self;
+    fn begin_sponsoring(&mut self, sponsor: &str, sponsored: &str) -> Result<&mut Self, String>;
+    fn end_sponsoring(&mut self) -> Result<&mut Self, String>;
+    fn sponsored<F>(&mut self, sponsor: &str, sponsored: &str, f: F) -> Result<&mut Self, String>
+    where
+        F: FnOnce(&mut Self);
 }
 
 pub const TIMEOUT_INFINITE: i64 = 0;
@@ -86,6 +99,7 @@ impl<'a> TransactionBuilderBehavior<'a> for TransactionBuilder<'a> {
             extra_signers: None,
             operations: Some(Vec::new()),
             soroban_data: None,
+            open_sponsorships: Vec::new(),
         }
     }
 
@@ -101,11 +115,22 @@ impl<'a> TransactionBuilderBehavior<'a> for TransactionBuilder<'a> {
         self
     }
 
-    fn add_memo(&mut self, memo_text: &str) -> &mut Self {
-        self.memo = Some(xdr::Memo::Text(
-            xdr::StringM::<28>::from_str(memo_text).unwrap(),
-        ));
-        self
+    /// Sets a text memo, failing when the text is longer than 28 bytes.
+    fn add_memo(&mut self, memo_text: &str) -> Result<&mut Self, String> {
+        let text = xdr::StringM::<28>::from_str(memo_text)
+            .map_err(|_| "String is longer than 28 bytes".to_string())?;
+        self.memo = Some(xdr::Memo::Text(text));
+        Ok(self)
+    }
+
+    /// Sets the memo from a `memo::Memo` or an `xdr::Memo`, failing when it is invalid.
+    fn set_memo<M>(&mut self, memo: M) -> Result<&mut Self, String>
+    where
+        M: TryInto<xdr::Memo>,
+        M::Error: fmt::Display,
+    {
+        self.memo = Some(memo.try_into().map_err(|e| e.to_string())?);
+        Ok(self)
     }
 
     fn set_timeout(&mut self, timeout_seconds: i64) -> Result<&mut Self, String> {
@@ -150,9 +175,7 @@ impl<'a> TransactionBuilderBehavior<'a> for TransactionBuilder<'a> {
 
     fn set_ledger_bounds(&mut self, ledger_bounds: xdr::LedgerBounds) -> &mut Self {
         if self.ledger_bounds.is_some() {
-            panic!(
-                "LedgerBounds has been already set - setting ledgerbounds would overwrite it."
-            );
+            panic!("LedgerBounds has been already set - setting ledgerbounds would overwrite it.");
         }
 
         if ledger_bounds.max_ledger > 0 && ledger_bounds.min_ledger > ledger_bounds.max_ledger {
@@ -177,10 +200,118 @@ impl<'a> TransactionBuilderBehavior<'a> for TransactionBuilder<'a> {
 
     fn clear_operations(&mut self) -> &mut Self {
         self.operations = Some(Vec::new());
+        self.open_sponsorships.clear();
         self
     }
 
+    /// Opens a sponsorship sandwich: adds a `begin_sponsoring_future_reserves` operation
+    /// sourced by `sponsor` for the `sponsored` account.
+    ///
+    /// Every reserve required by the following operations is paid by `sponsor` until the
+    /// matching [end_sponsoring](TransactionBuilderBehavior::end_sponsoring). Operations in
+    /// between keep their own source account, so operations performed by the sponsored account
+    /// (e.g. `change_trust`) must be created with [Operation::with_source](crate::operation::Operation::with_source).
+    fn begin_sponsoring(&mut self, sponsor: &str, sponsored: &str) -> Result<&mut Self, String> {
+        if sponsor == sponsored {
+            return Err("an account cannot sponsor itself".to_string());
+        }
+        if self.open_sponsorships.iter().any(|s| s == sponsored) {
+            return Err(format!("{} is already being sponsored", sponsored));
+        }
+
+        let op = Operation::with_source(sponsor)
+            .map_err(|_| "sponsor is invalid".to_string())?
+            .begin_sponsoring_future_reserves(sponsored)
+            .map_err(|_| "sponsored is invalid".to_string())?;
+        // The end operation is sourced by the sponsored account, check it can be one
+        Operation::with_source(sponsored).map_err(|_| "sponsored is invalid".to_string())?;
+
+        self.open_sponsorships.push(sponsored.to_string());
+        Ok(self.add_operation(op))
+    }
+
+    /// Closes the most recently opened sponsorship sandwich with an
+    /// `end_sponsoring_future_reserves` operation sourced by the sponsored account.
+    fn end_sponsoring(&mut self) -> Result<&mut Self, String> {
+        let sponsored = self
+            .open_sponsorships
+            .pop()
+            .ok_or("no sponsorship to end")?;
+        let op = Operation::with_source(&sponsored)
+            .map_err(|_| "sponsored is invalid".to_string())?
+            .end_sponsoring_future_reserves()
+            .map_err(|_| "sponsored is invalid".to_string())?;
+
+        Ok(self.add_operation(op))
+    }
+
+    /// Adds the operations of `f` between a begin and an end sponsoring operation, see
+    /// [begin_sponsoring](TransactionBuilderBehavior::begin_sponsoring).
+    ///
+    /// Operations added by `f` without a source account are sourced by the `sponsored`
+    /// account. On error nothing is added to the builder.
+    ///
+    /// ```rust
+    /// use stellar_baselib::account::{Account, AccountBehavior};
+    /// use stellar_baselib::asset::{Asset, AssetBehavior};
+    /// use stellar_baselib::network::{NetworkPassphrase, Networks};
+    /// use stellar_baselib::operation::Operation;
+    /// use stellar_baselib::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+    ///
+    /// let sponsor = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    /// let new_account = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+    /// let usd = Asset::new("USD", Some(sponsor)).unwrap();
+    ///
+    /// let mut source = Account::new(sponsor, "1").unwrap();
+    /// let tx = TransactionBuilder::new(&mut source, Networks::testnet(), None)
+    ///     .fee(100_u32)
+    ///     .sponsored(sponsor, new_account, |b| {
+    ///         b.add_operation(
+    ///             Operation::with_source(sponsor)
+    ///                 .unwrap()
+    ///                 .create_account(new_account, 0)
+    ///                 .unwrap(),
+    ///         )
+    ///         .add_operation(Operation::new().change_trust(&usd, None).unwrap());
+    ///     })
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// assert_eq!(tx.operations.unwrap().len(), 4);
+    /// ```
+    fn sponsored<F>(&mut self, sponsor: &str, sponsored: &str, f: F) -> Result<&mut Self, String>
+    where
+        F: FnOnce(&mut Self),
+    {
+        let source: xdr::MuxedAccount = sponsored
+            .parse()
+            .map_err(|_| "sponsored is invalid".to_string())?;
+        let depth = self.open_sponsorships.len();
+        let start = self.operations.as_ref().map_or(0, Vec::len);
+        self.begin_sponsoring(sponsor, sponsored)?;
+        f(self);
+        if self.open_sponsorships.len() != depth + 1 {
+            // Leave the builder as it was before the sandwich was opened
+            if let Some(operations) = self.operations.as_mut() {
+                operations.truncate(start);
+            }
+            self.open_sponsorships.truncate(depth);
+            return Err("sponsorships opened inside a sandwich must be closed in it".to_string());
+        }
+        if let Some(operations) = self.operations.as_mut() {
+            operations
+                .iter_mut()
+                .skip(start + 1)
+                .filter(|op| op.source_account.is_none())
+                .for_each(|op| op.source_account = Some(source.clone()));
+        }
+        self.end_sponsoring()
+    }
+
     fn build(&mut self) -> Transaction {
+        if let Some(sponsored) = self.open_sponsorships.last() {
+            panic!("Sponsorship of {} has not been ended", sponsored);
+        }
         let source = self.source.as_mut().expect("Source account not set");
 
         // Increment the sequence number directly on the mutable reference
@@ -303,6 +434,9 @@ impl<'a> TransactionBuilderBehavior<'a> for TransactionBuilder<'a> {
     /// # }
     /// ```
     fn build_for_simulation(&self) -> Transaction {
+        if let Some(sponsored) = self.open_sponsorships.last() {
+            panic!("Sponsorship of {} has not been ended", sponsored);
+        }
         let source = self.source.as_ref().expect("Source account not set");
 
         // Calculate the next sequence number (current + 1) without mutating the account
@@ -379,6 +513,205 @@ impl<'a> TransactionBuilderBehavior<'a> for TransactionBuilder<'a> {
     }
 }
 
+/// Supplies the source account of an `OwnedTransactionBuilder` and stores its incremented
+/// sequence number after `build()`.
+pub trait SequenceProvider {
+    fn with_account<R>(&mut self, f: impl FnOnce(&mut Account) -> R) -> Result<R, String>;
+}
+
+impl SequenceProvider for Account {
+    fn with_account<R>(&mut self, f: impl FnOnce(&mut Account) -> R) -> Result<R, String> {
+        Ok(f(self))
+    }
+}
+
+impl SequenceProvider for Arc<Mutex<Account>> {
+    fn with_account<R>(&mut self, f: impl FnOnce(&mut Account) -> R) -> Result<R, String> {
+        let mut account = self
+            .lock()
+            .map_err(|_| "source account lock is poisoned".to_string())?;
+        Ok(f(&mut account))
+    }
+}
+
+/// A transaction builder that owns its source, so it can be stored, moved across threads
+/// and chained by value. `build()` hands the source back with its sequence number incremented.
+///
+/// ```
+/// use stellar_baselib::account::{Account, AccountBehavior};
+/// use stellar_baselib::asset::{Asset, AssetBehavior};
+/// use stellar_baselib::network::{NetworkPassphrase, Networks};
+/// use stellar_baselib::operation::Operation;
+/// use stellar_baselib::transaction_builder::{
+///     OwnedTransactionBuilder, OwnedTransactionBuilderBehavior,
+/// };
+///
+/// # fn example() -> Result<(), String> {
+/// let source = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+/// let account = Account::new(source, "1")?;
+/// let payment = Operation::new()
+///     .payment(source, &Asset::native(), 100)
+///     .map_err(|e| format!("{:?}", e))?;
+///
+/// let (tx, account) = OwnedTransactionBuilder::new(account, Networks::testnet(), None)
+///     .fee(100_u32)
+///     .add_operation(payment)
+///     .set_timeout(30)?
+///     .build()?;
+/// assert_eq!(account.sequence_number(), "2");
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub struct OwnedTransactionBuilder<S: SequenceProvider = Account> {
+    source: S,
+    network_passphrase: String,
+    fee: Option<u32>,
+    memo: Option<xdr::Memo>,
+    time_bounds: Option<xdr::TimeBounds>,
+    timeout: Option<i64>,
+    ledger_bounds: Option<xdr::LedgerBounds>,
+    soroban_data: Option<xdr::SorobanTransactionData>,
+    operations: Vec<xdr::Operation>,
+}
+
+// Define a trait for OwnedTransactionBuilder behavior
+pub trait OwnedTransactionBuilderBehavior<S: SequenceProvider> {
+    fn new(source: S, network: &str, time_bounds: Option<xdr::TimeBounds>) -> Self;
+    fn fee(self, fee: impl Into<u32>) -> Self;
+    fn add_operation(self, operation: xdr::Operation) -> Self;
+    fn memo(self, memo: xdr::Memo) -> Self;
+    fn set_timeout(self, timeout_seconds: i64) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn set_time_bounds(self, time_bounds: xdr::TimeBounds) -> Self;
+    fn set_ledger_bounds(self, ledger_bounds: xdr::LedgerBounds) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn set_soroban_data(self, soroban_data: xdr::SorobanTransactionData) -> Self;
+    fn build(self) -> Result<(Transaction, S), String>;
+}
+
+impl<S: SequenceProvider> OwnedTransactionBuilderBehavior<S> for OwnedTransactionBuilder<S> {
+    fn new(source: S, network: &str, time_bounds: Option<xdr::TimeBounds>) -> Self {
+        Self {
+            source,
+            network_passphrase: network.to_string(),
+            fee: None,
+            memo: None,
+            time_bounds,
+            timeout: None,
+            ledger_bounds: None,
+            soroban_data: None,
+            operations: Vec::new(),
+        }
+    }
+
+    fn fee(mut self, fee: impl Into<u32>) -> Self {
+        self.fee = Some(fee.into());
+        self
+    }
+
+    fn add_operation(mut self, operation: xdr::Operation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    fn memo(mut self, memo: xdr::Memo) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    /// Sets the maximum time to `timeout_seconds` after `build()` is called, or no upper
+    /// bound for `TIMEOUT_INFINITE`.
+    fn set_timeout(mut self, timeout_seconds: i64) -> Result<Self, String> {
+        if timeout_seconds < 0 {
+            return Err("timeout cannot be negative".to_string());
+        }
+        if self
+            .time_bounds
+            .as_ref()
+            .is_some_and(|tb| tb.max_time > xdr::TimePoint(0))
+        {
+            return Err(
+                "TimeBounds.max_time has been already set - setting timeout would overwrite it."
+                    .to_string(),
+            );
+        }
+        self.timeout = Some(timeout_seconds);
+        Ok(self)
+    }
+
+    fn set_time_bounds(mut self, time_bounds: xdr::TimeBounds) -> Self {
+        self.time_bounds = Some(time_bounds);
+        self
+    }
+
+    fn set_ledger_bounds(mut self, ledger_bounds: xdr::LedgerBounds) -> Result<Self, String> {
+        if self.ledger_bounds.is_some() {
+            return Err("LedgerBounds has been already set".to_string());
+        }
+        if ledger_bounds.max_ledger > 0 && ledger_bounds.min_ledger > ledger_bounds.max_ledger {
+            return Err("min_ledger cannot be greater than max_ledger".to_string());
+        }
+        self.ledger_bounds = Some(ledger_bounds);
+        Ok(self)
+    }
+
+    fn set_soroban_data(mut self, soroban_data: xdr::SorobanTransactionData) -> Self {
+        self.soroban_data = Some(soroban_data);
+        self
+    }
+
+    /// Builds the transaction and returns it together with the source, whose sequence
+    /// number has been incremented. The source is left untouched on error.
+    fn build(self) -> Result<(Transaction, S), String> {
+        let fee = self.fee.ok_or("fee is not set")?;
+        if self.operations.is_empty() {
+            return Err("a transaction needs at least one operation".to_string());
+        }
+        if self.operations.len() > 100 {
+            return Err("a transaction can have at most 100 operations".to_string());
+        }
+        fee.checked_mul(self.operations.len() as u32)
+            .ok_or("fee overflows u32")?;
+
+        let Self {
+            mut source,
+            network_passphrase,
+            memo,
+            time_bounds,
+            timeout,
+            ledger_bounds,
+            soroban_data,
+            operations,
+            ..
+        } = self;
+        let tx = source.with_account(|account| {
+            let mut builder = TransactionBuilder::new(account, &network_passphrase, time_bounds);
+            builder.fee(fee);
+            if let Some(timeout) = timeout {
+                builder.set_timeout(timeout)?;
+            }
+            if let Some(ledger_bounds) = ledger_bounds {
+                builder.set_ledger_bounds(ledger_bounds);
+            }
+            if let Some(memo) = memo {
+                builder.set_memo(memo)?;
+            }
+            if let Some(soroban_data) = soroban_data {
+                builder.set_soroban_data(soroban_data);
+            }
+            for operation in operations {
+                builder.add_operation(operation);
+            }
+            Ok::<_, String>(builder.build())
+        })??;
+
+        Ok((tx, source))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -399,6 +732,7 @@ mod tests {
         asset::{Asset, AssetBehavior},
         contract::{ContractBehavior, Contracts},
         keypair::{self, Keypair},
+        memo::{Memo, MemoBehavior},
         network::{NetworkPassphrase, Networks},
         op_list::invoke_host,
         operation::Operation,
@@ -426,7 +760,7 @@ mod tests {
             )
             .build();
 
-        tx.sign(&[signer.clone()]);
+        tx.sign(std::slice::from_ref(&signer));
         let sig = &tx.signatures[0].signature.0;
         let verified = signer.verify(&tx.hash(), sig);
         assert!(verified);
@@ -454,6 +788,7 @@ mod tests {
                     .unwrap(),
             )
             .add_memo("100")
+            .unwrap()
             .set_timeout(TIMEOUT_INFINITE)
             .unwrap();
 
@@ -981,4 +1316,259 @@ mod tests {
         // Assert - sequence number should still be unchanged
         assert_eq!(source.sequence_number(), "200");
     }
+
+    #[test]
+    fn test_sponsored_sandwich_sets_sources() {
+        let sponsor = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+        let sponsored = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+        let asset = Asset::new("USD", Some(sponsor)).unwrap();
+        let mut source = Account::new(sponsor, "1").unwrap();
+
+        let tx = TransactionBuilder::new(&mut source, Networks::testnet(), None)
+            .fee(100_u32)
+            .sponsored(sponsor, sponsored, |b| {
+                b.add_operation(
+                    Operation::with_source(sponsor)
+                        .unwrap()
+                        .create_account(sponsored, 10 * operation::ONE)
+                        .unwrap(),
+                )
+                .add_operation(Operation::new().change_trust(&asset, None).unwrap());
+            })
+            .unwrap()
+            .build();
+
+        let ops = tx.operations.unwrap();
+        assert_eq!(ops.len(), 4);
+        assert_eq!(
+            ops[0].source_account,
+            Some(xdr::MuxedAccount::from_str(sponsor).unwrap())
+        );
+        assert_eq!(
+            ops[0].body,
+            xdr::OperationBody::BeginSponsoringFutureReserves(
+                xdr::BeginSponsoringFutureReservesOp {
+                    sponsored_id: xdr::AccountId::from_str(sponsored).unwrap(),
+                }
+            )
+        );
+        assert!(matches!(ops[1].body, xdr::OperationBody::CreateAccount(_)));
+        assert_eq!(
+            ops[1].source_account,
+            Some(xdr::MuxedAccount::from_str(sponsor).unwrap())
+        );
+        assert!(matches!(ops[2].body, xdr::OperationBody::ChangeTrust(_)));
+        assert_eq!(
+            ops[2].source_account,
+            Some(xdr::MuxedAccount::from_str(sponsored).unwrap())
+        );
+        assert_eq!(
+            ops[3].source_account,
+            Some(xdr::MuxedAccount::from_str(sponsored).unwrap())
+        );
+        assert_eq!(ops[3].body, xdr::OperationBody::EndSponsoringFutureReserves);
+    }
+
+    #[test]
+    fn test_sponsorship_errors() {
+        let sponsor = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+        let sponsored = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+        let mut source = Account::new(sponsor, "1").unwrap();
+        let mut builder = TransactionBuilder::new(&mut source, Networks::testnet(), None);
+
+        assert_eq!(
+            builder.end_sponsoring().err(),
+            Some("no sponsorship to end".to_string())
+        );
+        assert_eq!(
+            builder.begin_sponsoring(sponsor, sponsor).err(),
+            Some("an account cannot sponsor itself".to_string())
+        );
+        assert_eq!(
+            builder.begin_sponsoring("GBAD", sponsored).err(),
+            Some("sponsor is invalid".to_string())
+        );
+        assert_eq!(
+            builder
+                .sponsored(sponsor, sponsored, |b| {
+                    b.begin_sponsoring(sponsored, sponsor).unwrap();
+                })
+                .err(),
+            Some("sponsorships opened inside a sandwich must be closed in it".to_string())
+        );
+    }
+
+    #[test]
+    fn test_failed_sponsored_leaves_builder_unchanged() {
+        let sponsor = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+        let sponsored = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+        let mut source = Account::new(sponsor, "1").unwrap();
+        let mut builder = TransactionBuilder::new(&mut source, Networks::testnet(), None);
+        builder
+            .fee(100_u32)
+            .add_operation(Operation::new().bump_sequence(5).unwrap());
+
+        let result = builder.sponsored(sponsor, sponsored, |b| {
+            b.add_operation(Operation::new().bump_sequence(6).unwrap())
+                .begin_sponsoring(sponsored, sponsor)
+                .unwrap();
+        });
+        assert!(result.is_err());
+
+        let tx = builder.build();
+        let ops = tx.operations.unwrap();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(
+            ops[0].body,
+            xdr::OperationBody::BumpSequence(xdr::BumpSequenceOp {
+                bump_to: xdr::SequenceNumber(5)
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "has not been ended")]
+    fn test_build_rejects_open_sponsorship() {
+        let sponsor = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+        let sponsored = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+        let mut source = Account::new(sponsor, "1").unwrap();
+
+        TransactionBuilder::new(&mut source, Networks::testnet(), None)
+            .fee(100_u32)
+            .begin_sponsoring(sponsor, sponsored)
+            .unwrap()
+            .build();
+    }
+    fn owned_payment(destination: &str) -> xdr::Operation {
+        Operation::new()
+            .payment(destination, &Asset::native(), 10)
+            .unwrap()
+    }
+
+    fn build_owned(account: Account) -> Result<(Transaction, Account), String> {
+        let source = account.account_id();
+        OwnedTransactionBuilder::new(account, Networks::testnet(), None)
+            .fee(100_u32)
+            .add_operation(owned_payment(&source))
+            .memo(xdr::Memo::Id(7))
+            .set_timeout(TIMEOUT_INFINITE)?
+            .build()
+    }
+
+    #[test]
+    fn test_owned_builder_returns_incremented_account() {
+        let source = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+        let account = Account::new(source, "10").unwrap();
+
+        let (tx, account) = build_owned(account).unwrap();
+        assert_eq!(tx.sequence, Some("11".to_string()));
+        assert_eq!(tx.fee, 100);
+        assert_eq!(tx.memo, Some(xdr::Memo::Id(7)));
+        assert_eq!(account.sequence_number(), "11");
+
+        let (tx, account) = build_owned(account).unwrap();
+        assert_eq!(tx.sequence, Some("12".to_string()));
+        assert_eq!(account.sequence_number(), "12");
+    }
+
+    #[test]
+    fn test_owned_builder_with_shared_account() {
+        let source = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+        let shared = Arc::new(Mutex::new(Account::new(source, "1").unwrap()));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || {
+                    let (tx, _) = OwnedTransactionBuilder::new(shared, Networks::testnet(), None)
+                        .fee(100_u32)
+                        .add_operation(owned_payment(source))
+                        .build()
+                        .unwrap();
+                    tx.sequence.unwrap()
+                })
+            })
+            .collect();
+
+        let mut sequences: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        sequences.sort();
+        assert_eq!(sequences, vec!["2", "3", "4", "5"]);
+        assert_eq!(shared.lock().unwrap().sequence_number(), "5");
+    }
+
+    #[test]
+    fn test_owned_builder_errors_leave_account_untouched() {
+        let source = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+        let builder = || {
+            OwnedTransactionBuilder::new(
+                Account::new(source, "1").unwrap(),
+                Networks::testnet(),
+                None,
+            )
+        };
+
+        assert_eq!(
+            builder().add_operation(owned_payment(source)).build().err(),
+            Some("fee is not set".to_string())
+        );
+        assert_eq!(
+            builder().fee(100_u32).build().err(),
+            Some("a transaction needs at least one operation".to_string())
+        );
+        assert_eq!(
+            builder()
+                .fee(u32::MAX)
+                .add_operation(owned_payment(source))
+                .add_operation(owned_payment(source))
+                .build()
+                .err(),
+            Some("fee overflows u32".to_string())
+        );
+        assert!(builder().set_timeout(-1).is_err());
+        assert!(builder()
+            .set_ledger_bounds(xdr::LedgerBounds {
+                min_ledger: 10,
+                max_ledger: 5,
+            })
+            .is_err());
+
+        let shared = Arc::new(Mutex::new(Account::new(source, "1").unwrap()));
+        let result = OwnedTransactionBuilder::new(shared.clone(), Networks::testnet(), None)
+            .fee(100_u32)
+            .build();
+        assert!(result.is_err());
+        assert_eq!(shared.lock().unwrap().sequence_number(), "1");
+    }
+
+    #[test]
+    fn test_sets_typed_memos_without_panicking() {
+        let source = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+        let mut account = Account::new(source, "1").unwrap();
+        let mut builder = TransactionBuilder::new(&mut account, Networks::testnet(), None);
+
+        assert_eq!(
+            builder.add_memo("12345678901234567890123456789").err(),
+            Some("String is longer than 28 bytes".to_string())
+        );
+        assert!(Memo::id("not a number").is_err());
+        assert!(Memo::text_buffer(vec![0; 29]).is_err());
+
+        let tx = builder
+            .fee(100_u32)
+            .add_operation(owned_payment(source))
+            .set_memo(Memo::return_hash(vec![7; 32]).unwrap())
+            .unwrap()
+            .build();
+        assert_eq!(tx.memo, Some(xdr::Memo::Return(xdr::Hash([7; 32]))));
+
+        let tx = TransactionBuilder::new(&mut account, Networks::testnet(), None)
+            .fee(100_u32)
+            .add_operation(owned_payment(source))
+            .set_memo(Memo::id("18446744073709551615").unwrap())
+            .unwrap()
+            .set_memo(xdr::Memo::Id(9))
+            .unwrap()
+            .build();
+        assert_eq!(tx.memo, Some(xdr::Memo::Id(9)));
+    }
 }
@@ -0,0 +1,736 @@
+//! Results of a submitted transaction: result codes, the Soroban return value, events and fees.
+//!
+//! Result codes use the names Horizon reports in `extras.result_codes`, e.g. `tx_bad_seq`
+//! or `op_underfunded`.
+use std::fmt;
+
+use crate::xdr::{self, Limits, ReadXdr};
+
+/// A result code of a transaction or of one of its operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultCode {
+    Transaction(xdr::TransactionResultCode),
+    /// An operation that could not be applied, e.g. `OpBadAuth`.
+    Operation(xdr::OperationResultCode),
+    /// The result of an applied operation, with the raw code of its `*ResultCode` enum.
+    Inner {
+        operation: xdr::OperationType,
+        value: i32,
+        name: &'static str,
+    },
+}
+
+/// The codes of a transaction and its operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultCodes {
+    pub transaction: ResultCode,
+    /// The inner transaction's code, when the result is for a fee bump.
+    pub inner_transaction: Option<ResultCode>,
+    pub operations: Vec<ResultCode>,
+}
+
+/// Fees charged for a transaction, as reported by its result and Soroban meta.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeCharged {
+    /// The total fee charged, after any refund.
+    pub fee_charged: i64,
+    pub non_refundable_resource_fee: i64,
+    /// The refundable resource fee actually used, including `rent_fee`.
+    pub refundable_resource_fee: i64,
+    pub rent_fee: i64,
+}
+
+/// Events emitted by a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionEvents {
+    pub contract_events: Vec<xdr::ContractEvent>,
+    /// Transaction-level events, such as fee events, only present in `TransactionMetaV4`.
+    pub transaction_events: Vec<xdr::TransactionEvent>,
+    pub diagnostic_events: Vec<xdr::DiagnosticEvent>,
+}
+
+/// Everything read from a transaction's result and meta.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionOutcome {
+    pub codes: ResultCodes,
+    pub return_value: Option<xdr::ScVal>,
+    pub events: TransactionEvents,
+    pub fees: FeeCharged,
+}
+
+// Define a trait for ResultCode behavior
+pub trait ResultCodeBehavior {
+    fn from_operation_result(result: &xdr::OperationResult) -> Self;
+    fn code(&self) -> String;
+    fn is_success(&self) -> bool;
+}
+
+// Define a trait for ResultCodes behavior
+pub trait ResultCodesBehavior {
+    fn from_result(result: &xdr::TransactionResult) -> Self;
+    fn is_success(&self) -> bool;
+    fn failed_operations(&self) -> Vec<(usize, ResultCode)>;
+}
+
+// Define a trait for FeeCharged behavior
+pub trait FeeChargedBehavior {
+    fn from_result(result: &xdr::TransactionResult, meta: &xdr::TransactionMeta) -> Self;
+    fn resource_fee(&self) -> i64;
+    fn inclusion_fee(&self) -> i64;
+    fn refunded(&self, declared_resource_fee: i64) -> i64;
+}
+
+// Define a trait for TransactionEvents behavior
+pub trait TransactionEventsBehavior {
+    fn from_meta(meta: &xdr::TransactionMeta) -> Self;
+}
+
+// Define a trait for TransactionOutcome behavior
+pub trait TransactionOutcomeBehavior {
+    fn new(result: &xdr::TransactionResult, meta: &xdr::TransactionMeta) -> Self;
+    fn from_xdr_base64(result: &str, meta: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+}
+
+/// Returns the Soroban return value in `TransactionMetaV3` or `TransactionMetaV4`.
+pub fn return_value(meta: &xdr::TransactionMeta) -> Option<xdr::ScVal> {
+    match meta {
+        xdr::TransactionMeta::V3(meta) => meta
+            .soroban_meta
+            .as_ref()
+            .map(|soroban| soroban.return_value.clone()),
+        xdr::TransactionMeta::V4(meta) => meta
+            .soroban_meta
+            .as_ref()
+            .and_then(|soroban| soroban.return_value.clone()),
+        _ => None,
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+macro_rules! inner_code {
+    ($result:expr, $($variant:ident),+ $(,)?) => {
+        match $result {
+            $(xdr::OperationResultTr::$variant(result) => {
+                let code = result.discriminant();
+                (code as i32, code.name())
+            })+
+        }
+    };
+}
+
+fn operation_codes(results: &[xdr::OperationResult]) -> Vec<ResultCode> {
+    results
+        .iter()
+        .map(ResultCode::from_operation_result)
+        .collect()
+}
+
+impl ResultCodeBehavior for ResultCode {
+    fn from_operation_result(result: &xdr::OperationResult) -> Self {
+        let xdr::OperationResult::OpInner(result) = result else {
+            return ResultCode::Operation(result.discriminant());
+        };
+        let (value, name) = inner_code!(
+            result,
+            CreateAccount,
+            Payment,
+            PathPaymentStrictReceive,
+            ManageSellOffer,
+            CreatePassiveSellOffer,
+            SetOptions,
+            ChangeTrust,
+            AllowTrust,
+            AccountMerge,
+            Inflation,
+            ManageData,
+            BumpSequence,
+            ManageBuyOffer,
+            PathPaymentStrictSend,
+            CreateClaimableBalance,
+            ClaimClaimableBalance,
+            BeginSponsoringFutureReserves,
+            EndSponsoringFutureReserves,
+            RevokeSponsorship,
+            Clawback,
+            ClawbackClaimableBalance,
+            SetTrustLineFlags,
+            LiquidityPoolDeposit,
+            LiquidityPoolWithdraw,
+            InvokeHostFunction,
+            ExtendFootprintTtl,
+            RestoreFootprint,
+        );
+        ResultCode::Inner {
+            operation: result.discriminant(),
+            value,
+            name,
+        }
+    }
+
+    /// The code as Horizon reports it, e.g. `tx_bad_seq` or `op_underfunded`.
+    fn code(&self) -> String {
+        match self {
+            ResultCode::Transaction(xdr::TransactionResultCode::TxNoAccount) => {
+                "tx_no_source_account".to_string()
+            }
+            ResultCode::Transaction(xdr::TransactionResultCode::TxBadMinSeqAgeOrGap) => {
+                "tx_bad_minseq_age_or_gap".to_string()
+            }
+            ResultCode::Transaction(code) => snake_case(code.name()),
+            ResultCode::Operation(xdr::OperationResultCode::OpNoAccount) => {
+                "op_no_source_account".to_string()
+            }
+            ResultCode::Operation(code) => snake_case(code.name()),
+            ResultCode::Inner { value: 0, .. } => "op_success".to_string(),
+            ResultCode::Inner {
+                operation, name, ..
+            } => match (operation, *name) {
+                (xdr::OperationType::CreateAccount, "AlreadyExist") => {
+                    "op_already_exists".to_string()
+                }
+                (
+                    xdr::OperationType::PathPaymentStrictReceive
+                    | xdr::OperationType::PathPaymentStrictSend,
+                    "OfferCrossSelf",
+                ) => "op_cross_self".to_string(),
+                (xdr::OperationType::PathPaymentStrictReceive, "OverSendmax") => {
+                    "op_over_source_max".to_string()
+                }
+                (xdr::OperationType::PathPaymentStrictSend, "UnderDestmin") => {
+                    "op_under_dest_min".to_string()
+                }
+                (
+                    xdr::OperationType::ManageSellOffer
+                    | xdr::OperationType::ManageBuyOffer
+                    | xdr::OperationType::CreatePassiveSellOffer,
+                    "NotFound",
+                ) => "op_offer_not_found".to_string(),
+                (xdr::OperationType::AccountMerge, "SeqnumTooFar") => {
+                    "op_seq_num_too_far".to_string()
+                }
+                (xdr::OperationType::AllowTrust, "TrustNotRequired") => {
+                    "op_not_required".to_string()
+                }
+                (xdr::OperationType::ManageData, "NameNotFound") => {
+                    "op_data_name_not_found".to_string()
+                }
+                (xdr::OperationType::ManageData, "InvalidName") => {
+                    "op_data_invalid_name".to_string()
+                }
+                (xdr::OperationType::InvokeHostFunction, "Trapped") => {
+                    "function_trapped".to_string()
+                }
+                (xdr::OperationType::InvokeHostFunction, "EntryArchived") => {
+                    "entry_archived".to_string()
+                }
+                (
+                    xdr::OperationType::InvokeHostFunction
+                    | xdr::OperationType::ExtendFootprintTtl
+                    | xdr::OperationType::RestoreFootprint,
+                    "ResourceLimitExceeded" | "InsufficientRefundableFee",
+                ) => snake_case(name),
+                _ => format!("op_{}", snake_case(name)),
+            },
+        }
+    }
+
+    fn is_success(&self) -> bool {
+        match self {
+            ResultCode::Transaction(code) => matches!(
+                code,
+                xdr::TransactionResultCode::TxSuccess
+                    | xdr::TransactionResultCode::TxFeeBumpInnerSuccess
+            ),
+            ResultCode::Operation(_) => false,
+            ResultCode::Inner { value, .. } => *value == 0,
+        }
+    }
+}
+
+impl fmt::Display for ResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.code())
+    }
+}
+
+impl ResultCodesBehavior for ResultCodes {
+    fn from_result(result: &xdr::TransactionResult) -> Self {
+        let (inner_transaction, operations) = match &result.result {
+            xdr::TransactionResultResult::TxFeeBumpInnerSuccess(inner)
+            | xdr::TransactionResultResult::TxFeeBumpInnerFailed(inner) => {
+                let operations = match &inner.result.result {
+                    xdr::InnerTransactionResultResult::TxSuccess(results)
+                    | xdr::InnerTransactionResultResult::TxFailed(results) => {
+                        operation_codes(results)
+                    }
+                    _ => Vec::new(),
+                };
+                (
+                    Some(ResultCode::Transaction(inner.result.result.discriminant())),
+                    operations,
+                )
+            }
+            xdr::TransactionResultResult::TxSuccess(results)
+            | xdr::TransactionResultResult::TxFailed(results) => (None, operation_codes(results)),
+            _ => (None, Vec::new()),
+        };
+        Self {
+            transaction: ResultCode::Transaction(result.result.discriminant()),
+            inner_transaction,
+            operations,
+        }
+    }
+
+    fn is_success(&self) -> bool {
+        self.transaction.is_success()
+    }
+
+    /// The failed operations with their index in the transaction.
+    fn failed_operations(&self) -> Vec<(usize, ResultCode)> {
+        self.operations
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, code)| !code.is_success())
+            .collect()
+    }
+}
+
+impl FeeChargedBehavior for FeeCharged {
+    fn from_result(result: &xdr::TransactionResult, meta: &xdr::TransactionMeta) -> Self {
+        let soroban_ext = match meta {
+            xdr::TransactionMeta::V3(meta) => meta.soroban_meta.as_ref().map(|m| &m.ext),
+            xdr::TransactionMeta::V4(meta) => meta.soroban_meta.as_ref().map(|m| &m.ext),
+            _ => None,
+        };
+        let mut fees = Self {
+            fee_charged: result.fee_charged,
+            ..Self::default()
+        };
+        if let Some(xdr::SorobanTransactionMetaExt::V1(ext)) = soroban_ext {
+            fees.non_refundable_resource_fee = ext.total_non_refundable_resource_fee_charged;
+            fees.refundable_resource_fee = ext.total_refundable_resource_fee_charged;
+            fees.rent_fee = ext.rent_fee_charged;
+        }
+        fees
+    }
+
+    fn resource_fee(&self) -> i64 {
+        self.non_refundable_resource_fee + self.refundable_resource_fee
+    }
+
+    fn inclusion_fee(&self) -> i64 {
+        self.fee_charged - self.resource_fee()
+    }
+
+    /// The part of the `resource_fee` declared in the transaction's `SorobanTransactionData`
+    /// that was refunded.
+    fn refunded(&self, declared_resource_fee: i64) -> i64 {
+        (declared_resource_fee - self.resource_fee()).max(0)
+    }
+}
+
+impl TransactionEventsBehavior for TransactionEvents {
+    fn from_meta(meta: &xdr::TransactionMeta) -> Self {
+        match meta {
+            xdr::TransactionMeta::V3(meta) => match &meta.soroban_meta {
+                Some(soroban) => Self {
+                    contract_events: soroban.events.to_vec(),
+                    transaction_events: Vec::new(),
+                    diagnostic_events: soroban.diagnostic_events.to_vec(),
+                },
+                None => Self::default(),
+            },
+            xdr::TransactionMeta::V4(meta) => Self {
+                contract_events: meta
+                    .operations
+                    .iter()
+                    .flat_map(|op| op.events.iter().cloned())
+                    .collect(),
+                transaction_events: meta.events.to_vec(),
+                diagnostic_events: meta.diagnostic_events.to_vec(),
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
+impl TransactionOutcomeBehavior for TransactionOutcome {
+    fn new(result: &xdr::TransactionResult, meta: &xdr::TransactionMeta) -> Self {
+        Self {
+            codes: ResultCodes::from_result(result),
+            return_value: return_value(meta),
+            events: TransactionEvents::from_meta(meta),
+            fees: FeeCharged::from_result(result, meta),
+        }
+    }
+
+    /// Reads the `resultXdr` and `resultMetaXdr` returned after submission.
+    fn from_xdr_base64(result: &str, meta: &str) -> Result<Self, String> {
+        let result = xdr::TransactionResult::from_xdr_base64(result, Limits::none())
+            .map_err(|e| format!("invalid transaction result: {}", e))?;
+        let meta = xdr::TransactionMeta::from_xdr_base64(meta, Limits::none())
+            .map_err(|e| format!("invalid transaction meta: {}", e))?;
+        Ok(Self::new(&result, &meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xdr::WriteXdr;
+
+    fn tx_result(result: xdr::TransactionResultResult) -> xdr::TransactionResult {
+        xdr::TransactionResult {
+            fee_charged: 100,
+            result,
+            ext: xdr::TransactionResultExt::V0,
+        }
+    }
+
+    fn payment(result: xdr::PaymentResult) -> xdr::OperationResult {
+        xdr::OperationResult::OpInner(xdr::OperationResultTr::Payment(result))
+    }
+
+    fn event(value: u32) -> xdr::ContractEvent {
+        xdr::ContractEvent {
+            ext: xdr::ExtensionPoint::V0,
+            contract_id: None,
+            type_: xdr::ContractEventType::Contract,
+            body: xdr::ContractEventBody::V0(xdr::ContractEventV0 {
+                topics: vec![].try_into().unwrap(),
+                data: xdr::ScVal::U32(value),
+            }),
+        }
+    }
+
+    fn diagnostic(value: u32) -> xdr::DiagnosticEvent {
+        xdr::DiagnosticEvent {
+            in_successful_contract_call: true,
+            event: event(value),
+        }
+    }
+
+    fn soroban_ext() -> xdr::SorobanTransactionMetaExt {
+        xdr::SorobanTransactionMetaExt::V1(xdr::SorobanTransactionMetaExtV1 {
+            ext: xdr::ExtensionPoint::V0,
+            total_non_refundable_resource_fee_charged: 1_000,
+            total_refundable_resource_fee_charged: 3_000,
+            rent_fee_charged: 2_000,
+        })
+    }
+
+    fn meta_v3() -> xdr::TransactionMeta {
+        xdr::TransactionMeta::V3(xdr::TransactionMetaV3 {
+            ext: xdr::ExtensionPoint::V0,
+            tx_changes_before: vec![].try_into().unwrap(),
+            operations: vec![].try_into().unwrap(),
+            tx_changes_after: vec![].try_into().unwrap(),
+            soroban_meta: Some(xdr::SorobanTransactionMeta {
+                ext: soroban_ext(),
+                events: vec![event(1)].try_into().unwrap(),
+                return_value: xdr::ScVal::I32(42),
+                diagnostic_events: vec![diagnostic(2)].try_into().unwrap(),
+            }),
+        })
+    }
+
+    fn meta_v4() -> xdr::TransactionMeta {
+        xdr::TransactionMeta::V4(xdr::TransactionMetaV4 {
+            ext: xdr::ExtensionPoint::V0,
+            tx_changes_before: vec![].try_into().unwrap(),
+            operations: vec![xdr::OperationMetaV2 {
+                ext: xdr::ExtensionPoint::V0,
+                changes: vec![].try_into().unwrap(),
+                events: vec![event(1), event(3)].try_into().unwrap(),
+            }]
+            .try_into()
+            .unwrap(),
+            tx_changes_after: vec![].try_into().unwrap(),
+            soroban_meta: Some(xdr::SorobanTransactionMetaV2 {
+                ext: soroban_ext(),
+                return_value: Some(xdr::ScVal::Bool(true)),
+            }),
+            events: vec![xdr::TransactionEvent {
+                stage: xdr::TransactionEventStage::BeforeAllTxs,
+                event: event(4),
+            }]
+            .try_into()
+            .unwrap(),
+            diagnostic_events: vec![diagnostic(2)].try_into().unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_decodes_transaction_and_operation_codes() {
+        let codes = ResultCodes::from_result(&tx_result(xdr::TransactionResultResult::TxBadSeq));
+        assert_eq!(codes.transaction.code(), "tx_bad_seq");
+        assert!(!codes.is_success());
+        assert!(codes.operations.is_empty());
+
+        let codes = ResultCodes::from_result(&tx_result(xdr::TransactionResultResult::TxFailed(
+            vec![
+                payment(xdr::PaymentResult::Success),
+                payment(xdr::PaymentResult::Underfunded),
+                xdr::OperationResult::OpNoAccount,
+                xdr::OperationResult::OpInner(xdr::OperationResultTr::ManageData(
+                    xdr::ManageDataResult::NameNotFound,
+                )),
+            ]
+            .try_into()
+            .unwrap(),
+        )));
+        assert_eq!(codes.transaction.to_string(), "tx_failed");
+        let operations: Vec<String> = codes.operations.iter().map(|c| c.code()).collect();
+        assert_eq!(
+            operations,
+            [
+                "op_success",
+                "op_underfunded",
+                "op_no_source_account",
+                "op_data_name_not_found"
+            ]
+        );
+        assert_eq!(
+            codes.operations[1],
+            ResultCode::Inner {
+                operation: xdr::OperationType::Payment,
+                value: -2,
+                name: "Underfunded",
+            }
+        );
+        let failed: Vec<usize> = codes
+            .failed_operations()
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(failed, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_inner_codes_match_horizon_names() {
+        let code =
+            |result| ResultCode::from_operation_result(&xdr::OperationResult::OpInner(result));
+        let cases = [
+            (
+                xdr::OperationResultTr::CreateAccount(xdr::CreateAccountResult::AlreadyExist),
+                "op_already_exists",
+            ),
+            (
+                xdr::OperationResultTr::PathPaymentStrictReceive(
+                    xdr::PathPaymentStrictReceiveResult::OfferCrossSelf,
+                ),
+                "op_cross_self",
+            ),
+            (
+                xdr::OperationResultTr::PathPaymentStrictSend(
+                    xdr::PathPaymentStrictSendResult::OfferCrossSelf,
+                ),
+                "op_cross_self",
+            ),
+            (
+                xdr::OperationResultTr::PathPaymentStrictReceive(
+                    xdr::PathPaymentStrictReceiveResult::OverSendmax,
+                ),
+                "op_over_source_max",
+            ),
+            (
+                xdr::OperationResultTr::PathPaymentStrictSend(
+                    xdr::PathPaymentStrictSendResult::UnderDestmin,
+                ),
+                "op_under_dest_min",
+            ),
+            (
+                xdr::OperationResultTr::ManageSellOffer(xdr::ManageSellOfferResult::NotFound),
+                "op_offer_not_found",
+            ),
+            (
+                xdr::OperationResultTr::ManageBuyOffer(xdr::ManageBuyOfferResult::NotFound),
+                "op_offer_not_found",
+            ),
+            (
+                xdr::OperationResultTr::CreatePassiveSellOffer(
+                    xdr::ManageSellOfferResult::NotFound,
+                ),
+                "op_offer_not_found",
+            ),
+            (
+                xdr::OperationResultTr::AccountMerge(xdr::AccountMergeResult::SeqnumTooFar),
+                "op_seq_num_too_far",
+            ),
+            (
+                xdr::OperationResultTr::AllowTrust(xdr::AllowTrustResult::TrustNotRequired),
+                "op_not_required",
+            ),
+            (
+                xdr::OperationResultTr::ManageData(xdr::ManageDataResult::InvalidName),
+                "op_data_invalid_name",
+            ),
+            (
+                xdr::OperationResultTr::InvokeHostFunction(xdr::InvokeHostFunctionResult::Trapped),
+                "function_trapped",
+            ),
+            (
+                xdr::OperationResultTr::InvokeHostFunction(
+                    xdr::InvokeHostFunctionResult::ResourceLimitExceeded,
+                ),
+                "resource_limit_exceeded",
+            ),
+            (
+                xdr::OperationResultTr::InvokeHostFunction(
+                    xdr::InvokeHostFunctionResult::EntryArchived,
+                ),
+                "entry_archived",
+            ),
+            (
+                xdr::OperationResultTr::InvokeHostFunction(
+                    xdr::InvokeHostFunctionResult::InsufficientRefundableFee,
+                ),
+                "insufficient_refundable_fee",
+            ),
+            (
+                xdr::OperationResultTr::InvokeHostFunction(
+                    xdr::InvokeHostFunctionResult::Malformed,
+                ),
+                "op_malformed",
+            ),
+            (
+                xdr::OperationResultTr::ExtendFootprintTtl(
+                    xdr::ExtendFootprintTtlResult::ResourceLimitExceeded,
+                ),
+                "resource_limit_exceeded",
+            ),
+            (
+                xdr::OperationResultTr::ExtendFootprintTtl(
+                    xdr::ExtendFootprintTtlResult::InsufficientRefundableFee,
+                ),
+                "insufficient_refundable_fee",
+            ),
+            (
+                xdr::OperationResultTr::RestoreFootprint(
+                    xdr::RestoreFootprintResult::ResourceLimitExceeded,
+                ),
+                "resource_limit_exceeded",
+            ),
+            (
+                xdr::OperationResultTr::RestoreFootprint(
+                    xdr::RestoreFootprintResult::InsufficientRefundableFee,
+                ),
+                "insufficient_refundable_fee",
+            ),
+        ];
+        for (result, expected) in cases {
+            assert_eq!(code(result).code(), expected);
+        }
+        assert_eq!(
+            ResultCode::Transaction(xdr::TransactionResultCode::TxBadMinSeqAgeOrGap).code(),
+            "tx_bad_minseq_age_or_gap"
+        );
+    }
+
+    #[test]
+    fn test_decodes_fee_bump_codes() {
+        let result = tx_result(xdr::TransactionResultResult::TxFeeBumpInnerFailed(
+            xdr::InnerTransactionResultPair {
+                transaction_hash: xdr::Hash([0; 32]),
+                result: xdr::InnerTransactionResult {
+                    fee_charged: 100,
+                    result: xdr::InnerTransactionResultResult::TxFailed(
+                        vec![payment(xdr::PaymentResult::NoDestination)]
+                            .try_into()
+                            .unwrap(),
+                    ),
+                    ext: xdr::InnerTransactionResultExt::V0,
+                },
+            },
+        ));
+        let codes = ResultCodes::from_result(&result);
+        assert_eq!(codes.transaction.code(), "tx_fee_bump_inner_failed");
+        assert_eq!(codes.inner_transaction.unwrap().code(), "tx_failed");
+        assert_eq!(codes.operations[0].code(), "op_no_destination");
+    }
+
+    #[test]
+    fn test_extracts_return_value_and_events() {
+        assert_eq!(return_value(&meta_v3()), Some(xdr::ScVal::I32(42)));
+        let events = TransactionEvents::from_meta(&meta_v3());
+        assert_eq!(events.contract_events, [event(1)]);
+        assert_eq!(events.diagnostic_events, [diagnostic(2)]);
+        assert!(events.transaction_events.is_empty());
+
+        assert_eq!(return_value(&meta_v4()), Some(xdr::ScVal::Bool(true)));
+        let events = TransactionEvents::from_meta(&meta_v4());
+        assert_eq!(events.contract_events, [event(1), event(3)]);
+        assert_eq!(events.diagnostic_events, [diagnostic(2)]);
+        assert_eq!(events.transaction_events[0].event, event(4));
+
+        let classic = xdr::TransactionMeta::V0(vec![].try_into().unwrap());
+        assert_eq!(return_value(&classic), None);
+        assert_eq!(
+            TransactionEvents::from_meta(&classic),
+            TransactionEvents::default()
+        );
+    }
+
+    #[test]
+    fn test_computes_fee_charged_and_refunded() {
+        let mut result = tx_result(xdr::TransactionResultResult::TxSuccess(
+            vec![].try_into().unwrap(),
+        ));
+        result.fee_charged = 4_100;
+        let fees = FeeCharged::from_result(&result, &meta_v4());
+        assert_eq!(fees.resource_fee(), 4_000);
+        assert_eq!(fees.inclusion_fee(), 100);
+        assert_eq!(fees.rent_fee, 2_000);
+        assert_eq!(fees.refunded(10_000), 6_000);
+        assert_eq!(fees.refunded(3_000), 0);
+
+        let fees = FeeCharged::from_result(
+            &result,
+            &xdr::TransactionMeta::V0(vec![].try_into().unwrap()),
+        );
+        assert_eq!(fees.inclusion_fee(), 4_100);
+    }
+
+    #[test]
+    fn test_reads_outcome_from_base64() {
+        let result = tx_result(xdr::TransactionResultResult::TxSuccess(
+            vec![xdr::OperationResult::OpInner(
+                xdr::OperationResultTr::InvokeHostFunction(xdr::InvokeHostFunctionResult::Success(
+                    xdr::Hash([1; 32]),
+                )),
+            )]
+            .try_into()
+            .unwrap(),
+        ));
+        let outcome = TransactionOutcome::from_xdr_base64(
+            &result.to_xdr_base64(Limits::none()).unwrap(),
+            &meta_v3().to_xdr_base64(Limits::none()).unwrap(),
+        )
+        .unwrap();
+        assert!(outcome.codes.is_success());
+        assert_eq!(outcome.codes.operations[0].code(), "op_success");
+        assert_eq!(outcome.return_value, Some(xdr::ScVal::I32(42)));
+        assert_eq!(outcome.fees.fee_charged, 100);
+
+        assert!(TransactionOutcome::from_xdr_base64("AAAA", "")
+            .unwrap_err()
+            .starts_with("invalid transaction"));
+    }
+}
@@ -0,0 +1,1520 @@
+//! SEP-11 Txrep, a human-readable representation of transaction envelopes.
+//!
+//! Every XDR field becomes one `path: value` line. Accounts, signer keys and contract addresses
+//! are written as strkeys, assets as `XLM` or `CODE:ISSUER`, binary data as hex and strings as
+//! quoted, escaped text. Anything after the value on a line is a comment and is ignored when
+//! parsing. See <https://github.com/stellar/stellar-protocol/blob/master/ecosystem/sep-0011.md>.
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::xdr;
+
+/// Renders an envelope as Txrep.
+pub fn to_txrep(envelope: &xdr::TransactionEnvelope) -> String {
+    let mut lines = Vec::new();
+    envelope.to_lines("", &mut lines);
+    let mut txrep = lines.join("\n");
+    txrep.push('\n');
+    txrep
+}
+
+/// Parses the Txrep of an envelope.
+pub fn from_txrep(txrep: &str) -> Result<xdr::TransactionEnvelope, String> {
+    let fields = Fields::read(txrep)?;
+    xdr::TransactionEnvelope::from_lines("", &fields)
+}
+
+// Values of a Txrep document, by path
+struct Fields(HashMap<String, String>);
+
+impl Fields {
+    fn read(txrep: &str) -> Result<Self, String> {
+        let mut fields = HashMap::new();
+        for (number, line) in txrep.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("line {} is not a `key: value` pair", number + 1))?;
+            let key = key.trim().to_string();
+            if fields
+                .insert(key.clone(), value.trim().to_string())
+                .is_some()
+            {
+                return Err(format!("{} is set more than once", key));
+            }
+        }
+        Ok(Self(fields))
+    }
+
+    // The value of `key` without its trailing comment
+    fn value(&self, key: &str) -> Result<&str, String> {
+        let value = self
+            .0
+            .get(key)
+            .ok_or_else(|| format!("{} is missing", key))?;
+        if value.starts_with('"') {
+            return Ok(value);
+        }
+        Ok(value.split_whitespace().next().unwrap_or(""))
+    }
+
+    fn parse<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        self.value(key)?
+            .parse()
+            .map_err(|_| format!("{} is invalid", key))
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn line(lines: &mut Vec<String>, key: &str, value: impl std::fmt::Display) {
+    lines.push(format!("{}: {}", key, value));
+}
+
+// Quotes a string, escaping anything that is not printable ascii
+fn quote(bytes: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+    for b in bytes {
+        match b {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            0x20..=0x7e => quoted.push(*b as char),
+            _ => quoted.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn unquote(key: &str, value: &str) -> Result<Vec<u8>, String> {
+    let invalid = || format!("{} is not a valid string", key);
+    let mut chars = value.strip_prefix('"').ok_or_else(invalid)?.bytes();
+    let mut bytes = Vec::new();
+    loop {
+        match chars.next().ok_or_else(invalid)? {
+            b'"' => return Ok(bytes),
+            b'\\' => match chars.next().ok_or_else(invalid)? {
+                b'x' => {
+                    let hex = [
+                        chars.next().ok_or_else(invalid)?,
+                        chars.next().ok_or_else(invalid)?,
+                    ];
+                    let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                    bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                }
+                b'n' => bytes.push(b'\n'),
+                escaped => bytes.push(escaped),
+            },
+            b => bytes.push(b),
+        }
+    }
+}
+
+trait Txrep: Sized {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>);
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String>;
+}
+
+// Types written with their `Display` and read with their `FromStr` implementation
+macro_rules! txrep_display {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Txrep for $ty {
+                fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+                    line(lines, key, self);
+                }
+
+                fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+                    fields.parse(key)
+                }
+            }
+        )*
+    };
+}
+
+txrep_display!(
+    bool,
+    u32,
+    i32,
+    u64,
+    i64,
+    xdr::AccountId,
+    xdr::MuxedAccount,
+    xdr::PublicKey,
+    xdr::SignerKey,
+    xdr::ScAddress,
+    xdr::AssetCode,
+);
+
+// Single field wrappers written as their inner value
+macro_rules! txrep_newtype {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Txrep for $ty {
+                fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+                    self.0.to_lines(key, lines);
+                }
+
+                fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+                    Ok(Self(Txrep::from_lines(key, fields)?))
+                }
+            }
+        )*
+    };
+}
+
+txrep_newtype!(
+    xdr::SequenceNumber,
+    xdr::TimePoint,
+    xdr::Duration,
+    xdr::Hash,
+    xdr::Uint256,
+    xdr::SignatureHint,
+    xdr::Signature,
+    xdr::PoolId,
+    xdr::ContractId,
+    xdr::DataValue,
+    xdr::String32,
+    xdr::String64,
+    xdr::ScBytes,
+    xdr::ScString,
+    xdr::ScSymbol,
+    xdr::ScVec,
+    xdr::ScMap,
+);
+
+macro_rules! txrep_struct {
+    ($ty:ty { $($field:ident: $name:literal),* $(,)? }) => {
+        impl Txrep for $ty {
+            fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+                $( self.$field.to_lines(&join(key, $name), lines); )*
+            }
+
+            fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+                Ok(Self {
+                    $( $field: Txrep::from_lines(&join(key, $name), fields)?, )*
+                })
+            }
+        }
+    };
+}
+
+// Unions write their discriminant under `$tag`, then the value of the arm under its name
+macro_rules! txrep_union {
+    (
+        $ty:ty, $tag:literal,
+        { $($void:ident = $void_disc:literal),* $(,)? },
+        { $($variant:ident($arm:literal) = $disc:literal),* $(,)? } $(,)?
+    ) => {
+        impl Txrep for $ty {
+            fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+                let tag = join(key, $tag);
+                match self {
+                    $( Self::$void => line(lines, &tag, $void_disc), )*
+                    $(
+                        Self::$variant(value) => {
+                            line(lines, &tag, $disc);
+                            value.to_lines(&join(key, $arm), lines);
+                        }
+                    )*
+                }
+            }
+
+            fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+                let tag = join(key, $tag);
+                match fields.value(&tag)? {
+                    $( $void_disc => Ok(Self::$void), )*
+                    $( $disc => Ok(Self::$variant(Txrep::from_lines(&join(key, $arm), fields)?)), )*
+                    other => Err(format!("{} {} is not supported", tag, other)),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! txrep_enum {
+    ($ty:ty { $($variant:ident = $name:literal),* $(,)? }) => {
+        impl Txrep for $ty {
+            fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+                match self {
+                    $( Self::$variant => line(lines, key, $name), )*
+                }
+            }
+
+            fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+                match fields.value(key)? {
+                    $( $name => Ok(Self::$variant), )*
+                    other => Err(format!("{} {} is not supported", key, other)),
+                }
+            }
+        }
+    };
+}
+
+impl<const N: usize> Txrep for [u8; N] {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+        line(lines, key, hex::encode(self));
+    }
+
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+        hex::decode(fields.value(key)?)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| format!("{} is not {} hex encoded bytes", key, N))
+    }
+}
+
+impl<const N: u32> Txrep for xdr::BytesM<N> {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+        line(lines, key, hex::encode(self.as_slice()));
+    }
+
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+        hex::decode(fields.value(key)?)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| format!("{} is not valid hex encoded bytes", key))
+    }
+}
+
+impl<const N: u32> Txrep for xdr::StringM<N> {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+        line(lines, key, quote(self.as_slice()));
+    }
+
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+        unquote(key, fields.value(key)?)?
+            .try_into()
+            .map_err(|_| format!("{} is too long", key))
+    }
+}
+
+impl<T: Txrep, const N: u32> Txrep for xdr::VecM<T, N> {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+        line(lines, &format!("{}.len", key), self.len());
+        for (i, item) in self.iter().enumerate() {
+            item.to_lines(&format!("{}[{}]", key, i), lines);
+        }
+    }
+
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+        let len: usize = fields.parse(&format!("{}.len", key))?;
+        if len > N as usize {
+            return Err(format!("{} has more than {} items", key, N));
+        }
+        (0..len)
+            .map(|i| T::from_lines(&format!("{}[{}]", key, i), fields))
+            .collect::<Result<Vec<T>, String>>()?
+            .try_into()
+            .map_err(|_| format!("{} has too many items", key))
+    }
+}
+
+impl<T: Txrep> Txrep for Option<T> {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+        line(lines, &format!("{}._present", key), self.is_some());
+        if let Some(value) = self {
+            value.to_lines(key, lines);
+        }
+    }
+
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+        if fields.parse(&format!("{}._present", key))? {
+            Ok(Some(T::from_lines(key, fields)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Txrep> Txrep for Box<T> {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+        self.as_ref().to_lines(key, lines);
+    }
+
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+        Ok(Box::new(T::from_lines(key, fields)?))
+    }
+}
+
+// Native and credit assets are written in their compact `XLM` or `CODE:ISSUER` form
+fn asset_to_string(code: &xdr::AssetCode, issuer: &xdr::AccountId) -> String {
+    format!("{}:{}", code, issuer)
+}
+
+fn asset_from_str(
+    key: &str,
+    value: &str,
+) -> Result<Option<(xdr::AssetCode, xdr::AccountId)>, String> {
+    if value == "XLM" || value == "native" {
+        return Ok(None);
+    }
+    let invalid = || format!("{} is not a valid asset", key);
+    let (code, issuer) = value.split_once(':').ok_or_else(invalid)?;
+    Ok(Some((
+        xdr::AssetCode::from_str(code).map_err(|_| invalid())?,
+        xdr::AccountId::from_str(issuer).map_err(|_| invalid())?,
+    )))
+}
+
+impl Txrep for xdr::Asset {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+        let value = match self {
+            Self::Native => "XLM".to_string(),
+            Self::CreditAlphanum4(a) => asset_to_string(
+                &xdr::AssetCode::CreditAlphanum4(a.asset_code.clone()),
+                &a.issuer,
+            ),
+            Self::CreditAlphanum12(a) => asset_to_string(
+                &xdr::AssetCode::CreditAlphanum12(a.asset_code.clone()),
+                &a.issuer,
+            ),
+        };
+        line(lines, key, value);
+    }
+
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+        Ok(match asset_from_str(key, fields.value(key)?)? {
+            None => Self::Native,
+            Some((xdr::AssetCode::CreditAlphanum4(asset_code), issuer)) => {
+                Self::CreditAlphanum4(xdr::AlphaNum4 { asset_code, issuer })
+            }
+            Some((xdr::AssetCode::CreditAlphanum12(asset_code), issuer)) => {
+                Self::CreditAlphanum12(xdr::AlphaNum12 { asset_code, issuer })
+            }
+        })
+    }
+}
+
+// Pool shares have no compact form and are written as a union
+macro_rules! txrep_pool_asset {
+    ($ty:ty, $arm:literal) => {
+        impl Txrep for $ty {
+            fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+                let asset = match self {
+                    Self::Native => xdr::Asset::Native,
+                    Self::CreditAlphanum4(a) => xdr::Asset::CreditAlphanum4(a.clone()),
+                    Self::CreditAlphanum12(a) => xdr::Asset::CreditAlphanum12(a.clone()),
+                    Self::PoolShare(pool) => {
+                        line(lines, &join(key, "type"), "ASSET_TYPE_POOL_SHARE");
+                        pool.to_lines(&join(key, $arm), lines);
+                        return;
+                    }
+                };
+                asset.to_lines(key, lines);
+            }
+
+            fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+                let tag = join(key, "type");
+                if fields.contains(&tag) {
+                    return match fields.value(&tag)? {
+                        "ASSET_TYPE_POOL_SHARE" => Ok(Self::PoolShare(Txrep::from_lines(
+                            &join(key, $arm),
+                            fields,
+                        )?)),
+                        other => Err(format!("{} {} is not supported", tag, other)),
+                    };
+                }
+                Ok(match xdr::Asset::from_lines(key, fields)? {
+                    xdr::Asset::Native => Self::Native,
+                    xdr::Asset::CreditAlphanum4(a) => Self::CreditAlphanum4(a),
+                    xdr::Asset::CreditAlphanum12(a) => Self::CreditAlphanum12(a),
+                })
+            }
+        }
+    };
+}
+
+txrep_pool_asset!(xdr::ChangeTrustAsset, "liquidityPool");
+txrep_pool_asset!(xdr::TrustLineAsset, "liquidityPoolID");
+
+// v1 envelopes are written without their arm name, as before fee bumps existed
+impl Txrep for xdr::TransactionEnvelope {
+    fn to_lines(&self, key: &str, lines: &mut Vec<String>) {
+        let tag = join(key, "type");
+        match self {
+            Self::TxV0(v0) => {
+                line(lines, &tag, "ENVELOPE_TYPE_TX_V0");
+                v0.to_lines(&join(key, "v0"), lines);
+            }
+            Self::Tx(v1) => {
+                line(lines, &tag, "ENVELOPE_TYPE_TX");
+                v1.to_lines(key, lines);
+            }
+            Self::TxFeeBump(fee_bump) => {
+                line(lines, &tag, "ENVELOPE_TYPE_TX_FEE_BUMP");
+                fee_bump.to_lines(&join(key, "feeBump"), lines);
+            }
+        }
+    }
+
+    fn from_lines(key: &str, fields: &Fields) -> Result<Self, String> {
+        let tag = join(key, "type");
+        match fields.value(&tag)? {
+            "ENVELOPE_TYPE_TX_V0" => Ok(Self::TxV0(Txrep::from_lines(&join(key, "v0"), fields)?)),
+            "ENVELOPE_TYPE_TX" => Ok(Self::Tx(Txrep::from_lines(key, fields)?)),
+            "ENVELOPE_TYPE_TX_FEE_BUMP" => Ok(Self::TxFeeBump(Txrep::from_lines(
+                &join(key, "feeBump"),
+                fields,
+            )?)),
+            other => Err(format!("{} {} is not supported", tag, other)),
+        }
+    }
+}
+
+txrep_struct!(xdr::TransactionV0Envelope {
+    tx: "tx",
+    signatures: "signatures"
+});
+txrep_struct!(xdr::TransactionV1Envelope {
+    tx: "tx",
+    signatures: "signatures"
+});
+txrep_struct!(xdr::FeeBumpTransactionEnvelope {
+    tx: "tx",
+    signatures: "signatures"
+});
+txrep_struct!(xdr::FeeBumpTransaction {
+    fee_source: "feeSource",
+    fee: "fee",
+    inner_tx: "innerTx",
+    ext: "ext",
+});
+txrep_union!(xdr::FeeBumpTransactionInnerTx, "type", {}, {
+    Tx("v1") = "ENVELOPE_TYPE_TX"
+});
+txrep_union!(xdr::FeeBumpTransactionExt, "v", { V0 = "0" }, {});
+txrep_struct!(xdr::TransactionV0 {
+    source_account_ed25519: "sourceAccountEd25519",
+    fee: "fee",
+    seq_num: "seqNum",
+    time_bounds: "timeBounds",
+    memo: "memo",
+    operations: "operations",
+    ext: "ext",
+});
+txrep_union!(xdr::TransactionV0Ext, "v", { V0 = "0" }, {});
+txrep_struct!(xdr::Transaction {
+    source_account: "sourceAccount",
+    fee: "fee",
+    seq_num: "seqNum",
+    cond: "cond",
+    memo: "memo",
+    operations: "operations",
+    ext: "ext",
+});
+txrep_union!(xdr::TransactionExt, "v", { V0 = "0" }, {
+    V1("sorobanData") = "1"
+});
+txrep_struct!(xdr::DecoratedSignature {
+    hint: "hint",
+    signature: "signature"
+});
+
+txrep_union!(
+    xdr::Preconditions,
+    "type",
+    { None = "PRECOND_NONE" },
+    { Time("timeBounds") = "PRECOND_TIME", V2("v2") = "PRECOND_V2" },
+);
+txrep_struct!(xdr::PreconditionsV2 {
+    time_bounds: "timeBounds",
+    ledger_bounds: "ledgerBounds",
+    min_seq_num: "minSeqNum",
+    min_seq_age: "minSeqAge",
+    min_seq_ledger_gap: "minSeqLedgerGap",
+    extra_signers: "extraSigners",
+});
+txrep_struct!(xdr::TimeBounds {
+    min_time: "minTime",
+    max_time: "maxTime"
+});
+txrep_struct!(xdr::LedgerBounds {
+    min_ledger: "minLedger",
+    max_ledger: "maxLedger"
+});
+
+txrep_union!(
+    xdr::Memo,
+    "type",
+    { None = "MEMO_NONE" },
+    {
+        Text("text") = "MEMO_TEXT",
+        Id("id") = "MEMO_ID",
+        Hash("hash") = "MEMO_HASH",
+        Return("retHash") = "MEMO_RETURN",
+    },
+);
+
+txrep_struct!(xdr::Operation {
+    source_account: "sourceAccount",
+    body: "body"
+});
+txrep_union!(
+    xdr::OperationBody,
+    "type",
+    { Inflation = "INFLATION", EndSponsoringFutureReserves = "END_SPONSORING_FUTURE_RESERVES" },
+    {
+        CreateAccount("createAccountOp") = "CREATE_ACCOUNT",
+        Payment("paymentOp") = "PAYMENT",
+        PathPaymentStrictReceive("pathPaymentStrictReceiveOp") = "PATH_PAYMENT_STRICT_RECEIVE",
+        ManageSellOffer("manageSellOfferOp") = "MANAGE_SELL_OFFER",
+        CreatePassiveSellOffer("createPassiveSellOfferOp") = "CREATE_PASSIVE_SELL_OFFER",
+        SetOptions("setOptionsOp") = "SET_OPTIONS",
+        ChangeTrust("changeTrustOp") = "CHANGE_TRUST",
+        AllowTrust("allowTrustOp") = "ALLOW_TRUST",
+        AccountMerge("destination") = "ACCOUNT_MERGE",
+        ManageData("manageDataOp") = "MANAGE_DATA",
+        BumpSequence("bumpSequenceOp") = "BUMP_SEQUENCE",
+        ManageBuyOffer("manageBuyOfferOp") = "MANAGE_BUY_OFFER",
+        PathPaymentStrictSend("pathPaymentStrictSendOp") = "PATH_PAYMENT_STRICT_SEND",
+        CreateClaimableBalance("createClaimableBalanceOp") = "CREATE_CLAIMABLE_BALANCE",
+        ClaimClaimableBalance("claimClaimableBalanceOp") = "CLAIM_CLAIMABLE_BALANCE",
+        BeginSponsoringFutureReserves("beginSponsoringFutureReservesOp") =
+            "BEGIN_SPONSORING_FUTURE_RESERVES",
+        RevokeSponsorship("revokeSponsorshipOp") = "REVOKE_SPONSORSHIP",
+        Clawback("clawbackOp") = "CLAWBACK",
+        ClawbackClaimableBalance("clawbackClaimableBalanceOp") = "CLAWBACK_CLAIMABLE_BALANCE",
+        SetTrustLineFlags("setTrustLineFlagsOp") = "SET_TRUST_LINE_FLAGS",
+        LiquidityPoolDeposit("liquidityPoolDepositOp") = "LIQUIDITY_POOL_DEPOSIT",
+        LiquidityPoolWithdraw("liquidityPoolWithdrawOp") = "LIQUIDITY_POOL_WITHDRAW",
+        InvokeHostFunction("invokeHostFunctionOp") = "INVOKE_HOST_FUNCTION",
+        ExtendFootprintTtl("extendFootprintTTLOp") = "EXTEND_FOOTPRINT_TTL",
+        RestoreFootprint("restoreFootprintOp") = "RESTORE_FOOTPRINT",
+    },
+);
+
+txrep_struct!(xdr::CreateAccountOp {
+    destination: "destination",
+    starting_balance: "startingBalance"
+});
+txrep_struct!(xdr::PaymentOp {
+    destination: "destination",
+    asset: "asset",
+    amount: "amount"
+});
+txrep_struct!(xdr::PathPaymentStrictReceiveOp {
+    send_asset: "sendAsset",
+    send_max: "sendMax",
+    destination: "destination",
+    dest_asset: "destAsset",
+    dest_amount: "destAmount",
+    path: "path",
+});
+txrep_struct!(xdr::PathPaymentStrictSendOp {
+    send_asset: "sendAsset",
+    send_amount: "sendAmount",
+    destination: "destination",
+    dest_asset: "destAsset",
+    dest_min: "destMin",
+    path: "path",
+});
+txrep_struct!(xdr::ManageSellOfferOp {
+    selling: "selling",
+    buying: "buying",
+    amount: "amount",
+    price: "price",
+    offer_id: "offerID",
+});
+txrep_struct!(xdr::ManageBuyOfferOp {
+    selling: "selling",
+    buying: "buying",
+    buy_amount: "buyAmount",
+    price: "price",
+    offer_id: "offerID",
+});
+txrep_struct!(xdr::CreatePassiveSellOfferOp {
+    selling: "selling",
+    buying: "buying",
+    amount: "amount",
+    price: "price",
+});
+txrep_struct!(xdr::Price { n: "n", d: "d" });
+txrep_struct!(xdr::SetOptionsOp {
+    inflation_dest: "inflationDest",
+    clear_flags: "clearFlags",
+    set_flags: "setFlags",
+    master_weight: "masterWeight",
+    low_threshold: "lowThreshold",
+    med_threshold: "medThreshold",
+    high_threshold: "highThreshold",
+    home_domain: "homeDomain",
+    signer: "signer",
+});
+txrep_struct!(xdr::Signer {
+    key: "key",
+    weight: "weight"
+});
+txrep_struct!(xdr::ChangeTrustOp {
+    line: "line",
+    limit: "limit"
+});
+txrep_union!(xdr::LiquidityPoolParameters, "type", {}, {
+    LiquidityPoolConstantProduct("constantProduct") = "LIQUIDITY_POOL_CONSTANT_PRODUCT"
+},);
+txrep_struct!(xdr::LiquidityPoolConstantProductParameters {
+    asset_a: "assetA",
+    asset_b: "assetB",
+    fee: "fee",
+});
+txrep_struct!(xdr::AllowTrustOp {
+    trustor: "trustor",
+    asset: "asset",
+    authorize: "authorize"
+});
+txrep_struct!(xdr::ManageDataOp {
+    data_name: "dataName",
+    data_value: "dataValue"
+});
+txrep_struct!(xdr::BumpSequenceOp { bump_to: "bumpTo" });
+txrep_struct!(xdr::CreateClaimableBalanceOp {
+    asset: "asset",
+    amount: "amount",
+    claimants: "claimants",
+});
+txrep_union!(xdr::Claimant, "type", {}, {
+    ClaimantTypeV0("v0") = "CLAIMANT_TYPE_V0"
+});
+txrep_struct!(xdr::ClaimantV0 {
+    destination: "destination",
+    predicate: "predicate"
+});
+txrep_union!(
+    xdr::ClaimPredicate,
+    "type",
+    { Unconditional = "CLAIM_PREDICATE_UNCONDITIONAL" },
+    {
+        And("andPredicates") = "CLAIM_PREDICATE_AND",
+        Or("orPredicates") = "CLAIM_PREDICATE_OR",
+        Not("notPredicate") = "CLAIM_PREDICATE_NOT",
+        BeforeAbsoluteTime("absBefore") = "CLAIM_PREDICATE_BEFORE_ABSOLUTE_TIME",
+        BeforeRelativeTime("relBefore") = "CLAIM_PREDICATE_BEFORE_RELATIVE_TIME",
+    },
+);
+txrep_struct!(xdr::ClaimClaimableBalanceOp {
+    balance_id: "balanceID"
+});
+txrep_union!(xdr::ClaimableBalanceId, "type", {}, {
+    ClaimableBalanceIdTypeV0("v0") = "CLAIMABLE_BALANCE_ID_TYPE_V0"
+},);
+txrep_struct!(xdr::BeginSponsoringFutureReservesOp {
+    sponsored_id: "sponsoredID"
+});
+txrep_union!(
+    xdr::RevokeSponsorshipOp,
+    "type",
+    {},
+    {
+        LedgerEntry("ledgerKey") = "REVOKE_SPONSORSHIP_LEDGER_ENTRY",
+        Signer("signer") = "REVOKE_SPONSORSHIP_SIGNER",
+    },
+);
+txrep_struct!(xdr::RevokeSponsorshipOpSigner {
+    account_id: "accountID",
+    signer_key: "signerKey"
+});
+txrep_struct!(xdr::ClawbackOp {
+    asset: "asset",
+    from: "from",
+    amount: "amount"
+});
+txrep_struct!(xdr::ClawbackClaimableBalanceOp {
+    balance_id: "balanceID"
+});
+txrep_struct!(xdr::SetTrustLineFlagsOp {
+    trustor: "trustor",
+    asset: "asset",
+    clear_flags: "clearFlags",
+    set_flags: "setFlags",
+});
+txrep_struct!(xdr::LiquidityPoolDepositOp {
+    liquidity_pool_id: "liquidityPoolID",
+    max_amount_a: "maxAmountA",
+    max_amount_b: "maxAmountB",
+    min_price: "minPrice",
+    max_price: "maxPrice",
+});
+txrep_struct!(xdr::LiquidityPoolWithdrawOp {
+    liquidity_pool_id: "liquidityPoolID",
+    amount: "amount",
+    min_amount_a: "minAmountA",
+    min_amount_b: "minAmountB",
+});
+txrep_struct!(xdr::InvokeHostFunctionOp {
+    host_function: "hostFunction",
+    auth: "auth"
+});
+txrep_struct!(xdr::ExtendFootprintTtlOp {
+    ext: "ext",
+    extend_to: "extendTo"
+});
+txrep_struct!(xdr::RestoreFootprintOp { ext: "ext" });
+txrep_union!(xdr::ExtensionPoint, "v", { V0 = "0" }, {});
+
+txrep_union!(
+    xdr::LedgerKey,
+    "type",
+    {},
+    {
+        Account("account") = "ACCOUNT",
+        Trustline("trustLine") = "TRUSTLINE",
+        Offer("offer") = "OFFER",
+        Data("data") = "DATA",
+        ClaimableBalance("claimableBalance") = "CLAIMABLE_BALANCE",
+        LiquidityPool("liquidityPool") = "LIQUIDITY_POOL",
+        ContractData("contractData") = "CONTRACT_DATA",
+        ContractCode("contractCode") = "CONTRACT_CODE",
+        ConfigSetting("configSetting") = "CONFIG_SETTING",
+        Ttl("ttl") = "TTL",
+    },
+);
+txrep_struct!(xdr::LedgerKeyAccount {
+    account_id: "accountID"
+});
+txrep_struct!(xdr::LedgerKeyTrustLine {
+    account_id: "accountID",
+    asset: "asset"
+});
+txrep_struct!(xdr::LedgerKeyOffer {
+    seller_id: "sellerID",
+    offer_id: "offerID"
+});
+txrep_struct!(xdr::LedgerKeyData {
+    account_id: "accountID",
+    data_name: "dataName"
+});
+txrep_struct!(xdr::LedgerKeyClaimableBalance {
+    balance_id: "balanceID"
+});
+txrep_struct!(xdr::LedgerKeyLiquidityPool {
+    liquidity_pool_id: "liquidityPoolID"
+});
+txrep_struct!(xdr::LedgerKeyContractData {
+    contract: "contract",
+    key: "key",
+    durability: "durability",
+});
+txrep_struct!(xdr::LedgerKeyContractCode { hash: "hash" });
+txrep_struct!(xdr::LedgerKeyConfigSetting {
+    config_setting_id: "configSettingID"
+});
+txrep_struct!(xdr::LedgerKeyTtl {
+    key_hash: "keyHash"
+});
+txrep_enum!(xdr::ContractDataDurability { Temporary = "TEMPORARY", Persistent = "PERSISTENT" });
+txrep_enum!(xdr::ConfigSettingId {
+    ContractMaxSizeBytes = "CONFIG_SETTING_CONTRACT_MAX_SIZE_BYTES",
+    ContractComputeV0 = "CONFIG_SETTING_CONTRACT_COMPUTE_V0",
+    ContractLedgerCostV0 = "CONFIG_SETTING_CONTRACT_LEDGER_COST_V0",
+    ContractHistoricalDataV0 = "CONFIG_SETTING_CONTRACT_HISTORICAL_DATA_V0",
+    ContractEventsV0 = "CONFIG_SETTING_CONTRACT_EVENTS_V0",
+    ContractBandwidthV0 = "CONFIG_SETTING_CONTRACT_BANDWIDTH_V0",
+    ContractCostParamsCpuInstructions = "CONFIG_SETTING_CONTRACT_COST_PARAMS_CPU_INSTRUCTIONS",
+    ContractCostParamsMemoryBytes = "CONFIG_SETTING_CONTRACT_COST_PARAMS_MEMORY_BYTES",
+    ContractDataKeySizeBytes = "CONFIG_SETTING_CONTRACT_DATA_KEY_SIZE_BYTES",
+    ContractDataEntrySizeBytes = "CONFIG_SETTING_CONTRACT_DATA_ENTRY_SIZE_BYTES",
+    StateArchival = "CONFIG_SETTING_STATE_ARCHIVAL",
+    ContractExecutionLanes = "CONFIG_SETTING_CONTRACT_EXECUTION_LANES",
+    LiveSorobanStateSizeWindow = "CONFIG_SETTING_LIVE_SOROBAN_STATE_SIZE_WINDOW",
+    EvictionIterator = "CONFIG_SETTING_EVICTION_ITERATOR",
+    ContractParallelComputeV0 = "CONFIG_SETTING_CONTRACT_PARALLEL_COMPUTE_V0",
+    ContractLedgerCostExtV0 = "CONFIG_SETTING_CONTRACT_LEDGER_COST_EXT_V0",
+    ScpTiming = "CONFIG_SETTING_SCP_TIMING",
+});
+
+txrep_union!(
+    xdr::HostFunction,
+    "type",
+    {},
+    {
+        InvokeContract("invokeContract") = "HOST_FUNCTION_TYPE_INVOKE_CONTRACT",
+        CreateContract("createContract") = "HOST_FUNCTION_TYPE_CREATE_CONTRACT",
+        UploadContractWasm("wasm") = "HOST_FUNCTION_TYPE_UPLOAD_CONTRACT_WASM",
+        CreateContractV2("createContractV2") = "HOST_FUNCTION_TYPE_CREATE_CONTRACT_V2",
+    },
+);
+txrep_struct!(xdr::InvokeContractArgs {
+    contract_address: "contractAddress",
+    function_name: "functionName",
+    args: "args",
+});
+txrep_struct!(xdr::CreateContractArgs {
+    contract_id_preimage: "contractIDPreimage",
+    executable: "executable",
+});
+txrep_struct!(xdr::CreateContractArgsV2 {
+    contract_id_preimage: "contractIDPreimage",
+    executable: "executable",
+    constructor_args: "constructorArgs",
+});
+txrep_union!(
+    xdr::ContractIdPreimage,
+    "type",
+    {},
+    {
+        Address("fromAddress") = "CONTRACT_ID_PREIMAGE_FROM_ADDRESS",
+        Asset("fromAsset") = "CONTRACT_ID_PREIMAGE_FROM_ASSET",
+    },
+);
+txrep_struct!(xdr::ContractIdPreimageFromAddress {
+    address: "address",
+    salt: "salt"
+});
+txrep_union!(
+    xdr::ContractExecutable,
+    "type",
+    { StellarAsset = "CONTRACT_EXECUTABLE_STELLAR_ASSET" },
+    { Wasm("wasm_hash") = "CONTRACT_EXECUTABLE_WASM" },
+);
+
+txrep_union!(
+    xdr::ScVal,
+    "type",
+    { Void = "SCV_VOID", LedgerKeyContractInstance = "SCV_LEDGER_KEY_CONTRACT_INSTANCE" },
+    {
+        Bool("b") = "SCV_BOOL",
+        Error("error") = "SCV_ERROR",
+        U32("u32") = "SCV_U32",
+        I32("i32") = "SCV_I32",
+        U64("u64") = "SCV_U64",
+        I64("i64") = "SCV_I64",
+        Timepoint("timepoint") = "SCV_TIMEPOINT",
+        Duration("duration") = "SCV_DURATION",
+        U128("u128") = "SCV_U128",
+        I128("i128") = "SCV_I128",
+        U256("u256") = "SCV_U256",
+        I256("i256") = "SCV_I256",
+        Bytes("bytes") = "SCV_BYTES",
+        String("str") = "SCV_STRING",
+        Symbol("sym") = "SCV_SYMBOL",
+        Vec("vec") = "SCV_VEC",
+        Map("map") = "SCV_MAP",
+        Address("address") = "SCV_ADDRESS",
+        ContractInstance("instance") = "SCV_CONTRACT_INSTANCE",
+        LedgerKeyNonce("nonce_key") = "SCV_LEDGER_KEY_NONCE",
+    },
+);
+txrep_union!(
+    xdr::ScError,
+    "type",
+    {},
+    {
+        Contract("contractCode") = "SCE_CONTRACT",
+        WasmVm("code") = "SCE_WASM_VM",
+        Context("code") = "SCE_CONTEXT",
+        Storage("code") = "SCE_STORAGE",
+        Object("code") = "SCE_OBJECT",
+        Crypto("code") = "SCE_CRYPTO",
+        Events("code") = "SCE_EVENTS",
+        Budget("code") = "SCE_BUDGET",
+        Value("code") = "SCE_VALUE",
+        Auth("code") = "SCE_AUTH",
+    },
+);
+txrep_enum!(xdr::ScErrorCode {
+    ArithDomain = "SCEC_ARITH_DOMAIN",
+    IndexBounds = "SCEC_INDEX_BOUNDS",
+    InvalidInput = "SCEC_INVALID_INPUT",
+    MissingValue = "SCEC_MISSING_VALUE",
+    ExistingValue = "SCEC_EXISTING_VALUE",
+    ExceededLimit = "SCEC_EXCEEDED_LIMIT",
+    InvalidAction = "SCEC_INVALID_ACTION",
+    InternalError = "SCEC_INTERNAL_ERROR",
+    UnexpectedType = "SCEC_UNEXPECTED_TYPE",
+    UnexpectedSize = "SCEC_UNEXPECTED_SIZE",
+});
+txrep_struct!(xdr::ScMapEntry {
+    key: "key",
+    val: "val"
+});
+txrep_struct!(xdr::ScNonceKey { nonce: "nonce" });
+txrep_struct!(xdr::ScContractInstance {
+    executable: "executable",
+    storage: "storage"
+});
+txrep_struct!(xdr::UInt128Parts { hi: "hi", lo: "lo" });
+txrep_struct!(xdr::Int128Parts { hi: "hi", lo: "lo" });
+txrep_struct!(xdr::UInt256Parts {
+    hi_hi: "hi_hi",
+    hi_lo: "hi_lo",
+    lo_hi: "lo_hi",
+    lo_lo: "lo_lo",
+});
+txrep_struct!(xdr::Int256Parts {
+    hi_hi: "hi_hi",
+    hi_lo: "hi_lo",
+    lo_hi: "lo_hi",
+    lo_lo: "lo_lo",
+});
+
+txrep_struct!(xdr::SorobanAuthorizationEntry {
+    credentials: "credentials",
+    root_invocation: "rootInvocation",
+});
+txrep_union!(
+    xdr::SorobanCredentials,
+    "type",
+    { SourceAccount = "SOROBAN_CREDENTIALS_SOURCE_ACCOUNT" },
+    { Address("address") = "SOROBAN_CREDENTIALS_ADDRESS" },
+);
+txrep_struct!(xdr::SorobanAddressCredentials {
+    address: "address",
+    nonce: "nonce",
+    signature_expiration_ledger: "signatureExpirationLedger",
+    signature: "signature",
+});
+txrep_struct!(xdr::SorobanAuthorizedInvocation {
+    function: "function",
+    sub_invocations: "subInvocations",
+});
+txrep_union!(
+    xdr::SorobanAuthorizedFunction,
+    "type",
+    {},
+    {
+        ContractFn("contractFn") = "SOROBAN_AUTHORIZED_FUNCTION_TYPE_CONTRACT_FN",
+        CreateContractHostFn("createContractHostFn") =
+            "SOROBAN_AUTHORIZED_FUNCTION_TYPE_CREATE_CONTRACT_HOST_FN",
+        CreateContractV2HostFn("createContractV2HostFn") =
+            "SOROBAN_AUTHORIZED_FUNCTION_TYPE_CREATE_CONTRACT_V2_HOST_FN",
+    },
+);
+
+txrep_struct!(xdr::SorobanTransactionData {
+    ext: "ext",
+    resources: "resources",
+    resource_fee: "resourceFee",
+});
+txrep_union!(xdr::SorobanTransactionDataExt, "v", { V0 = "0" }, {
+    V1("resourceExt") = "1"
+},);
+txrep_struct!(xdr::SorobanResourcesExtV0 {
+    archived_soroban_entries: "archivedSorobanEntries"
+});
+txrep_struct!(xdr::SorobanResources {
+    footprint: "footprint",
+    instructions: "instructions",
+    disk_read_bytes: "diskReadBytes",
+    write_bytes: "writeBytes",
+});
+txrep_struct!(xdr::LedgerFootprint {
+    read_only: "readOnly",
+    read_write: "readWrite"
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xdr::{ReadXdr, WriteXdr};
+
+    const SOURCE: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const DESTINATION: &str = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+
+    fn account(address: &str) -> xdr::AccountId {
+        xdr::AccountId::from_str(address).unwrap()
+    }
+
+    fn muxed(address: &str) -> xdr::MuxedAccount {
+        xdr::MuxedAccount::from_str(address).unwrap()
+    }
+
+    fn usd() -> xdr::Asset {
+        xdr::Asset::CreditAlphanum4(xdr::AlphaNum4 {
+            asset_code: xdr::AssetCode4(*b"USD\0"),
+            issuer: account(SOURCE),
+        })
+    }
+
+    fn op(body: xdr::OperationBody) -> xdr::Operation {
+        xdr::Operation {
+            source_account: None,
+            body,
+        }
+    }
+
+    fn envelope(
+        operations: Vec<xdr::Operation>,
+        ext: xdr::TransactionExt,
+    ) -> xdr::TransactionEnvelope {
+        xdr::TransactionEnvelope::Tx(xdr::TransactionV1Envelope {
+            tx: xdr::Transaction {
+                source_account: muxed(SOURCE),
+                fee: 100,
+                seq_num: xdr::SequenceNumber(46489056724385793),
+                cond: xdr::Preconditions::Time(xdr::TimeBounds {
+                    min_time: xdr::TimePoint(1535756672),
+                    max_time: xdr::TimePoint(1567292672),
+                }),
+                memo: xdr::Memo::Text("Enjoy this transaction".try_into().unwrap()),
+                operations: operations.try_into().unwrap(),
+                ext,
+            },
+            signatures: vec![xdr::DecoratedSignature {
+                hint: xdr::SignatureHint([0x4a, 0xa0, 0x7e, 0xd0]),
+                signature: xdr::Signature(vec![0xde; 64].try_into().unwrap()),
+            }]
+            .try_into()
+            .unwrap(),
+        })
+    }
+
+    fn assert_round_trip(envelope: &xdr::TransactionEnvelope) {
+        let txrep = to_txrep(envelope);
+        let parsed = from_txrep(&txrep).unwrap_or_else(|e| panic!("{}\n{}", e, txrep));
+
+        assert_eq!(
+            parsed.to_xdr(xdr::Limits::none()).unwrap(),
+            envelope.to_xdr(xdr::Limits::none()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_payment_matches_sep_11_example() {
+        let envelope = envelope(
+            vec![op(xdr::OperationBody::Payment(xdr::PaymentOp {
+                destination: muxed(DESTINATION),
+                asset: usd(),
+                amount: 400004000,
+            }))],
+            xdr::TransactionExt::V0,
+        );
+
+        let expected = format!(
+            "type: ENVELOPE_TYPE_TX
+tx.sourceAccount: {source}
+tx.fee: 100
+tx.seqNum: 46489056724385793
+tx.cond.type: PRECOND_TIME
+tx.cond.timeBounds.minTime: 1535756672
+tx.cond.timeBounds.maxTime: 1567292672
+tx.memo.type: MEMO_TEXT
+tx.memo.text: \"Enjoy this transaction\"
+tx.operations.len: 1
+tx.operations[0].sourceAccount._present: false
+tx.operations[0].body.type: PAYMENT
+tx.operations[0].body.paymentOp.destination: {destination}
+tx.operations[0].body.paymentOp.asset: USD:{source}
+tx.operations[0].body.paymentOp.amount: 400004000
+tx.ext.v: 0
+signatures.len: 1
+signatures[0].hint: 4aa07ed0
+signatures[0].signature: {signature}
+",
+            source = SOURCE,
+            destination = DESTINATION,
+            signature = "de".repeat(64)
+        );
+
+        assert_eq!(to_txrep(&envelope), expected);
+        assert_round_trip(&envelope);
+    }
+
+    #[test]
+    fn test_parses_comments_and_any_line_order() {
+        let txrep = format!(
+            "// a payment
+signatures.len: 0
+type: ENVELOPE_TYPE_TX
+tx.fee: 100 (0.00001 XLM)
+tx.sourceAccount: {source}
+tx.seqNum: 1
+tx.cond.type: PRECOND_NONE
+tx.memo.type: MEMO_NONE
+tx.operations.len: 1
+tx.operations[0].sourceAccount._present: true
+tx.operations[0].sourceAccount: {destination}
+tx.operations[0].body.type: BUMP_SEQUENCE
+tx.operations[0].body.bumpSequenceOp.bumpTo: 10
+tx.ext.v: 0
+",
+            source = SOURCE,
+            destination = DESTINATION
+        );
+
+        let xdr::TransactionEnvelope::Tx(envelope) = from_txrep(&txrep).unwrap() else {
+            panic!("expected a v1 envelope");
+        };
+        assert_eq!(envelope.tx.fee, 100);
+        assert_eq!(
+            envelope.tx.operations[0].source_account,
+            Some(muxed(DESTINATION))
+        );
+    }
+
+    #[test]
+    fn test_classic_operations_round_trip() {
+        let pool = xdr::PoolId(xdr::Hash([7; 32]));
+        let twelve = xdr::Asset::CreditAlphanum12(xdr::AlphaNum12 {
+            asset_code: xdr::AssetCode12(*b"LONGCODE\0\0\0\0"),
+            issuer: account(DESTINATION),
+        });
+        let predicate = xdr::ClaimPredicate::And(
+            vec![
+                xdr::ClaimPredicate::Not(Some(Box::new(xdr::ClaimPredicate::BeforeRelativeTime(
+                    60,
+                )))),
+                xdr::ClaimPredicate::BeforeAbsoluteTime(1_700_000_000),
+            ]
+            .try_into()
+            .unwrap(),
+        );
+        let operations = vec![
+            op(xdr::OperationBody::CreateAccount(xdr::CreateAccountOp {
+                destination: account(DESTINATION),
+                starting_balance: 10,
+            })),
+            op(xdr::OperationBody::PathPaymentStrictReceive(
+                xdr::PathPaymentStrictReceiveOp {
+                    send_asset: xdr::Asset::Native,
+                    send_max: 5,
+                    destination: muxed(DESTINATION),
+                    dest_asset: usd(),
+                    dest_amount: 1,
+                    path: vec![twelve.clone()].try_into().unwrap(),
+                },
+            )),
+            op(xdr::OperationBody::PathPaymentStrictSend(
+                xdr::PathPaymentStrictSendOp {
+                    send_asset: usd(),
+                    send_amount: 5,
+                    destination: muxed(DESTINATION),
+                    dest_asset: xdr::Asset::Native,
+                    dest_min: 1,
+                    path: vec![].try_into().unwrap(),
+                },
+            )),
+            op(xdr::OperationBody::ManageSellOffer(
+                xdr::ManageSellOfferOp {
+                    selling: usd(),
+                    buying: twelve.clone(),
+                    amount: 1,
+                    price: xdr::Price { n: 1, d: 2 },
+                    offer_id: 0,
+                },
+            )),
+            op(xdr::OperationBody::ManageBuyOffer(xdr::ManageBuyOfferOp {
+                selling: usd(),
+                buying: xdr::Asset::Native,
+                buy_amount: 1,
+                price: xdr::Price { n: 3, d: 2 },
+                offer_id: 12,
+            })),
+            op(xdr::OperationBody::CreatePassiveSellOffer(
+                xdr::CreatePassiveSellOfferOp {
+                    selling: xdr::Asset::Native,
+                    buying: usd(),
+                    amount: 1,
+                    price: xdr::Price { n: 1, d: 1 },
+                },
+            )),
+            op(xdr::OperationBody::SetOptions(xdr::SetOptionsOp {
+                inflation_dest: Some(account(DESTINATION)),
+                clear_flags: Some(1),
+                set_flags: None,
+                master_weight: Some(0),
+                low_threshold: None,
+                med_threshold: None,
+                high_threshold: Some(2),
+                home_domain: Some(xdr::String32("stellar.org \"\x01".try_into().unwrap())),
+                signer: Some(xdr::Signer {
+                    key: xdr::SignerKey::HashX(xdr::Uint256([3; 32])),
+                    weight: 1,
+                }),
+            })),
+            op(xdr::OperationBody::ChangeTrust(xdr::ChangeTrustOp {
+                line: xdr::ChangeTrustAsset::PoolShare(
+                    xdr::LiquidityPoolParameters::LiquidityPoolConstantProduct(
+                        xdr::LiquidityPoolConstantProductParameters {
+                            asset_a: xdr::Asset::Native,
+                            asset_b: usd(),
+                            fee: 30,
+                        },
+                    ),
+                ),
+                limit: i64::MAX,
+            })),
+            op(xdr::OperationBody::AllowTrust(xdr::AllowTrustOp {
+                trustor: account(DESTINATION),
+                asset: xdr::AssetCode::CreditAlphanum4(xdr::AssetCode4(*b"USD\0")),
+                authorize: 1,
+            })),
+            op(xdr::OperationBody::AccountMerge(muxed(
+                "MA7QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJVAAAAAAAAAAAAAJLK",
+            ))),
+            op(xdr::OperationBody::Inflation),
+            op(xdr::OperationBody::ManageData(xdr::ManageDataOp {
+                data_name: xdr::String64("name".try_into().unwrap()),
+                data_value: Some(xdr::DataValue(vec![0, 1, 2].try_into().unwrap())),
+            })),
+            op(xdr::OperationBody::BumpSequence(xdr::BumpSequenceOp {
+                bump_to: xdr::SequenceNumber(-1),
+            })),
+            op(xdr::OperationBody::CreateClaimableBalance(
+                xdr::CreateClaimableBalanceOp {
+                    asset: usd(),
+                    amount: 10,
+                    claimants: vec![xdr::Claimant::ClaimantTypeV0(xdr::ClaimantV0 {
+                        destination: account(DESTINATION),
+                        predicate,
+                    })]
+                    .try_into()
+                    .unwrap(),
+                },
+            )),
+            op(xdr::OperationBody::ClaimClaimableBalance(
+                xdr::ClaimClaimableBalanceOp {
+                    balance_id: xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(
+                        [1; 32],
+                    )),
+                },
+            )),
+            op(xdr::OperationBody::BeginSponsoringFutureReserves(
+                xdr::BeginSponsoringFutureReservesOp {
+                    sponsored_id: account(DESTINATION),
+                },
+            )),
+            op(xdr::OperationBody::EndSponsoringFutureReserves),
+            op(xdr::OperationBody::RevokeSponsorship(
+                xdr::RevokeSponsorshipOp::LedgerEntry(xdr::LedgerKey::Trustline(
+                    xdr::LedgerKeyTrustLine {
+                        account_id: account(DESTINATION),
+                        asset: xdr::TrustLineAsset::PoolShare(pool.clone()),
+                    },
+                )),
+            )),
+            op(xdr::OperationBody::RevokeSponsorship(
+                xdr::RevokeSponsorshipOp::Signer(xdr::RevokeSponsorshipOpSigner {
+                    account_id: account(DESTINATION),
+                    signer_key: xdr::SignerKey::PreAuthTx(xdr::Uint256([4; 32])),
+                }),
+            )),
+            op(xdr::OperationBody::Clawback(xdr::ClawbackOp {
+                asset: usd(),
+                from: muxed(DESTINATION),
+                amount: 3,
+            })),
+            op(xdr::OperationBody::ClawbackClaimableBalance(
+                xdr::ClawbackClaimableBalanceOp {
+                    balance_id: xdr::ClaimableBalanceId::ClaimableBalanceIdTypeV0(xdr::Hash(
+                        [2; 32],
+                    )),
+                },
+            )),
+            op(xdr::OperationBody::SetTrustLineFlags(
+                xdr::SetTrustLineFlagsOp {
+                    trustor: account(DESTINATION),
+                    asset: usd(),
+                    clear_flags: 1,
+                    set_flags: 2,
+                },
+            )),
+            op(xdr::OperationBody::LiquidityPoolDeposit(
+                xdr::LiquidityPoolDepositOp {
+                    liquidity_pool_id: pool.clone(),
+                    max_amount_a: 10,
+                    max_amount_b: 20,
+                    min_price: xdr::Price { n: 1, d: 3 },
+                    max_price: xdr::Price { n: 3, d: 1 },
+                },
+            )),
+            op(xdr::OperationBody::LiquidityPoolWithdraw(
+                xdr::LiquidityPoolWithdrawOp {
+                    liquidity_pool_id: pool,
+                    amount: 10,
+                    min_amount_a: 1,
+                    min_amount_b: 2,
+                },
+            )),
+        ];
+
+        assert_round_trip(&envelope(operations, xdr::TransactionExt::V0));
+    }
+
+    #[test]
+    fn test_soroban_transaction_round_trips() {
+        let contract = xdr::ScAddress::Contract(xdr::ContractId(xdr::Hash([5; 32])));
+        let args = xdr::InvokeContractArgs {
+            contract_address: contract.clone(),
+            function_name: xdr::ScSymbol("transfer".try_into().unwrap()),
+            args: vec![
+                xdr::ScVal::Address(xdr::ScAddress::Account(account(SOURCE))),
+                xdr::ScVal::I128(xdr::Int128Parts { hi: -1, lo: 5 }),
+                xdr::ScVal::Vec(Some(xdr::ScVec(
+                    vec![xdr::ScVal::Bool(true), xdr::ScVal::Void]
+                        .try_into()
+                        .unwrap(),
+                ))),
+                xdr::ScVal::Map(Some(xdr::ScMap(
+                    vec![xdr::ScMapEntry {
+                        key: xdr::ScVal::Symbol(xdr::ScSymbol("k".try_into().unwrap())),
+                        val: xdr::ScVal::Bytes(xdr::ScBytes(vec![].try_into().unwrap())),
+                    }]
+                    .try_into()
+                    .unwrap(),
+                ))),
+                xdr::ScVal::Error(xdr::ScError::Auth(xdr::ScErrorCode::InvalidAction)),
+                xdr::ScVal::U256(xdr::UInt256Parts {
+                    hi_hi: 1,
+                    hi_lo: 2,
+                    lo_hi: 3,
+                    lo_lo: 4,
+                }),
+            ]
+            .try_into()
+            .unwrap(),
+        };
+        let auth = xdr::SorobanAuthorizationEntry {
+            credentials: xdr::SorobanCredentials::Address(xdr::SorobanAddressCredentials {
+                address: xdr::ScAddress::Account(account(DESTINATION)),
+                nonce: 42,
+                signature_expiration_ledger: 100,
+                signature: xdr::ScVal::Vec(None),
+            }),
+            root_invocation: xdr::SorobanAuthorizedInvocation {
+                function: xdr::SorobanAuthorizedFunction::ContractFn(args.clone()),
+                sub_invocations: vec![].try_into().unwrap(),
+            },
+        };
+        let data = xdr::SorobanTransactionData {
+            ext: xdr::SorobanTransactionDataExt::V1(xdr::SorobanResourcesExtV0 {
+                archived_soroban_entries: vec![0].try_into().unwrap(),
+            }),
+            resources: xdr::SorobanResources {
+                footprint: xdr::LedgerFootprint {
+                    read_only: vec![xdr::LedgerKey::ContractCode(xdr::LedgerKeyContractCode {
+                        hash: xdr::Hash([6; 32]),
+                    })]
+                    .try_into()
+                    .unwrap(),
+                    read_write: vec![xdr::LedgerKey::ContractData(xdr::LedgerKeyContractData {
+                        contract,
+                        key: xdr::ScVal::LedgerKeyContractInstance,
+                        durability: xdr::ContractDataDurability::Persistent,
+                    })]
+                    .try_into()
+                    .unwrap(),
+                },
+                instructions: 1000,
+                disk_read_bytes: 200,
+                write_bytes: 300,
+            },
+            resource_fee: 5000,
+        };
+        let operations = vec![
+            op(xdr::OperationBody::InvokeHostFunction(
+                xdr::InvokeHostFunctionOp {
+                    host_function: xdr::HostFunction::InvokeContract(args),
+                    auth: vec![auth].try_into().unwrap(),
+                },
+            )),
+            op(xdr::OperationBody::InvokeHostFunction(
+                xdr::InvokeHostFunctionOp {
+                    host_function: xdr::HostFunction::CreateContractV2(xdr::CreateContractArgsV2 {
+                        contract_id_preimage: xdr::ContractIdPreimage::Address(
+                            xdr::ContractIdPreimageFromAddress {
+                                address: xdr::ScAddress::Account(account(SOURCE)),
+                                salt: xdr::Uint256([9; 32]),
+                            },
+                        ),
+                        executable: xdr::ContractExecutable::Wasm(xdr::Hash([8; 32])),
+                        constructor_args: vec![xdr::ScVal::U32(1)].try_into().unwrap(),
+                    }),
+                    auth: vec![].try_into().unwrap(),
+                },
+            )),
+            op(xdr::OperationBody::InvokeHostFunction(
+                xdr::InvokeHostFunctionOp {
+                    host_function: xdr::HostFunction::UploadContractWasm(
+                        vec![0, 97, 115, 109].try_into().unwrap(),
+                    ),
+                    auth: vec![].try_into().unwrap(),
+                },
+            )),
+            op(xdr::OperationBody::ExtendFootprintTtl(
+                xdr::ExtendFootprintTtlOp {
+                    ext: xdr::ExtensionPoint::V0,
+                    extend_to: 1000,
+                },
+            )),
+            op(xdr::OperationBody::RestoreFootprint(
+                xdr::RestoreFootprintOp {
+                    ext: xdr::ExtensionPoint::V0,
+                },
+            )),
+        ];
+
+        let envelope = envelope(operations, xdr::TransactionExt::V1(data));
+        let txrep = to_txrep(&envelope);
+        assert!(txrep.contains("tx.ext.sorobanData.ext.resourceExt.archivedSorobanEntries[0]: 0\n"));
+        assert!(txrep.contains(".durability: PERSISTENT\n"));
+        assert_round_trip(&envelope);
+    }
+
+    #[test]
+    fn test_fee_bump_and_v0_envelopes_round_trip() {
+        let xdr::TransactionEnvelope::Tx(inner) = envelope(
+            vec![op(xdr::OperationBody::Inflation)],
+            xdr::TransactionExt::V0,
+        ) else {
+            unreachable!()
+        };
+        let fee_bump = xdr::TransactionEnvelope::TxFeeBump(xdr::FeeBumpTransactionEnvelope {
+            tx: xdr::FeeBumpTransaction {
+                fee_source: muxed(DESTINATION),
+                fee: 400,
+                inner_tx: xdr::FeeBumpTransactionInnerTx::Tx(inner.clone()),
+                ext: xdr::FeeBumpTransactionExt::V0,
+            },
+            signatures: vec![].try_into().unwrap(),
+        });
+        let txrep = to_txrep(&fee_bump);
+        assert!(txrep.starts_with("type: ENVELOPE_TYPE_TX_FEE_BUMP\nfeeBump.tx.feeSource: "));
+        assert!(txrep.contains("feeBump.tx.innerTx.v1.tx.sourceAccount: "));
+        assert_round_trip(&fee_bump);
+
+        let v0 = xdr::TransactionEnvelope::TxV0(xdr::TransactionV0Envelope {
+            tx: xdr::TransactionV0 {
+                source_account_ed25519: xdr::Uint256([1; 32]),
+                fee: 100,
+                seq_num: xdr::SequenceNumber(1),
+                time_bounds: None,
+                memo: xdr::Memo::Return(xdr::Hash([2; 32])),
+                operations: inner.tx.operations,
+                ext: xdr::TransactionV0Ext::V0,
+            },
+            signatures: vec![].try_into().unwrap(),
+        });
+        assert_round_trip(&v0);
+    }
+
+    #[test]
+    fn test_invalid_txrep() {
+        assert_eq!(from_txrep("").unwrap_err(), "type is missing");
+        assert_eq!(
+            from_txrep("type: ENVELOPE_TYPE_TX\ntype: ENVELOPE_TYPE_TX").unwrap_err(),
+            "type is set more than once"
+        );
+        assert_eq!(
+            from_txrep("type: ENVELOPE_TYPE_SCP").unwrap_err(),
+            "type ENVELOPE_TYPE_SCP is not supported"
+        );
+        assert_eq!(
+            from_txrep("type: ENVELOPE_TYPE_TX\ntx.sourceAccount: GABC").unwrap_err(),
+            "tx.sourceAccount is invalid"
+        );
+        assert!(from_txrep("not a txrep").is_err());
+    }
+}
@@ -0,0 +1,400 @@
+//! Readers for the XDR streams written by captive core, Galexie and history archives.
+//!
+//! Streams are record-marked as in RFC 5531: each record is split into fragments, each preceded
+//! by a 4-byte header holding a last-fragment bit and a 31-bit length. Files are usually
+//! compressed (gzip for history archives, zstd for Galexie), decompress them before reading.
+use std::io::Read;
+use std::marker::PhantomData;
+
+use crate::xdr::{self, Limits, ReadXdr};
+
+const LAST_FRAGMENT: u32 = 1 << 31;
+
+/// Reads the records of a record-marked XDR stream one at a time.
+pub struct XdrStreamReader<R: Read, T: ReadXdr> {
+    reader: R,
+    limits: Limits,
+    done: bool,
+    _item: PhantomData<T>,
+}
+
+/// Captive core's meta stream of closed ledgers.
+pub type LedgerCloseMetaReader<R> = XdrStreamReader<R, xdr::LedgerCloseMeta>;
+/// A history archive `ledger-*.xdr` checkpoint file.
+pub type LedgerHeaderReader<R> = XdrStreamReader<R, xdr::LedgerHeaderHistoryEntry>;
+/// A history archive `transactions-*.xdr` checkpoint file.
+pub type TransactionHistoryReader<R> = XdrStreamReader<R, xdr::TransactionHistoryEntry>;
+/// A history archive `results-*.xdr` checkpoint file.
+pub type TransactionResultReader<R> = XdrStreamReader<R, xdr::TransactionHistoryResultEntry>;
+
+// Define a trait for XdrStreamReader behavior
+pub trait XdrStreamReaderBehavior<R: Read> {
+    fn new(reader: R) -> Self;
+    fn with_limits(reader: R, limits: Limits) -> Self;
+    fn into_inner(self) -> R;
+}
+
+/// Fills `buf`, returning `false` if the stream ended before any byte was read.
+fn read_header(reader: &mut impl Read, buf: &mut [u8]) -> Result<bool, String> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err("stream ends in the middle of a record header".to_string()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(true)
+}
+
+impl<R: Read, T: ReadXdr> XdrStreamReader<R, T> {
+    /// Reads the fragments of the next record, or `None` at the end of the stream.
+    fn read_record(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let mut record = Vec::new();
+        loop {
+            let mut header = [0; 4];
+            if !read_header(&mut self.reader, &mut header)? {
+                if record.is_empty() {
+                    return Ok(None);
+                }
+                return Err("stream ends before the last fragment of a record".to_string());
+            }
+            let header = u32::from_be_bytes(header);
+            let len = u64::from(header & !LAST_FRAGMENT);
+            // Reading through `take` only allocates for bytes that are actually there.
+            let read = (&mut self.reader)
+                .take(len)
+                .read_to_end(&mut record)
+                .map_err(|e| e.to_string())?;
+            if read as u64 != len {
+                return Err("stream ends in the middle of a record".to_string());
+            }
+            if header & LAST_FRAGMENT != 0 {
+                return Ok(Some(record));
+            }
+        }
+    }
+}
+
+impl<R: Read, T: ReadXdr> XdrStreamReaderBehavior<R> for XdrStreamReader<R, T> {
+    fn new(reader: R) -> Self {
+        Self::with_limits(reader, Limits::none())
+    }
+
+    fn with_limits(reader: R, limits: Limits) -> Self {
+        Self {
+            reader,
+            limits,
+            done: false,
+            _item: PhantomData,
+        }
+    }
+
+    fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read, T: ReadXdr> Iterator for XdrStreamReader<R, T> {
+    type Item = Result<T, String>;
+
+    /// Yields the next record, stopping after the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = match self.read_record() {
+            Ok(None) => None,
+            Ok(Some(record)) => Some(
+                T::from_xdr(record, self.limits.clone())
+                    .map_err(|e| format!("invalid record: {}", e)),
+            ),
+            Err(e) => Some(Err(e)),
+        };
+        self.done = !matches!(item, Some(Ok(_)));
+        item
+    }
+}
+
+/// Reads a Galexie data file, a single `LedgerCloseMetaBatch` without record marks.
+pub fn read_ledger_close_meta_batch(
+    mut reader: impl Read,
+) -> Result<xdr::LedgerCloseMetaBatch, String> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    xdr::LedgerCloseMetaBatch::from_xdr(bytes, Limits::none())
+        .map_err(|e| format!("invalid ledger close meta batch: {}", e))
+}
+
+fn generalized_envelopes(
+    tx_set: &xdr::GeneralizedTransactionSet,
+) -> Vec<&xdr::TransactionEnvelope> {
+    let xdr::GeneralizedTransactionSet::V1(tx_set) = tx_set;
+    let mut envelopes = Vec::new();
+    for phase in tx_set.phases.iter() {
+        match phase {
+            xdr::TransactionPhase::V0(components) => {
+                for component in components.iter() {
+                    let xdr::TxSetComponent::TxsetCompTxsMaybeDiscountedFee(component) = component;
+                    envelopes.extend(component.txs.iter());
+                }
+            }
+            xdr::TransactionPhase::V1(parallel) => {
+                for stage in parallel.execution_stages.iter() {
+                    for cluster in stage.0.iter() {
+                        envelopes.extend(cluster.0.iter());
+                    }
+                }
+            }
+        }
+    }
+    envelopes
+}
+
+/// The sequence number of the ledger `meta` closed.
+pub fn ledger_sequence(meta: &xdr::LedgerCloseMeta) -> u32 {
+    match meta {
+        xdr::LedgerCloseMeta::V0(meta) => meta.ledger_header.header.ledger_seq,
+        xdr::LedgerCloseMeta::V1(meta) => meta.ledger_header.header.ledger_seq,
+        xdr::LedgerCloseMeta::V2(meta) => meta.ledger_header.header.ledger_seq,
+    }
+}
+
+/// The envelopes in the transaction set of a closed ledger, in transaction set order.
+pub fn ledger_transaction_envelopes(meta: &xdr::LedgerCloseMeta) -> Vec<&xdr::TransactionEnvelope> {
+    match meta {
+        xdr::LedgerCloseMeta::V0(meta) => meta.tx_set.txs.iter().collect(),
+        xdr::LedgerCloseMeta::V1(meta) => generalized_envelopes(&meta.tx_set),
+        xdr::LedgerCloseMeta::V2(meta) => generalized_envelopes(&meta.tx_set),
+    }
+}
+
+/// The envelopes of a history archive entry, from its generalized transaction set when present.
+pub fn history_transaction_envelopes(
+    entry: &xdr::TransactionHistoryEntry,
+) -> Vec<&xdr::TransactionEnvelope> {
+    match &entry.ext {
+        xdr::TransactionHistoryEntryExt::V0 => entry.tx_set.txs.iter().collect(),
+        xdr::TransactionHistoryEntryExt::V1(tx_set) => generalized_envelopes(tx_set),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, AccountBehavior};
+    use crate::asset::{Asset, AssetBehavior};
+    use crate::network::{NetworkPassphrase, Networks};
+    use crate::operation::Operation;
+    use crate::transaction::{Transaction, TransactionBehavior};
+    use crate::transaction_builder::{TransactionBuilder, TransactionBuilderBehavior};
+    use crate::xdr::WriteXdr;
+
+    const SOURCE: &str = "GBBM6BKZPEHWYO3E3YKREDPQXMS4VK35YLNU7NFBRI26RAN7GI5POFBB";
+    const DESTINATION: &str = "GDJJRRMBK4IWLEPJGIE6SXD2LP7REGZODU7WDC3I2D6MR37F4XSHBKX2";
+
+    /// Writes `value` as a record split into fragments of at most `fragment_size` bytes.
+    fn record(value: &impl WriteXdr, fragment_size: usize) -> Vec<u8> {
+        let bytes = value.to_xdr(Limits::none()).unwrap();
+        let mut stream = Vec::new();
+        let fragments: Vec<&[u8]> = bytes.chunks(fragment_size).collect();
+        for (i, fragment) in fragments.iter().enumerate() {
+            let mut header = fragment.len() as u32;
+            if i == fragments.len() - 1 {
+                header |= LAST_FRAGMENT;
+            }
+            stream.extend(header.to_be_bytes());
+            stream.extend(*fragment);
+        }
+        stream
+    }
+
+    fn header(ledger_seq: u32) -> xdr::LedgerHeaderHistoryEntry {
+        let mut entry = xdr::LedgerHeaderHistoryEntry::default();
+        entry.header.ledger_seq = ledger_seq;
+        entry
+    }
+
+    fn transaction(sequence: &str) -> Transaction {
+        let mut source = Account::new(SOURCE, sequence).unwrap();
+        TransactionBuilder::new(&mut source, Networks::testnet(), None)
+            .fee(100_u32)
+            .add_operation(
+                Operation::new()
+                    .payment(DESTINATION, &Asset::native(), 10)
+                    .unwrap(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_reads_ledger_close_meta_stream() {
+        let metas: Vec<xdr::LedgerCloseMeta> = (7..10)
+            .map(|seq| {
+                xdr::LedgerCloseMeta::V0(xdr::LedgerCloseMetaV0 {
+                    ledger_header: header(seq),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let stream: Vec<u8> = metas
+            .iter()
+            .enumerate()
+            .flat_map(|(i, meta)| record(meta, 16 * (i + 1)))
+            .collect();
+
+        let read: Vec<xdr::LedgerCloseMeta> = LedgerCloseMetaReader::new(stream.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, metas);
+        assert_eq!(
+            read.iter().map(ledger_sequence).collect::<Vec<_>>(),
+            [7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn test_reads_history_archive_checkpoint() {
+        let stream: Vec<u8> = [record(&header(63), 1024), record(&header(64), 1024)].concat();
+        let headers: Vec<u32> = LedgerHeaderReader::new(stream.as_slice())
+            .map(|entry| entry.unwrap().header.ledger_seq)
+            .collect();
+        assert_eq!(headers, [63, 64]);
+
+        let result = xdr::TransactionHistoryResultEntry {
+            ledger_seq: 64,
+            ..Default::default()
+        };
+        let stream = record(&result, 1024);
+        let mut results = TransactionResultReader::new(stream.as_slice());
+        assert_eq!(results.next(), Some(Ok(result)));
+        assert_eq!(results.next(), None);
+    }
+
+    #[test]
+    fn test_recomputes_transaction_hashes() {
+        let transactions = [transaction("1"), transaction("2")];
+        let envelopes: Vec<xdr::TransactionEnvelope> = transactions
+            .iter()
+            .map(|tx| tx.to_envelope().unwrap())
+            .collect();
+        let entry = xdr::TransactionHistoryEntry {
+            ledger_seq: 64,
+            tx_set: xdr::TransactionSet {
+                previous_ledger_hash: xdr::Hash([0; 32]),
+                txs: envelopes.clone().try_into().unwrap(),
+            },
+            ext: xdr::TransactionHistoryEntryExt::V0,
+        };
+        let stream = record(&entry, 64);
+
+        let entry = TransactionHistoryReader::new(stream.as_slice())
+            .next()
+            .unwrap()
+            .unwrap();
+        let hashes: Vec<[u8; 32]> = history_transaction_envelopes(&entry)
+            .into_iter()
+            .map(|envelope| {
+                Transaction::from_xdr_envelope(
+                    &envelope.to_xdr_base64(Limits::none()).unwrap(),
+                    Networks::testnet(),
+                )
+                .hash()
+            })
+            .collect();
+        assert_eq!(hashes, [transactions[0].hash(), transactions[1].hash()]);
+
+        let tx_set = xdr::GeneralizedTransactionSet::V1(xdr::TransactionSetV1 {
+            previous_ledger_hash: xdr::Hash([0; 32]),
+            phases: vec![
+                xdr::TransactionPhase::V0(
+                    vec![xdr::TxSetComponent::TxsetCompTxsMaybeDiscountedFee(
+                        xdr::TxSetComponentTxsMaybeDiscountedFee {
+                            base_fee: None,
+                            txs: vec![envelopes[0].clone()].try_into().unwrap(),
+                        },
+                    )]
+                    .try_into()
+                    .unwrap(),
+                ),
+                xdr::TransactionPhase::V1(xdr::ParallelTxsComponent {
+                    base_fee: None,
+                    execution_stages: vec![xdr::ParallelTxExecutionStage(
+                        vec![xdr::DependentTxCluster(
+                            vec![envelopes[1].clone()].try_into().unwrap(),
+                        )]
+                        .try_into()
+                        .unwrap(),
+                    )]
+                    .try_into()
+                    .unwrap(),
+                }),
+            ]
+            .try_into()
+            .unwrap(),
+        });
+        let meta = xdr::LedgerCloseMeta::V2(xdr::LedgerCloseMetaV2 {
+            tx_set,
+            ..Default::default()
+        });
+        let ledger_envelopes: Vec<xdr::TransactionEnvelope> = ledger_transaction_envelopes(&meta)
+            .into_iter()
+            .cloned()
+            .collect();
+        assert_eq!(ledger_envelopes, envelopes);
+    }
+
+    #[test]
+    fn test_reports_truncated_and_invalid_records() {
+        let stream = record(&header(1), 1024);
+
+        let mut reader = LedgerHeaderReader::new(&stream[..stream.len() - 1]);
+        assert_eq!(
+            reader.next().unwrap().unwrap_err(),
+            "stream ends in the middle of a record"
+        );
+        assert_eq!(reader.next(), None);
+
+        let mut reader = LedgerHeaderReader::new(&stream[..2]);
+        assert_eq!(
+            reader.next().unwrap().unwrap_err(),
+            "stream ends in the middle of a record header"
+        );
+
+        // A first fragment without the last one.
+        let mut partial = stream.clone();
+        partial[0] &= 0x7f;
+        let mut reader = LedgerHeaderReader::new(partial.as_slice());
+        assert_eq!(
+            reader.next().unwrap().unwrap_err(),
+            "stream ends before the last fragment of a record"
+        );
+
+        let mut reader = TransactionResultReader::new(stream.as_slice());
+        assert!(reader
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .starts_with("invalid record"));
+    }
+
+    #[test]
+    fn test_reads_galexie_batch() {
+        let batch = xdr::LedgerCloseMetaBatch {
+            start_sequence: 5,
+            end_sequence: 5,
+            ledger_close_metas: vec![xdr::LedgerCloseMeta::V1(xdr::LedgerCloseMetaV1 {
+                ledger_header: header(5),
+                ..Default::default()
+            })]
+            .try_into()
+            .unwrap(),
+        };
+        let bytes = batch.to_xdr(Limits::none()).unwrap();
+        let read = read_ledger_close_meta_batch(bytes.as_slice()).unwrap();
+        assert_eq!(ledger_sequence(&read.ledger_close_metas[0]), 5);
+        assert!(read_ledger_close_meta_batch(&bytes[..8]).is_err());
+    }
+}